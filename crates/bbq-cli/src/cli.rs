@@ -1,23 +1,51 @@
 use bbq::{
-    checkout_repo, checkout_repo_with_name, create_worktree, create_worktree_from, default_branch,
-    list_repos, list_worktrees, remove_repo, remove_worktree, resolve_repo, run_post_create_script,
-    run_pre_delete_script, suggest_worktree_name, Repo, ScriptOutput, Worktree,
+    available_worktree_name, checkout_repo_with_options, clone_repo_name, copy_untracked_files,
+    copy_worktree_templates, create_worktree_from_with_options,
+    create_worktree_with_name_and_options, default_branch,
+    find_repo_by_remote, find_worktree, git_changed_files, head_commit_info, head_commit_time,
+    head_divergence, list_repos, list_worktrees, paths, reclone_repo, remove_repo,
+    remove_worktree_with_force, repair_worktrees, repo_remote_url, resolve_repo,
+    run_post_create_script, run_pre_delete_script, suggest_worktree_name,
+    worktree_conflict_state, worktree_diff, worktree_name_from_branch, worktree_upstream_ref,
+    BbqError, CloneOptions, Repo, ScriptOutput, Worktree, WorktreeOptions,
 };
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use clap_complete::ArgValueCandidates;
 use std::collections::HashSet;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use crate::config::{
-    default_branch_name, load_default_worktree_name_mode, load_editor_command,
-    load_terminal_command,
+    apply_repo_git_config, default_branch_name, editor_new_window_enabled, get_config_key,
+    load_default_worktree_name_mode, load_editor_command, load_open_order, load_repo_env,
+    load_restore_state, load_terminal_command, load_worktree_name_template, record_last_opened,
+    set_config_key,
 };
+use crate::color::{bold, dim, ColorChoice};
+use crate::completion::{branch_candidates, print_registration, SHELLS};
+use crate::env_info::env_report;
 use crate::open::{
-    detect_open_targets, normalize_target, open_in_editor, open_in_target,
-    open_terminal_at_path_with_config, OpenTarget,
+    detect_open_targets, detect_open_targets_in_order, normalize_target, open_file_in_editor,
+    open_file_in_target, open_in_editor, open_in_target, open_in_terminal_editor, open_in_tmux,
+    open_terminal_at_path_with_config, resolve_file_target, resolve_open_command, OpenCommand,
+    OpenTarget,
 };
 
 #[derive(Parser)]
 #[command(name = "bbq", version, about = "bbq worktree manager")]
 pub(crate) struct Cli {
+    #[arg(long, global = true)]
+    pub(crate) verbose: bool,
+    /// Skip update checks and gh lookups; clones and fetches fail instead of hanging.
+    #[arg(long, global = true)]
+    pub(crate) offline: bool,
+    /// How to print errors on stderr.
+    #[arg(long, global = true, value_enum, default_value_t = ErrorFormat::Text)]
+    pub(crate) error_format: ErrorFormat,
+    /// When to color output.
+    #[arg(long, global = true, value_enum, default_value_t = ColorChoice::Auto)]
+    pub(crate) color: ColorChoice,
     #[command(subcommand)]
     pub(crate) command: Option<Commands>,
 }
@@ -32,77 +60,384 @@ pub(crate) enum Commands {
         #[command(subcommand)]
         command: WorktreeCommand,
     },
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+    /// Re-open the most recently opened worktree in the editor.
+    Open,
+    /// Move a legacy `~/.bbq` layout to the XDG config and data directories.
+    Migrate {
+        /// Perform the moves; without this only the plan is printed.
+        #[arg(long)]
+        apply: bool,
+    },
+    /// Show the bbq root and detected git/gh versions.
+    Env {
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+    /// Print the bbq version.
+    Version {
+        /// Also print the platform, bbq root, and git/gh versions for bug reports.
+        #[arg(long)]
+        long: bool,
+    },
+    /// Print the shell script that enables completion, e.g. `source <(bbq completions bash)`.
+    Completions {
+        #[arg(value_parser = SHELLS)]
+        shell: String,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum ErrorFormat {
+    Text,
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub(crate) enum OutputFormat {
+    Text,
+    Json,
 }
 
 #[derive(Subcommand)]
 pub(crate) enum RepoCommand {
-    Clone { url: String, name: Option<String> },
-    List,
+    Clone {
+        url: String,
+        name: Option<String>,
+        #[arg(long)]
+        single_branch: bool,
+        #[arg(long, requires = "single_branch")]
+        branch: Option<String>,
+        /// Name the cloned remote instead of `origin`.
+        #[arg(long, value_name = "NAME")]
+        origin: Option<String>,
+        /// Share objects with an existing bbq repo to save disk space.
+        #[arg(long, value_name = "REPO")]
+        reference: Option<String>,
+        /// Clone every ref with `git clone --mirror` instead of `--bare`.
+        #[arg(long, conflicts_with = "single_branch")]
+        mirror: bool,
+        /// Print the repo name bbq would use and exit without cloning.
+        #[arg(long)]
+        print_name: bool,
+        /// Clone even if another repo already has the same remote.
+        #[arg(long)]
+        force: bool,
+        /// Ask for another name on stdin if the repo name is already taken.
+        #[arg(long)]
+        interactive: bool,
+        /// Also create a worktree on the default branch, named NAME or after the branch. The
+        /// name must be attached (`--with-worktree=<name>`) so it isn't taken for the repo name.
+        #[arg(
+            long,
+            value_name = "NAME",
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = ""
+        )]
+        with_worktree: Option<String>,
+    },
+    List {
+        /// Stable `name<TAB>path` output for scripts.
+        #[arg(long)]
+        porcelain: bool,
+    },
+    /// Show a repo's path, remote URL, and worktree count.
+    Info { name: String },
+    /// Delete and re-clone a repo from its remote URL; it must have no worktrees.
+    Reclone { name: String },
     Rm { name: String },
 }
 
+#[derive(Subcommand)]
+pub(crate) enum ConfigCommand {
+    /// Print where bbq reads its config and keeps repos and worktrees.
+    Path,
+    /// Print the effective value of a config option, falling back to its default.
+    Get { key: String },
+    /// Validate and write a config option; lists are given comma-separated.
+    Set { key: String, value: String },
+}
+
 #[derive(Subcommand)]
 pub(crate) enum WorktreeCommand {
     Create {
         repo: String,
-        #[arg(long)]
+        #[arg(long, add = ArgValueCandidates::new(branch_candidates))]
         branch: Option<String>,
+        /// Start the new branch at this commit instead of the source branch tip.
+        #[arg(long, value_name = "COMMIT", requires = "branch")]
+        at: Option<String>,
+        /// If the worktree name is taken, use `<name>-2`, `<name>-3`, ... instead of failing.
+        #[arg(long)]
+        auto_suffix: bool,
+        /// Create one worktree per `name[:source]` line read from stdin.
+        #[arg(long, conflicts_with_all = ["branch", "at"])]
+        stdin: bool,
+        /// Stop at the first failed line instead of continuing.
+        #[arg(long, requires = "stdin")]
+        fail_fast: bool,
+        /// Copy untracked and ignored files (e.g. `.env`) from this worktree into the new one.
+        #[arg(long, value_name = "NAME")]
+        copy_ignored_from: Option<String>,
+        /// Don't set an upstream for the new branch (by default it tracks its remote branch).
+        #[arg(long)]
+        no_track: bool,
+    },
+    List {
+        repo: String,
+        /// Stable `name<TAB>branch<TAB>head<TAB>path` output for scripts.
+        #[arg(long)]
+        porcelain: bool,
+        /// Terminate each porcelain field with NUL instead of separating them with tabs and
+        /// newlines, and skip quoting. Every worktree is four fields.
+        #[arg(short = 'z', requires = "porcelain")]
+        null_terminated: bool,
+        /// Also show each worktree's branch and HEAD commit subject.
+        #[arg(long, conflicts_with = "porcelain")]
+        message: bool,
+        /// Only list worktrees with a commit or change within this window (e.g. `7d`, `24h`).
+        #[arg(long, value_name = "DURATION", value_parser = parse_since)]
+        since: Option<Duration>,
+        /// Only list worktrees tagged with this label (press `l` in the TUI to add labels).
+        #[arg(long, value_name = "LABEL")]
+        label: Option<String>,
+        /// `json` prints an array of `{name, branch, head, path}` objects.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+        /// With `--format json`, also include `upstream`, `ahead`, `behind`, and `changed`
+        /// (slower).
+        #[arg(long)]
+        status: bool,
     },
-    List { repo: String },
     Open {
         repo: String,
         name: String,
         #[arg(long)]
         target: Option<String>,
+        #[arg(long, value_name = "PATH[:LINE]")]
+        file: Option<String>,
+    },
+    /// Show uncommitted changes in a worktree.
+    Diff {
+        repo: String,
+        name: String,
+        /// Print a per-file summary instead of the full diff.
+        #[arg(long)]
+        stat: bool,
+        /// Diff the index instead of the working tree.
+        #[arg(long)]
+        staged: bool,
+    },
+    Rm {
+        repo: String,
+        name: String,
+        /// Remove it even with uncommitted changes or a merge or rebase in progress.
+        #[arg(long)]
+        force: bool,
     },
-    Rm { repo: String, name: String },
 }
 
 pub(crate) fn run_command(command: Commands) -> Result<(), Box<dyn std::error::Error>> {
     match command {
         Commands::Repo { command: repo_cmd } => match repo_cmd {
-            RepoCommand::Clone { url, name } => {
-                let repo = if let Some(name) = name {
-                    checkout_repo_with_name(&url, &name)?
-                } else {
-                    checkout_repo(&url)?
+            RepoCommand::Clone {
+                url,
+                name,
+                single_branch,
+                branch,
+                origin,
+                reference,
+                mirror,
+                print_name,
+                force,
+                interactive,
+                with_worktree,
+            } => {
+                if print_name {
+                    println!("{}", clone_repo_name(&url, name.as_deref())?);
+                    return Ok(());
+                }
+                if let Some(existing) = find_repo_by_remote(&url)? {
+                    if !force {
+                        return Err(format!(
+                            "{} is already cloned as {}; pass --force to clone it again",
+                            url.trim(),
+                            existing.name
+                        )
+                        .into());
+                    }
+                    eprintln!(
+                        "warning: {} is already cloned as {}",
+                        url.trim(),
+                        existing.name
+                    );
+                }
+                let reference = reference.as_deref().map(resolve_repo).transpose()?;
+                let options = CloneOptions {
+                    single_branch,
+                    branch,
+                    origin,
+                    reference: reference.as_ref().map(|repo| repo.path.clone()),
+                    mirror,
                 };
+                let repo = clone_with_name_prompt(&url, name, &options, interactive)?;
                 println!("checked out {}", repo.name);
+                if single_branch {
+                    eprintln!(
+                        "warning: single-branch clone; creating worktrees from other branches will fetch them first"
+                    );
+                }
+                if let Some(reference) = reference {
+                    eprintln!(
+                        "warning: {} shares objects with {}; removing {} can corrupt it",
+                        repo.name, reference.name, reference.name
+                    );
+                }
+                if let Some(name) = with_worktree {
+                    create_default_worktree(&repo, &name).map_err(|err| {
+                        format!("cloned {} but could not create its worktree: {err}", repo.name)
+                    })?;
+                }
             }
-            RepoCommand::List => {
+            RepoCommand::List { porcelain } => {
                 let repos = list_repos()?;
-                if repos.is_empty() {
+                if porcelain {
+                    for repo in repos {
+                        let path = repo.path.to_string_lossy();
+                        println!("{}\t{}", porcelain_quote(&repo.name), porcelain_quote(&path));
+                    }
+                } else if repos.is_empty() {
                     println!("no repos");
                 } else {
                     for repo in repos {
-                        println!("{}", repo.name);
+                        println!("{}", bold(&repo.name));
                     }
                 }
             }
+            RepoCommand::Info { name } => {
+                let repo = resolve_repo(&name)?;
+                println!("name: {}", repo.name);
+                println!("path: {}", repo.path.display());
+                match repo_remote_url(&repo)? {
+                    Some((remote, url)) => println!("remote: {remote} {url}"),
+                    None => println!("remote: none"),
+                }
+                println!("worktrees: {}", list_worktrees(&repo)?.len());
+            }
+            RepoCommand::Reclone { name } => {
+                let repo = reclone_repo(&name)?;
+                println!("recloned {}", repo.name);
+            }
             RepoCommand::Rm { name } => {
                 remove_repo(&name)?;
                 println!("removed {}", name);
             }
         },
+        Commands::Config { command } => match command {
+            ConfigCommand::Path => {
+                println!("config: {}", paths::config_path()?.display());
+                println!("config root: {}", paths::config_root()?.display());
+                println!("templates: {}", paths::templates_root()?.display());
+                println!("root: {}", paths::bbq_root()?.display());
+                println!("repos: {}", paths::repos_root()?.display());
+                println!("worktrees: {}", paths::worktrees_root()?.display());
+            }
+            ConfigCommand::Get { key } => println!("{}", get_config_key(&key)?),
+            ConfigCommand::Set { key, value } => set_config_key(&key, &value)?,
+        },
+        Commands::Open => {
+            let Some((repo, name)) = load_restore_state().last_opened else {
+                return Err("no recently opened worktree; open one with `bbq worktree open`".into());
+            };
+            let repo = resolve_repo(&repo)?;
+            let worktree = find_worktree(&repo, &name)?;
+            open_worktree(&repo, &worktree, None, None)?;
+        }
+        Commands::Migrate { apply } => {
+            let moves = paths::xdg_migration_plan()?;
+            if moves.is_empty() {
+                println!("nothing to migrate");
+                return Ok(());
+            }
+            for step in &moves {
+                let verb = if apply { "moving" } else { "would move" };
+                println!("{verb} {} -> {}", step.from.display(), step.to.display());
+            }
+            if !apply {
+                println!("run `bbq migrate --apply` to move these");
+                return Ok(());
+            }
+            paths::apply_xdg_migration(&moves)?;
+            paths::ensure_root_dirs()?;
+            for repo in list_repos()? {
+                repair_worktrees(&repo)?;
+            }
+            println!("migrated; bbq root is now {}", paths::bbq_root()?.display());
+        }
+        Commands::Env { format } => {
+            let report = env_report()?;
+            match format {
+                OutputFormat::Json => println!("{}", report),
+                OutputFormat::Text => print_env_report(&report),
+            }
+        }
+        Commands::Completions { shell } => print_registration(&shell)?,
+        Commands::Version { long } => {
+            println!("bbq {}", env!("CARGO_PKG_VERSION"));
+            if long {
+                println!("platform: {}-{}", std::env::consts::OS, std::env::consts::ARCH);
+                print_env_report(&env_report()?);
+            }
+        }
         Commands::Worktree {
             command: worktree_cmd,
         } => match worktree_cmd {
-            WorktreeCommand::Create { repo, branch } => {
+            WorktreeCommand::Create {
+                repo,
+                branch,
+                at,
+                auto_suffix,
+                stdin,
+                fail_fast,
+                copy_ignored_from,
+                no_track,
+            } => {
                 let repo = resolve_repo(&repo)?;
+                let copy_from = copy_ignored_from
+                    .map(|name| find_worktree(&repo, &name))
+                    .transpose()?;
+                let copy_from = copy_from.as_ref();
+                let options = WorktreeOptions { no_track, at };
+                if stdin {
+                    return create_worktrees_from_stdin(
+                        &repo, auto_suffix, fail_fast, copy_from, &options,
+                    );
+                }
+                let name_for = |branch: &str| -> bbq::Result<String> {
+                    if auto_suffix {
+                        available_worktree_name(&repo, branch)
+                    } else {
+                        Ok(branch.to_string())
+                    }
+                };
                 if let Some(branch) = branch {
                     let branch = branch.trim();
                     if branch.is_empty() {
                         return Err("branch name required".into());
                     }
-                    let worktree = create_worktree(&repo, branch)?;
-                    return finish_worktree_create(worktree);
+                    let name = name_for(branch)?;
+                    let worktree =
+                        create_worktree_with_name_and_options(&repo, &name, branch, &options)?;
+                    return finish_worktree_create(&repo, worktree, copy_from);
                 }
 
                 if let Some(mode) = load_default_worktree_name_mode() {
-                    let default_source = default_branch(&repo)
-                        .ok()
-                        .flatten()
-                        .unwrap_or_else(|| "main".to_string());
+                    let default_source =
+                        default_branch(&repo)?.unwrap_or_else(|| "main".to_string());
                     let default_source = default_source.trim();
                     if default_source.is_empty() {
                         return Err("source branch required".into());
@@ -122,75 +457,114 @@ pub(crate) fn run_command(command: Commands) -> Result<(), Box<dyn std::error::E
                         return Err("worktree name required".into());
                     }
                     let branch_name = default_branch_name(&name);
-                    let worktree =
-                        create_worktree_from(&repo, &name, &branch_name, default_source)?;
-                    return finish_worktree_create(worktree);
+                    let worktree = create_worktree_from_with_options(
+                        &repo, &name, &branch_name, default_source, &options,
+                    )?;
+                    return finish_worktree_create(&repo, worktree, copy_from);
                 }
 
-                let branch = default_branch(&repo)
-                    .ok()
-                    .flatten()
-                    .unwrap_or_else(|| "main".to_string());
+                let branch = default_branch(&repo)?.unwrap_or_else(|| "main".to_string());
                 let branch = branch.trim();
                 if branch.is_empty() {
                     return Err("branch name required".into());
                 }
-                let worktree = create_worktree(&repo, branch)?;
-                finish_worktree_create(worktree)?;
+                let name = name_for(branch)?;
+                let worktree =
+                    create_worktree_with_name_and_options(&repo, &name, branch, &options)?;
+                finish_worktree_create(&repo, worktree, copy_from)?;
             }
-            WorktreeCommand::List { repo } => {
+            WorktreeCommand::List {
+                repo,
+                porcelain,
+                null_terminated,
+                message,
+                since,
+                label,
+                format,
+                status,
+            } => {
+                let json = matches!(format, OutputFormat::Json);
+                if json && (porcelain || message) {
+                    return Err("--format json cannot be used with --porcelain or --message".into());
+                }
+                if status && !json {
+                    return Err("--status requires --format json".into());
+                }
                 let repo = resolve_repo(&repo)?;
-                let worktrees = list_worktrees(&repo)?;
-                if worktrees.is_empty() {
+                let mut worktrees = list_worktrees(&repo)?;
+                if let Some(window) = since {
+                    let cutoff = SystemTime::now().checked_sub(window).unwrap_or(UNIX_EPOCH);
+                    worktrees.retain(|worktree| {
+                        last_activity(&worktree.path).is_some_and(|time| time >= cutoff)
+                    });
+                }
+                if let Some(label) = label.as_deref() {
+                    let labels = load_restore_state().worktree_labels;
+                    worktrees.retain(|worktree| {
+                        labels
+                            .get(&(repo.name.clone(), worktree.display_name()))
+                            .is_some_and(|labels| labels.iter().any(|item| item == label))
+                    });
+                }
+                if json {
+                    println!("{}", worktrees_json(&worktrees, status));
+                } else if porcelain {
+                    print_worktrees_porcelain(&worktrees, null_terminated)?;
+                } else if worktrees.is_empty() {
                     println!("no worktrees");
                 } else {
                     for worktree in worktrees {
-                        println!("{}\t{}", worktree.display_name(), worktree.path.display());
+                        let name = bold(&worktree.display_name());
+                        if message {
+                            let subject = head_commit_info(&worktree.path)
+                                .map(|info| info.message)
+                                .unwrap_or_default();
+                            let branch = match worktree.branch.as_deref() {
+                                Some(branch) => branch.to_string(),
+                                None => dim("(detached)"),
+                            };
+                            println!(
+                                "{}\t{}\t{}\t{}",
+                                name,
+                                worktree.path.display(),
+                                branch,
+                                subject
+                            );
+                        } else {
+                            println!("{}\t{}", name, worktree.path.display());
+                        }
                     }
                 }
             }
-            WorktreeCommand::Open { repo, name, target } => {
+            WorktreeCommand::Open {
+                repo,
+                name,
+                target,
+                file,
+            } => {
                 let repo = resolve_repo(&repo)?;
                 let worktree = find_worktree(&repo, &name)?;
-                if let Some(target) = target.as_deref() {
-                    let normalized = normalize_target(target);
-                    if normalized == "terminal" {
-                        open_terminal_at_path_with_config(
-                            &worktree.path,
-                            load_terminal_command().as_deref(),
-                        )?;
-                        println!("opened {} in terminal", worktree.display_name());
-                        return Ok(());
-                    }
-                    let selected = OpenTarget::from_config(target)
-                        .ok_or_else(|| format!("unknown target: {target}"))?;
-                    let available = detect_open_targets();
-                    if !available.contains(&selected) {
-                        return Err(format!("{} launcher not available", selected.label()).into());
-                    }
-                    open_in_target(selected, &worktree.path)?;
-                    println!("opened {} in {}", worktree.display_name(), selected.label());
-                    return Ok(());
-                }
-
-                if let Some(command) = load_editor_command().as_deref() {
-                    open_in_editor(command, &worktree.path)?;
-                    println!("opened {} in editor", worktree.display_name());
-                    return Ok(());
-                }
-
-                let available = detect_open_targets();
-                let selected = available.first().copied().ok_or_else(|| {
-                    "no open targets available; install zed, cursor, or vscode".to_string()
-                })?;
-                open_in_target(selected, &worktree.path)?;
-                println!("opened {} in {}", worktree.display_name(), selected.label());
+                open_worktree(&repo, &worktree, target.as_deref(), file.as_deref())?;
+                remember_opened(&repo, &worktree);
+            }
+            WorktreeCommand::Diff {
+                repo,
+                name,
+                stat,
+                staged,
+            } => {
+                let repo = resolve_repo(&repo)?;
+                let worktree = find_worktree(&repo, &name)?;
+                print!("{}", worktree_diff(&worktree, stat, staged)?);
             }
-            WorktreeCommand::Rm { repo, name } => {
+            WorktreeCommand::Rm { repo, name, force } => {
                 let repo = resolve_repo(&repo)?;
                 let worktree = find_worktree(&repo, &name)?;
+                if !force {
+                    check_worktree_removable(&worktree)?;
+                }
                 run_pre_delete_script(&worktree, ScriptOutput::Inherit)?;
-                remove_worktree(&repo, &name)?;
+                remove_worktree_with_force(&repo, &name, force)?;
                 println!("removed {}", name);
             }
         },
@@ -199,22 +573,389 @@ pub(crate) fn run_command(command: Commands) -> Result<(), Box<dyn std::error::E
     Ok(())
 }
 
-fn find_worktree(repo: &Repo, name: &str) -> Result<Worktree, bbq::BbqError> {
-    let worktrees = list_worktrees(repo)?;
-    worktrees
-        .into_iter()
-        .find(|item| {
-            item.display_name() == name
-                || item
-                    .branch
-                    .as_deref()
-                    .map(|branch| branch == name)
-                    .unwrap_or(false)
+/// Refuses worktrees that would lose work: uncommitted changes, or a merge or rebase stopped
+/// partway.
+fn check_worktree_removable(worktree: &Worktree) -> Result<(), Box<dyn std::error::Error>> {
+    let name = worktree.display_name();
+    if let Some(state) = worktree_conflict_state(&worktree.path) {
+        return Err(format!(
+            "{name} has a {}; finish or abort it first, or pass --force",
+            state.label()
+        )
+        .into());
+    }
+    let (_, changed) = git_changed_files(&worktree.path, Some(0));
+    if changed > 0 {
+        return Err(format!(
+            "{name} has {changed} uncommitted change(s); commit or stash them first, or pass \
+             --force"
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Opens a worktree (or a file inside it) in `target`, the configured editor, or the first
+/// detected editor.
+fn open_worktree(
+    repo: &Repo,
+    worktree: &Worktree,
+    target: Option<&str>,
+    file: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let env = load_repo_env(&worktree.path);
+    let new_window = editor_new_window_enabled();
+    let file = file
+        .map(|spec| resolve_file_target(&worktree.path, spec))
+        .transpose()?;
+    let opened = match &file {
+        Some(file) => file.path.display().to_string(),
+        None => worktree.display_name(),
+    };
+    if let Some(target) = target {
+        let normalized = normalize_target(target);
+        if normalized == "terminal" {
+            if file.is_some() {
+                return Err("--file cannot be used with --target terminal".into());
+            }
+            open_terminal_at_path_with_config(
+                &worktree.path,
+                load_terminal_command().as_deref(),
+                &env,
+            )?;
+            println!("opened {} in terminal", worktree.display_name());
+            return Ok(());
+        }
+        if normalized == "tmux" {
+            if file.is_some() {
+                return Err("--file cannot be used with --target tmux".into());
+            }
+            open_in_tmux(&repo.name, &worktree.display_name(), &worktree.path, &env)?;
+            return Ok(());
+        }
+        let selected = OpenTarget::from_config(target)
+            .ok_or_else(|| format!("unknown target: {target}"))?;
+        let available = detect_open_targets();
+        if !available.contains(&selected) {
+            return Err(format!("{} launcher not available", selected.label()).into());
+        }
+        match &file {
+            Some(file) => open_file_in_target(selected, file, &env, new_window)?,
+            None => open_in_target(selected, &worktree.path, &env, new_window)?,
+        }
+        println!("opened {} in {}", opened, selected.label());
+        return Ok(());
+    }
+
+    let terminal = load_terminal_command();
+    if let Some(command) = load_editor_command().as_deref() {
+        let path = &worktree.path;
+        match &file {
+            Some(file) => {
+                open_file_in_editor(command, path, file, terminal.as_deref(), &env, new_window)?
+            }
+            None => open_in_editor(command, path, terminal.as_deref(), &env, new_window)?,
+        }
+        println!("opened {} in editor", opened);
+        return Ok(());
+    }
+
+    let available = detect_open_targets_in_order(&load_open_order());
+    let selected = match resolve_open_command(&available, |name| std::env::var(name).ok()) {
+        Some(OpenCommand::Target(target)) => target,
+        Some(OpenCommand::TerminalEditor(editor)) => {
+            open_in_terminal_editor(
+                &editor,
+                &worktree.path,
+                file.as_ref(),
+                terminal.as_deref(),
+                &env,
+            )?;
+            println!("opened {} in {}", opened, editor);
+            return Ok(());
+        }
+        None => {
+            let hint = "install zed, cursor, or vscode, or set $EDITOR";
+            return Err(format!("no open targets available; {hint}").into());
+        }
+    };
+    match &file {
+        Some(file) => open_file_in_target(selected, file, &env, new_window)?,
+        None => open_in_target(selected, &worktree.path, &env, new_window)?,
+    }
+    println!("opened {} in {}", opened, selected.label());
+    Ok(())
+}
+
+/// Failing to remember the worktree shouldn't fail an open that already happened.
+fn remember_opened(repo: &Repo, worktree: &Worktree) {
+    if let Err(err) = record_last_opened(&repo.name, &worktree.display_name()) {
+        eprintln!("warning: could not record last opened worktree: {err}");
+    }
+}
+
+/// `{ "error", "message", "command", "stderr" }` for `--error-format json`. Errors that don't
+/// come from the bbq library are reported with the kind `Error`.
+pub(crate) fn error_json(err: &(dyn std::error::Error + 'static)) -> serde_json::Value {
+    let Some(err) = err.downcast_ref::<BbqError>() else {
+        return serde_json::json!({
+            "error": "Error",
+            "message": err.to_string(),
+            "command": null,
+            "stderr": null,
+        });
+    };
+    let (command, stderr) = match err {
+        BbqError::GitCommand { command, stderr }
+        | BbqError::GitHubCliCommand { command, stderr } => (Some(command), Some(stderr)),
+        _ => (None, None),
+    };
+    serde_json::json!({
+        "error": err.kind(),
+        "message": err.to_string(),
+        "command": command,
+        "stderr": stderr,
+    })
+}
+
+/// Parses `--since` windows like `90m`, `24h`, `7d`, or `2w`.
+fn parse_since(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value
+        .find(|ch: char| !ch.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+    let amount: u64 = amount
+        .parse()
+        .map_err(|_| format!("expected a duration like 7d or 24h, got `{}`", value))?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(format!("unknown duration unit in `{}`; use s, m, h, d, or w", value)),
+    };
+    Ok(Duration::from_secs(amount.saturating_mul(seconds)))
+}
+
+/// The more recent of the HEAD commit date and the worktree directory's mtime.
+fn last_activity(path: &Path) -> Option<SystemTime> {
+    let modified = std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok();
+    head_commit_time(path).max(modified)
+}
+
+fn print_worktrees_porcelain(
+    worktrees: &[Worktree],
+    null_terminated: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut stdout = io::stdout().lock();
+    for worktree in worktrees {
+        let path = worktree.path.to_string_lossy();
+        let fields = [
+            worktree.display_name(),
+            worktree.branch.clone().unwrap_or_default(),
+            worktree.head.clone().unwrap_or_default(),
+            path.into_owned(),
+        ];
+        if null_terminated {
+            for field in &fields {
+                write!(stdout, "{field}\0")?;
+            }
+        } else {
+            let quoted: Vec<String> = fields.iter().map(|field| porcelain_quote(field)).collect();
+            writeln!(stdout, "{}", quoted.join("\t"))?;
+        }
+    }
+    stdout.flush()?;
+    Ok(())
+}
+
+fn worktrees_json(worktrees: &[Worktree], status: bool) -> serde_json::Value {
+    let items = worktrees
+        .iter()
+        .map(|worktree| {
+            let mut item = serde_json::json!({
+                "name": worktree.display_name(),
+                "branch": worktree.branch,
+                "head": worktree.head,
+                "path": worktree.path,
+            });
+            if status {
+                // The same values the TUI detail panel shows.
+                let upstream = worktree_upstream_ref(&worktree.path);
+                let divergence = upstream
+                    .as_ref()
+                    .and_then(|upstream| head_divergence(&worktree.path, &upstream.rev));
+                item["upstream"] = serde_json::json!(upstream.map(|upstream| upstream.display));
+                item["ahead"] = serde_json::json!(divergence.map(|(ahead, _)| ahead));
+                item["behind"] = serde_json::json!(divergence.map(|(_, behind)| behind));
+                item["changed"] = serde_json::json!(git_changed_files(&worktree.path, Some(0)).1);
+            }
+            item
         })
-        .ok_or_else(|| bbq::BbqError::WorktreeNotFound(name.to_string()))
+        .collect();
+    serde_json::Value::Array(items)
+}
+
+// Mirrors git's core.quotePath style: fields containing separators, quotes, or control
+// characters are wrapped in double quotes with C-style escapes.
+fn porcelain_quote(field: &str) -> String {
+    let needs_quoting = field
+        .chars()
+        .any(|ch| ch == '"' || ch == '\\' || ch.is_control());
+    if !needs_quoting {
+        return field.to_string();
+    }
+
+    let mut quoted = String::with_capacity(field.len() + 2);
+    quoted.push('"');
+    for ch in field.chars() {
+        match ch {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\t' => quoted.push_str("\\t"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            ch if ch.is_control() => quoted.push_str(&format!("\\{:03o}", ch as u32)),
+            ch => quoted.push(ch),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn print_env_report(report: &serde_json::Value) {
+    let field = |key: &str| match &report[key] {
+        serde_json::Value::Null => "not found".to_string(),
+        serde_json::Value::String(value) => value.clone(),
+        value => value.to_string(),
+    };
+    println!("root: {}", field("root"));
+    println!("git: {}", field("git_version"));
+    println!("gh: {}", field("gh_version"));
+    println!("gh authenticated: {}", field("gh_authenticated"));
+    println!("repos: {}", field("repos_count"));
 }
 
-fn finish_worktree_create(worktree: Worktree) -> Result<(), Box<dyn std::error::Error>> {
+/// Clones `url`; with `interactive`, a taken repo name prompts for another on stdin until one is
+/// free. An empty answer gives up with the original error.
+fn clone_with_name_prompt(
+    url: &str,
+    name: Option<String>,
+    options: &CloneOptions,
+    interactive: bool,
+) -> Result<Repo, Box<dyn std::error::Error>> {
+    let mut name = name;
+    loop {
+        match checkout_repo_with_options(url, name.as_deref(), options) {
+            Err(BbqError::RepoAlreadyExists(existing)) if interactive => {
+                eprint!("repo {existing} already exists; new name (empty to cancel): ");
+                io::stderr().flush()?;
+                let mut answer = String::new();
+                io::stdin().lock().read_line(&mut answer)?;
+                if answer.trim().is_empty() {
+                    return Err(BbqError::RepoAlreadyExists(existing).into());
+                }
+                name = Some(clone_repo_name(url, Some(&answer))?);
+            }
+            result => return Ok(result?),
+        }
+    }
+}
+
+/// Batch `worktree create --stdin`: one `name[:source]` per line, blank lines and `#` comments
+/// skipped. Each line reports `created` or `failed`; any failure makes the command fail.
+fn create_worktrees_from_stdin(
+    repo: &Repo,
+    auto_suffix: bool,
+    fail_fast: bool,
+    copy_from: Option<&Worktree>,
+    options: &WorktreeOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let default_source = default_branch(repo)?.unwrap_or_else(|| "main".to_string());
+    let mut attempted = 0usize;
+    let mut failed = 0usize;
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        attempted += 1;
+        let (name, source) = match line.split_once(':') {
+            Some((name, source)) => (name.trim(), source.trim()),
+            None => (line, default_source.as_str()),
+        };
+        let result = create_batch_worktree(repo, name, source, auto_suffix, copy_from, options);
+        if let Err(err) = result {
+            failed += 1;
+            eprintln!("failed {name}: {err}");
+            if fail_fast {
+                break;
+            }
+        }
+    }
+    if failed > 0 {
+        return Err(format!("{failed} of {attempted} worktrees failed").into());
+    }
+    Ok(())
+}
+
+fn create_batch_worktree(
+    repo: &Repo,
+    name: &str,
+    source: &str,
+    auto_suffix: bool,
+    copy_from: Option<&Worktree>,
+    options: &WorktreeOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let name = if auto_suffix {
+        available_worktree_name(repo, name)?
+    } else {
+        name.to_string()
+    };
+    let branch_name = default_branch_name(&name);
+    let worktree = create_worktree_from_with_options(repo, &name, &branch_name, source, options)?;
+    finish_worktree_create(repo, worktree, copy_from)
+}
+
+/// `repo clone --with-worktree`: checks out the default branch in a worktree named `name`, or
+/// after the branch when `name` is empty.
+fn create_default_worktree(repo: &Repo, name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(branch) = default_branch(repo)? else {
+        return Err(format!("could not determine the default branch of {}", repo.name).into());
+    };
+    // `default_branch` is usually `origin/<branch>`; the worktree is named after the tail.
+    let name = match name.trim() {
+        "" => {
+            let template = load_worktree_name_template();
+            worktree_name_from_branch(&repo.name, &branch, template.as_deref())
+        }
+        name => name.to_string(),
+    };
+    let worktree = create_worktree_with_name_and_options(
+        repo,
+        &name,
+        &branch,
+        &WorktreeOptions::default(),
+    )?;
+    finish_worktree_create(repo, worktree, None)
+}
+
+fn finish_worktree_create(
+    repo: &Repo,
+    worktree: Worktree,
+    copy_from: Option<&Worktree>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Before templates, so a worktree's own local config wins over the shared defaults.
+    if let Some(source) = copy_from {
+        copy_untracked_files(source, &worktree)?;
+    }
+    copy_worktree_templates(repo, &worktree)?;
+    apply_repo_git_config(&worktree)?;
     run_post_create_script(&worktree, ScriptOutput::Inherit)?;
     println!("created {}", worktree.display_name());
     Ok(())
@@ -0,0 +1,210 @@
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+use bbq::Worktree;
+
+use crate::open::shell_escape;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ClipboardContent {
+    Branch(String),
+    PullRequest { repo: String, number: u64 },
+    Invalid,
+}
+
+pub(crate) fn read_clipboard() -> io::Result<String> {
+    for (program, args) in clipboard_commands() {
+        let output = match Command::new(program)
+            .args(*args)
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+        {
+            Ok(output) => output,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err),
+        };
+        if output.status.success() {
+            return Ok(String::from_utf8_lossy(&output.stdout).to_string());
+        }
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        "no clipboard tool found (pbpaste, wl-paste, xclip, or xsel)",
+    ))
+}
+
+pub(crate) fn write_clipboard(text: &str) -> io::Result<()> {
+    for (program, args) in clipboard_write_commands() {
+        let mut child = match Command::new(program)
+            .args(*args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err),
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        if child.wait()?.success() {
+            return Ok(());
+        }
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        "no clipboard tool found (pbcopy, wl-copy, xclip, or xsel)",
+    ))
+}
+
+/// A plain `git worktree add` that recreates `worktree` next to another clone: same name,
+/// same branch, starting from the same commit. A branch that has been pushed (`branch_pushed`)
+/// already exists for the other clone, so it is checked out instead of created with `-b`.
+pub(crate) fn git_worktree_add_command(worktree: &Worktree, branch_pushed: bool) -> String {
+    let path = shell_escape(&format!("../{}", worktree.display_name()));
+    match (worktree.branch.as_deref(), worktree.head.as_deref()) {
+        (Some(branch), _) if branch_pushed => {
+            format!("git worktree add {} {}", path, shell_escape(branch))
+        }
+        (Some(branch), Some(head)) => {
+            format!("git worktree add -b {} {} {}", shell_escape(branch), path, head)
+        }
+        (Some(branch), None) => format!("git worktree add {} {}", path, shell_escape(branch)),
+        (None, Some(head)) => format!("git worktree add --detach {} {}", path, head),
+        (None, None) => format!("git worktree add --detach {}", path),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn clipboard_write_commands() -> &'static [(&'static str, &'static [&'static str])] {
+    &[("pbcopy", &[])]
+}
+
+#[cfg(not(target_os = "macos"))]
+fn clipboard_write_commands() -> &'static [(&'static str, &'static [&'static str])] {
+    &[
+        ("wl-copy", &[]),
+        ("xclip", &["-selection", "clipboard", "-i"]),
+        ("xsel", &["--clipboard", "--input"]),
+    ]
+}
+
+#[cfg(target_os = "macos")]
+fn clipboard_commands() -> &'static [(&'static str, &'static [&'static str])] {
+    &[("pbpaste", &[])]
+}
+
+#[cfg(not(target_os = "macos"))]
+fn clipboard_commands() -> &'static [(&'static str, &'static [&'static str])] {
+    &[
+        ("wl-paste", &["--no-newline"]),
+        ("xclip", &["-selection", "clipboard", "-o"]),
+        ("xsel", &["--clipboard", "--output"]),
+    ]
+}
+
+pub(crate) fn classify_clipboard(text: &str) -> ClipboardContent {
+    let trimmed = text.trim();
+    if trimmed.is_empty() || trimmed.lines().count() > 1 {
+        return ClipboardContent::Invalid;
+    }
+
+    if let Some((repo, number)) = trimmed.split_once('#') {
+        let is_repo = repo
+            .split_once('/')
+            .map(|(owner, name)| is_github_name(owner) && is_github_name(name))
+            .unwrap_or(false);
+        return match number.parse::<u64>() {
+            Ok(number) if is_repo && number > 0 => ClipboardContent::PullRequest {
+                repo: repo.to_string(),
+                number,
+            },
+            _ => ClipboardContent::Invalid,
+        };
+    }
+
+    if bbq::validate_branch_name(trimmed).is_err() {
+        return ClipboardContent::Invalid;
+    }
+    ClipboardContent::Branch(trimmed.to_string())
+}
+
+fn is_github_name(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' || ch == '.')
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use bbq::Worktree;
+
+    use super::{classify_clipboard, git_worktree_add_command, ClipboardContent};
+
+    #[test]
+    fn classifies_branch_names() {
+        assert_eq!(
+            classify_clipboard("octocat/fix-login\n"),
+            ClipboardContent::Branch("octocat/fix-login".to_string())
+        );
+        assert_eq!(
+            classify_clipboard("  main "),
+            ClipboardContent::Branch("main".to_string())
+        );
+    }
+
+    #[test]
+    fn classifies_pull_request_refs() {
+        assert_eq!(
+            classify_clipboard("richardhenry/bbq#123"),
+            ClipboardContent::PullRequest {
+                repo: "richardhenry/bbq".to_string(),
+                number: 123,
+            }
+        );
+        assert_eq!(classify_clipboard("bbq#123"), ClipboardContent::Invalid);
+        assert_eq!(classify_clipboard("richardhenry/bbq#abc"), ClipboardContent::Invalid);
+    }
+
+    #[test]
+    fn rejects_junk() {
+        assert_eq!(classify_clipboard(""), ClipboardContent::Invalid);
+        assert_eq!(classify_clipboard("two words"), ClipboardContent::Invalid);
+        assert_eq!(classify_clipboard("line-one\nline-two"), ClipboardContent::Invalid);
+        assert_eq!(classify_clipboard("/leading-slash"), ClipboardContent::Invalid);
+    }
+
+    #[test]
+    fn builds_git_worktree_add_command() {
+        let worktree = Worktree {
+            path: PathBuf::from("/home/me/.bbq/worktrees/api/fix login"),
+            branch: Some("octocat/fix-login".to_string()),
+            head: Some("0123abcd".to_string()),
+        };
+        assert_eq!(
+            git_worktree_add_command(&worktree, false),
+            "git worktree add -b octocat/fix-login '../fix login' 0123abcd"
+        );
+        assert_eq!(
+            git_worktree_add_command(&worktree, true),
+            "git worktree add '../fix login' octocat/fix-login"
+        );
+
+        let detached = Worktree {
+            branch: None,
+            ..worktree
+        };
+        assert_eq!(
+            git_worktree_add_command(&detached, false),
+            "git worktree add --detach '../fix login' 0123abcd"
+        );
+    }
+}
@@ -0,0 +1,44 @@
+use std::io::{self, IsTerminal};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use clap::ValueEnum;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub(crate) enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+static COLOR: AtomicBool = AtomicBool::new(false);
+
+/// Resolves `--color` once at startup; `auto` colors only when stdout is a terminal and
+/// `NO_COLOR` is unset.
+pub(crate) fn set_color_choice(choice: ColorChoice) {
+    let enabled = match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+    };
+    COLOR.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn should_color() -> bool {
+    COLOR.load(Ordering::Relaxed)
+}
+
+pub(crate) fn bold(text: &str) -> String {
+    paint(text, "1")
+}
+
+pub(crate) fn dim(text: &str) -> String {
+    paint(text, "2")
+}
+
+fn paint(text: &str, code: &str) -> String {
+    if should_color() {
+        format!("\x1b[{code}m{text}\x1b[0m")
+    } else {
+        text.to_string()
+    }
+}
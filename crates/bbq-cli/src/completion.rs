@@ -0,0 +1,74 @@
+use std::ffi::OsString;
+use std::io;
+
+use bbq::{list_branches, resolve_repo};
+use clap::CommandFactory;
+use clap_complete::env::Shells;
+use clap_complete::CompletionCandidate;
+
+use crate::cli::Cli;
+
+/// Environment variable the shell scripts set when they call back into `bbq` to complete.
+pub(crate) const COMPLETE_VAR: &str = "COMPLETE";
+
+pub(crate) const SHELLS: [&str; 5] = ["bash", "elvish", "fish", "powershell", "zsh"];
+
+/// Prints the script that registers `bbq` completions with `shell`. The script calls back
+/// into this binary, so branch names are looked up at completion time.
+pub(crate) fn print_registration(shell: &str) -> io::Result<()> {
+    let shells = Shells::builtins();
+    let completer = shells.completer(shell).ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, format!("unsupported shell: {shell}"))
+    })?;
+    let program = std::env::current_exe()
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or_else(|_| "bbq".to_string());
+    completer.write_registration(COMPLETE_VAR, "bbq", "bbq", &program, &mut io::stdout())
+}
+
+/// `worktree create --branch` candidates: the branches of the `<repo>` already typed on the
+/// command line being completed.
+pub(crate) fn branch_candidates() -> Vec<CompletionCandidate> {
+    let Some(repo) = completing_worktree_repo(std::env::args_os()) else {
+        return Vec::new();
+    };
+    let Ok(repo) = resolve_repo(&repo) else {
+        return Vec::new();
+    };
+    list_branches(&repo)
+        .unwrap_or_default()
+        .into_iter()
+        .map(CompletionCandidate::new)
+        .collect()
+}
+
+/// The `<repo>` of a `worktree create` in the words after `--`, which is how the shell passes
+/// the line being completed. The last word is usually unfinished, so errors are ignored.
+fn completing_worktree_repo(args: impl IntoIterator<Item = OsString>) -> Option<String> {
+    let words = args.into_iter().skip_while(|arg| arg != "--").skip(1);
+    let matches = Cli::command().ignore_errors(true).try_get_matches_from(words).ok()?;
+    let create = matches.subcommand_matches("worktree")?.subcommand_matches("create")?;
+    create.get_one::<String>("repo").cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsString;
+
+    use super::completing_worktree_repo;
+
+    fn words(line: &str) -> Vec<OsString> {
+        line.split(' ').map(OsString::from).collect()
+    }
+
+    #[test]
+    fn completing_repo_comes_from_the_words_after_the_separator() {
+        let repo = completing_worktree_repo(words("bbq -- bbq worktree create api --branch "));
+        assert_eq!(repo.as_deref(), Some("api"));
+        let line = "bbq -- bbq --offline worktree create api --branch fe";
+        let repo = completing_worktree_repo(words(line));
+        assert_eq!(repo.as_deref(), Some("api"));
+        assert_eq!(completing_worktree_repo(words("bbq -- bbq worktree create --branch ")), None);
+        assert_eq!(completing_worktree_repo(words("bbq -- bbq worktree list api")), None);
+    }
+}
@@ -1,13 +1,17 @@
+use std::collections::HashMap;
 use std::fs;
-use std::io;
-use std::path::PathBuf;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
 
-use bbq::paths;
-use bbq::DefaultWorktreeNameMode;
+use bbq::acquire_state_lock;
+use bbq::paths::{self, parse_config_list, parse_config_string};
+use bbq::{set_worktree_config, DefaultWorktreeNameMode, Worktree};
 
-use crate::theme::{default_theme_index, theme_index_by_name};
+use crate::open::OpenTarget;
+use crate::theme::{default_theme_index, theme_index_by_name, THEMES};
 
 #[derive(Debug, Default, Clone)]
 pub(crate) struct Config {
@@ -20,6 +24,22 @@ pub(crate) struct Config {
     pub(crate) known_latest_version: Option<String>,
     pub(crate) check_updates: Option<bool>,
     pub(crate) force_upgrade_prompt: Option<bool>,
+    pub(crate) open_order: Vec<String>,
+    pub(crate) auto_fetch_minutes: Option<u64>,
+    pub(crate) editor_new_window: Option<bool>,
+    pub(crate) notify_on_complete: Option<bool>,
+    pub(crate) confirm_deletes: Option<bool>,
+    pub(crate) show_env: Option<bool>,
+    pub(crate) branch_prefix_interactive: Option<bool>,
+    pub(crate) hide_empty_repos: Option<bool>,
+    pub(crate) tree_show_churn: Option<bool>,
+    pub(crate) expand_paths: Option<bool>,
+    pub(crate) worktree_name_template: Option<String>,
+    pub(crate) tree_glyphs: Option<String>,
+    pub(crate) tree_indent: Option<usize>,
+    pub(crate) max_changed_files: Option<usize>,
+    pub(crate) poll_ms: Option<u64>,
+    pub(crate) keys: Vec<(String, String)>,
 }
 
 pub(crate) fn load_config() -> Config {
@@ -36,10 +56,15 @@ pub(crate) fn load_config() -> Config {
 
 fn parse_config(contents: &str) -> Config {
     let mut config = Config::default();
+    let mut in_keys_section = false;
 
     for line in contents.lines() {
         let line = line.trim();
-        if line.is_empty() || line.starts_with('#') || line.starts_with('[') {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') {
+            in_keys_section = line == "[keys]";
             continue;
         }
 
@@ -50,10 +75,15 @@ fn parse_config(contents: &str) -> Config {
         };
         let value = value.trim();
 
+        if in_keys_section {
+            config.keys.push((key.to_string(), parse_config_string(value)));
+            continue;
+        }
+
         match key {
-            "theme" => config.theme = Some(trim_quotes(value)),
+            "theme" => config.theme = Some(parse_config_string(value)),
             "default_worktree_name" => {
-                let trimmed = trim_quotes(value);
+                let trimmed = parse_config_string(value);
                 config.default_worktree_name = DefaultWorktreeNameMode::from_config(&trimmed);
                 config.default_worktree_name_set = true;
             }
@@ -63,21 +93,92 @@ fn parse_config(contents: &str) -> Config {
                 }
             }
             "editor" => {
-                let editor = trim_quotes(value);
-                if !editor.is_empty() {
-                    if config.editor.is_none() {
-                        config.editor = Some(editor.clone());
-                    }
+                let editor = parse_config_string(value);
+                if !editor.is_empty() && config.editor.is_none() {
+                    config.editor = Some(editor);
                 }
             }
             "terminal" => {
-                let terminal = trim_quotes(value);
+                let terminal = parse_config_string(value);
                 if !terminal.is_empty() {
                     config.terminal = Some(terminal);
                 }
             }
+            "open_order" => config.open_order = parse_config_list(value),
+            "worktree_name_template" => {
+                let template = parse_config_string(value);
+                if !template.is_empty() {
+                    config.worktree_name_template = Some(template);
+                }
+            }
+            "editor_new_window" => {
+                if let Some(enabled) = parse_bool(value) {
+                    config.editor_new_window = Some(enabled);
+                }
+            }
+            "notify_on_complete" => {
+                if let Some(enabled) = parse_bool(value) {
+                    config.notify_on_complete = Some(enabled);
+                }
+            }
+            "confirm_deletes" => {
+                if let Some(enabled) = parse_bool(value) {
+                    config.confirm_deletes = Some(enabled);
+                }
+            }
+            "show_env" => {
+                if let Some(enabled) = parse_bool(value) {
+                    config.show_env = Some(enabled);
+                }
+            }
+            "expand_paths" => {
+                if let Some(enabled) = parse_bool(value) {
+                    config.expand_paths = Some(enabled);
+                }
+            }
+            "tree_show_churn" => {
+                if let Some(enabled) = parse_bool(value) {
+                    config.tree_show_churn = Some(enabled);
+                }
+            }
+            "hide_empty_repos" => {
+                if let Some(enabled) = parse_bool(value) {
+                    config.hide_empty_repos = Some(enabled);
+                }
+            }
+            "branch_prefix_interactive" => {
+                if let Some(enabled) = parse_bool(value) {
+                    config.branch_prefix_interactive = Some(enabled);
+                }
+            }
+            "tree_glyphs" => {
+                let glyphs = parse_config_string(value);
+                if !glyphs.is_empty() {
+                    config.tree_glyphs = Some(glyphs);
+                }
+            }
+            "tree_indent" => {
+                if let Ok(indent) = parse_config_string(value).parse::<usize>() {
+                    config.tree_indent = Some(indent);
+                }
+            }
+            "max_changed_files" => {
+                if let Ok(max) = parse_config_string(value).parse::<usize>() {
+                    config.max_changed_files = Some(max);
+                }
+            }
+            "poll_ms" => {
+                if let Ok(ms) = parse_config_string(value).parse::<u64>() {
+                    config.poll_ms = Some(ms);
+                }
+            }
+            "auto_fetch_minutes" => {
+                if let Ok(minutes) = parse_config_string(value).parse::<u64>() {
+                    config.auto_fetch_minutes = Some(minutes);
+                }
+            }
             "known_latest_version" => {
-                let latest = trim_quotes(value);
+                let latest = parse_config_string(value);
                 if !latest.is_empty() {
                     config.known_latest_version = Some(latest);
                 }
@@ -114,6 +215,23 @@ pub(crate) fn load_default_worktree_name_mode() -> Option<DefaultWorktreeNameMod
     load_config().default_worktree_name
 }
 
+pub(crate) fn load_worktree_name_template() -> Option<String> {
+    load_config().worktree_name_template
+}
+
+/// Whether `tree_glyphs = "ascii"` is set; anything else keeps the unicode arrows.
+pub(crate) fn tree_glyphs_ascii_enabled() -> bool {
+    load_config()
+        .tree_glyphs
+        .is_some_and(|glyphs| glyphs.eq_ignore_ascii_case("ascii"))
+}
+
+/// Worktree rows need two columns for a favorite or mark glyph and its space, so smaller
+/// values are raised to that.
+pub(crate) fn load_tree_indent() -> usize {
+    load_config().tree_indent.unwrap_or(2).max(2)
+}
+
 pub(crate) fn default_worktree_name_is_configured() -> bool {
     load_config().default_worktree_name_set
 }
@@ -137,10 +255,194 @@ pub(crate) fn terminal_is_configured() -> bool {
     load_config().terminal.is_some()
 }
 
+pub(crate) fn load_open_order() -> Vec<OpenTarget> {
+    load_config()
+        .open_order
+        .iter()
+        .filter_map(|name| OpenTarget::from_config(name))
+        .collect()
+}
+
+pub(crate) const REPO_CONFIG_RELATIVE: &str = ".bbq/repo.toml";
+
+/// Reads the `[env]` table from a worktree's `.bbq/repo.toml`, skipping keys that are not valid
+/// environment variable names.
+pub(crate) fn load_repo_env(worktree_path: &Path) -> Vec<(String, String)> {
+    load_repo_table(worktree_path, "env", is_env_var_name)
+}
+
+/// Reads the `[git-config]` table from a worktree's `.bbq/repo.toml`, skipping keys that don't
+/// look like `section.name` git config keys or fall outside `REPO_GIT_CONFIG_SECTIONS`.
+pub(crate) fn load_repo_git_config(worktree_path: &Path) -> Vec<(String, String)> {
+    load_repo_table(worktree_path, "git-config", |key| {
+        is_git_config_key(key) && repo_git_config_allowed(key)
+    })
+}
+
+/// Sections a checked-in `[git-config]` table may set. The file comes with the repo, so keys
+/// that make git run commands (`core.sshCommand`, `core.fsmonitor`, `gpg.program`, diff and
+/// merge drivers, credential helpers, ...) or silently redirect remotes (`url.*.insteadOf`)
+/// are never applied from it.
+const REPO_GIT_CONFIG_SECTIONS: [&str; 9] = [
+    "author", "commit", "committer", "fetch", "pull", "push", "rebase", "tag", "user",
+];
+
+fn repo_git_config_allowed(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    let section = key.split('.').next().unwrap_or("");
+    REPO_GIT_CONFIG_SECTIONS.contains(&section) || key == "gpg.format"
+}
+
+/// Whether a worktree's `.bbq/repo.toml` sets the top-level `sign = true`.
+pub(crate) fn repo_signing_enabled(worktree_path: &Path) -> bool {
+    repo_setting(worktree_path, "sign").is_some_and(|value| value == "true")
+}
+
+/// The top-level `hooks_path` from a worktree's `.bbq/repo.toml`, if set and non-empty.
+pub(crate) fn repo_hooks_path(worktree_path: &Path) -> Option<String> {
+    repo_setting(worktree_path, "hooks_path").filter(|value| !value.is_empty())
+}
+
+fn repo_setting(worktree_path: &Path, key: &str) -> Option<String> {
+    load_repo_table(worktree_path, "", |_| true)
+        .into_iter()
+        .find_map(|(name, value)| (name == key).then_some(value))
+}
+
+// An empty `table` reads the top-level keys before the first `[table]` header.
+fn load_repo_table(
+    worktree_path: &Path,
+    table: &str,
+    valid_key: fn(&str) -> bool,
+) -> Vec<(String, String)> {
+    let Ok(contents) = fs::read_to_string(worktree_path.join(REPO_CONFIG_RELATIVE)) else {
+        return Vec::new();
+    };
+
+    let header = format!("[{table}]");
+    let mut entries = Vec::new();
+    let mut in_section = table.is_empty();
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') {
+            in_section = line == header;
+            continue;
+        }
+        if !in_section {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = parse_config_string(key);
+        if valid_key(&key) {
+            entries.push((key, parse_config_string(value)));
+        }
+    }
+    entries
+}
+
+fn is_env_var_name(value: &str) -> bool {
+    let mut chars = value.chars();
+    matches!(chars.next(), Some(ch) if ch.is_ascii_alphabetic() || ch == '_')
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+}
+
+/// Applies the repo's `sign` and `hooks_path` settings and `[git-config]` table to a freshly
+/// created worktree. bbq never unsets `commit.gpgsign`, so signing already configured for the
+/// repo carries over.
+pub(crate) fn apply_repo_git_config(worktree: &Worktree) -> bbq::Result<()> {
+    if repo_signing_enabled(&worktree.path) {
+        set_worktree_config(worktree, "commit.gpgsign", "true")?;
+    }
+    if let Some(hooks_path) = repo_hooks_path(&worktree.path) {
+        set_worktree_config(worktree, "core.hooksPath", &hooks_path)?;
+    }
+    for (key, value) in load_repo_git_config(&worktree.path) {
+        set_worktree_config(worktree, &key, &value)?;
+    }
+    Ok(())
+}
+
+// `section.name` or `section.subsection.name`; section and name are alphanumeric or `-`, and
+// the name starts with a letter.
+fn is_git_config_key(value: &str) -> bool {
+    let Some((section, name)) = value.split_once('.') else {
+        return false;
+    };
+    let name = name.rsplit('.').next().unwrap_or(name);
+    let simple = |part: &str| {
+        !part.is_empty() && part.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '-')
+    };
+    simple(section) && simple(name) && name.starts_with(|ch: char| ch.is_ascii_alphabetic())
+}
+
+pub(crate) fn load_key_bindings() -> Vec<(String, String)> {
+    load_config().keys
+}
+
+pub(crate) fn theme_is_configured() -> bool {
+    load_config().theme.is_some()
+}
+
 pub(crate) fn known_latest_version() -> Option<String> {
     load_config().known_latest_version
 }
 
+pub(crate) fn editor_new_window_enabled() -> bool {
+    load_config().editor_new_window.unwrap_or(false)
+}
+
+pub(crate) fn show_env_enabled() -> bool {
+    load_config().show_env.unwrap_or(true)
+}
+
+pub(crate) fn notify_on_complete_enabled() -> bool {
+    load_config().notify_on_complete.unwrap_or(false)
+}
+
+pub(crate) fn expand_paths_enabled() -> bool {
+    load_config().expand_paths.unwrap_or(false)
+}
+
+pub(crate) fn hide_empty_repos_enabled() -> bool {
+    load_config().hide_empty_repos.unwrap_or(false)
+}
+
+pub(crate) fn tree_show_churn_enabled() -> bool {
+    load_config().tree_show_churn.unwrap_or(false)
+}
+
+pub(crate) fn branch_prefix_interactive_enabled() -> bool {
+    load_config().branch_prefix_interactive.unwrap_or(false)
+}
+
+pub(crate) fn confirm_deletes_enabled() -> bool {
+    load_config().confirm_deletes.unwrap_or(true)
+}
+
+/// Interval for background fetches in the TUI; `None` when `auto_fetch_minutes` is unset or 0.
+pub(crate) fn load_auto_fetch_interval() -> Option<Duration> {
+    load_config()
+        .auto_fetch_minutes
+        .filter(|minutes| *minutes > 0)
+        .map(|minutes| Duration::from_secs(minutes.saturating_mul(60)))
+}
+
+/// Cap on changed files the TUI collects per worktree; `None` when `max_changed_files` is unset
+/// or 0.
+pub(crate) fn load_max_changed_files() -> Option<usize> {
+    load_config().max_changed_files.filter(|max| *max > 0)
+}
+
+/// How long the idle TUI waits for input between wakeups; `None` when `poll_ms` is unset or 0.
+pub(crate) fn load_poll_ms() -> Option<u64> {
+    load_config().poll_ms.filter(|ms| *ms > 0)
+}
+
 pub(crate) fn check_updates_enabled() -> bool {
     load_config().check_updates.unwrap_or(true)
 }
@@ -175,11 +477,15 @@ pub(crate) fn github_prefix_enabled() -> bool {
     load_config().github_prefix.unwrap_or(true)
 }
 
-pub(crate) fn default_branch_name(worktree_name: &str) -> String {
+fn branch_prefix() -> Option<String> {
     if !github_prefix_enabled() {
-        return worktree_name.to_string();
+        return None;
     }
-    let Some(username) = gh_username() else {
+    gh_username()
+}
+
+pub(crate) fn default_branch_name(worktree_name: &str) -> String {
+    let Some(username) = branch_prefix() else {
         return worktree_name.to_string();
     };
     let candidate = format!("{}/{}", username, worktree_name);
@@ -190,6 +496,44 @@ pub(crate) fn default_branch_name(worktree_name: &str) -> String {
     }
 }
 
+/// `<prefix>/<key>-<name>` for a ticket key such as `ABC-123`, or `<key>-<name>` when there is
+/// no prefix.
+pub(crate) fn ticket_branch_name(prefix: Option<&str>, key: &str, worktree_name: &str) -> String {
+    let branch = format!("{}-{}", key.trim(), worktree_name);
+    match prefix {
+        Some(prefix) => format!("{prefix}/{branch}"),
+        None => branch,
+    }
+}
+
+/// Like `default_branch_name`, with the ticket key in front of the worktree name.
+pub(crate) fn default_ticket_branch_name(worktree_name: &str, key: &str) -> Result<String, String> {
+    let branch = ticket_branch_name(branch_prefix().as_deref(), key, worktree_name);
+    bbq::validate_branch_name(&branch)?;
+    Ok(branch)
+}
+
+pub(crate) fn config_file_exists() -> bool {
+    config_path().map(|path| path.exists()).unwrap_or(false)
+}
+
+pub(crate) fn save_root_dir(value: &str) -> io::Result<()> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "path required"));
+    }
+    let root = paths::expand_root_dir(value).map_err(io::Error::other)?;
+    if root.is_relative() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "path must be absolute or start with ~/",
+        ));
+    }
+    fs::create_dir_all(&root)?;
+    set_config_value("root_dir", value)?;
+    paths::ensure_root_dirs().map_err(io::Error::other)
+}
+
 pub(crate) fn save_theme_name(name: &str) -> io::Result<()> {
     set_config_value("theme", name)
 }
@@ -199,16 +543,143 @@ pub(crate) fn save_check_updates(enabled: bool) -> io::Result<()> {
     set_config_value("check_updates", value)
 }
 
+pub(crate) fn save_github_prefix(enabled: bool) -> io::Result<()> {
+    let value = if enabled { "true" } else { "false" };
+    set_config_value("github_user_prefix", value)
+}
+
 pub(crate) fn save_known_latest_version(value: &str) -> io::Result<()> {
     set_config_value("known_latest_version", value)
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConfigValueKind {
+    Text,
+    Bool,
+    Number,
+    List,
+}
+
+/// Top-level options `bbq config get/set` accept, with the value printed when they're unset.
+/// `root_dir` and `theme` are resolved separately so the env override and theme default apply.
+const CONFIG_KEYS: [(&str, ConfigValueKind, &str); 28] = [
+    ("root_dir", ConfigValueKind::Text, ""),
+    ("theme", ConfigValueKind::Text, ""),
+    ("editor", ConfigValueKind::Text, ""),
+    ("editor_new_window", ConfigValueKind::Bool, "false"),
+    ("open_order", ConfigValueKind::List, "zed,cursor,vscode"),
+    ("terminal", ConfigValueKind::Text, ""),
+    ("github_user_prefix", ConfigValueKind::Bool, "true"),
+    ("default_worktree_name", ConfigValueKind::Text, ""),
+    ("ignore_repos", ConfigValueKind::List, ""),
+    ("notify_on_complete", ConfigValueKind::Bool, "false"),
+    ("confirm_deletes", ConfigValueKind::Bool, "true"),
+    ("show_env", ConfigValueKind::Bool, "true"),
+    ("branch_prefix_interactive", ConfigValueKind::Bool, "false"),
+    ("hide_empty_repos", ConfigValueKind::Bool, "false"),
+    ("tree_show_churn", ConfigValueKind::Bool, "false"),
+    ("expand_paths", ConfigValueKind::Bool, "false"),
+    ("fuzzy_match", ConfigValueKind::Bool, "false"),
+    ("tree_glyphs", ConfigValueKind::Text, "unicode"),
+    ("tree_indent", ConfigValueKind::Number, "2"),
+    ("auto_fetch_minutes", ConfigValueKind::Number, ""),
+    ("max_changed_files", ConfigValueKind::Number, ""),
+    ("poll_ms", ConfigValueKind::Number, "1000"),
+    ("worktree_name_template", ConfigValueKind::Text, "{branch_tail}"),
+    ("clone_args", ConfigValueKind::List, ""),
+    ("commit_graph", ConfigValueKind::Bool, "true"),
+    ("network_retries", ConfigValueKind::Number, "2"),
+    ("check_updates", ConfigValueKind::Bool, "true"),
+    ("known_latest_version", ConfigValueKind::Text, ""),
+];
+
+fn config_key(key: &str) -> io::Result<(ConfigValueKind, &'static str)> {
+    CONFIG_KEYS
+        .iter()
+        .find(|(name, _, _)| *name == key)
+        .map(|(_, kind, default)| (*kind, *default))
+        .ok_or_else(|| {
+            let names: Vec<&str> = CONFIG_KEYS.iter().map(|(name, _, _)| *name).collect();
+            invalid_input(format!("unknown config key `{key}`; valid keys: {}", names.join(", ")))
+        })
+}
+
+/// Effective value of a config key: the config file's value if it parses, else the default.
+/// Lists print comma-separated, the same form `set_config_key` accepts.
+pub(crate) fn get_config_key(key: &str) -> io::Result<String> {
+    let (kind, default) = config_key(key)?;
+    match key {
+        "root_dir" => {
+            let root = paths::bbq_root().map_err(io::Error::other)?;
+            return Ok(root.display().to_string());
+        }
+        "theme" => return Ok(THEMES[load_theme_index()].name.to_string()),
+        _ => {}
+    }
+    let value = paths::config_value(key).and_then(|raw| match kind {
+        ConfigValueKind::Text => Some(raw).filter(|value| !value.is_empty()),
+        ConfigValueKind::Bool => parse_bool(&raw).map(|enabled| enabled.to_string()),
+        ConfigValueKind::Number => raw.trim().parse::<u64>().ok().map(|n| n.to_string()),
+        ConfigValueKind::List => Some(parse_config_list(&raw).join(",")),
+    });
+    Ok(value.unwrap_or_else(|| default.to_string()))
+}
+
+/// Validates `value` for `key` and writes it to the config file. List values are given
+/// comma-separated and stored as a TOML array.
+pub(crate) fn set_config_key(key: &str, value: &str) -> io::Result<()> {
+    let (kind, _) = config_key(key)?;
+    match key {
+        "root_dir" => return save_root_dir(value),
+        "theme" if theme_index_by_name(value).is_none() => {
+            let names: Vec<&str> = THEMES.iter().map(|theme| theme.name).collect();
+            return Err(invalid_input(format!(
+                "unknown theme `{value}`; valid themes: {}",
+                names.join(", ")
+            )));
+        }
+        _ => {}
+    }
+    match kind {
+        ConfigValueKind::Text => set_config_value(key, value.trim()),
+        ConfigValueKind::Bool => match parse_bool(value) {
+            Some(enabled) => set_config_value(key, &enabled.to_string()),
+            None => Err(invalid_input(format!("`{key}` expects true or false"))),
+        },
+        ConfigValueKind::Number => match value.trim().parse::<u64>() {
+            Ok(number) => set_config_value(key, &number.to_string()),
+            Err(_) => Err(invalid_input(format!("`{key}` expects a whole number"))),
+        },
+        ConfigValueKind::List => {
+            let items: Vec<String> = value
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| format!("\"{}\"", escape_toml_string(item)))
+                .collect();
+            set_config_raw(key, &format!("[{}]", items.join(", ")))
+        }
+    }
+}
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
 #[derive(Debug, Default, Clone)]
 pub(crate) struct RestoreState {
     pub(crate) expanded_repos: Vec<String>,
     pub(crate) selected_repo: Option<String>,
     pub(crate) selected_worktree_repo: Option<String>,
     pub(crate) selected_worktree_name: Option<String>,
+    /// Favorite worktrees as `(repo, worktree name)` pairs.
+    pub(crate) favorite_worktrees: Vec<(String, String)>,
+    /// Environment box visibility toggled in the TUI, overriding `show_env` when set.
+    pub(crate) show_env: Option<bool>,
+    /// Most recently opened worktree as `(repo, worktree name)`, for `bbq open`.
+    pub(crate) last_opened: Option<(String, String)>,
+    /// Labels attached to worktrees, keyed by `(repo, worktree name)`.
+    pub(crate) worktree_labels: HashMap<(String, String), Vec<String>>,
 }
 
 pub(crate) fn load_restore_state() -> RestoreState {
@@ -221,8 +692,28 @@ pub(crate) fn load_restore_state() -> RestoreState {
     parse_restore(&contents)
 }
 
+/// Remembers `(repo, name)` as the last opened worktree without touching the rest of the
+/// restore state.
+pub(crate) fn record_last_opened(repo: &str, name: &str) -> io::Result<()> {
+    let _lock = acquire_state_lock().map_err(io::Error::other)?;
+    let mut state = load_restore_state();
+    state.last_opened = Some((repo.to_string(), name.to_string()));
+    write_restore_state(&state)
+}
+
+/// Writes the TUI's restore state, keeping `last_opened` as it is on disk: only
+/// `record_last_opened` sets it, and `bbq worktree open` may have done so since the TUI loaded.
 pub(crate) fn save_restore_state(state: &RestoreState) -> io::Result<()> {
-    let path = restore_path().map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+    let _lock = acquire_state_lock().map_err(io::Error::other)?;
+    let state = RestoreState {
+        last_opened: load_restore_state().last_opened,
+        ..state.clone()
+    };
+    write_restore_state(&state)
+}
+
+fn write_restore_state(state: &RestoreState) -> io::Result<()> {
+    let path = restore_path().map_err(io::Error::other)?;
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)?;
     }
@@ -237,6 +728,46 @@ pub(crate) fn save_restore_state(state: &RestoreState) -> io::Result<()> {
             .join(", ");
         lines.push(format!("expanded = [{items}]"));
     }
+    if !state.favorite_worktrees.is_empty() {
+        let items = state
+            .favorite_worktrees
+            .iter()
+            .map(|(repo, name)| {
+                format!("\"{}/{}\"", escape_toml_string(repo), escape_toml_string(name))
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        lines.push(format!("favorites = [{items}]"));
+    }
+    if !state.worktree_labels.is_empty() {
+        let mut labeled: Vec<_> = state.worktree_labels.iter().collect();
+        labeled.sort();
+        let items = labeled
+            .into_iter()
+            .flat_map(|((repo, name), labels)| {
+                labels.iter().map(move |label| {
+                    format!(
+                        "\"{}/{}:{}\"",
+                        escape_toml_string(repo),
+                        escape_toml_string(name),
+                        escape_toml_string(label)
+                    )
+                })
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        lines.push(format!("labels = [{items}]"));
+    }
+    if let Some(show_env) = state.show_env {
+        lines.push(format!("show_env = {show_env}"));
+    }
+    if let Some((repo, name)) = state.last_opened.as_ref() {
+        lines.push(format!(
+            "last_opened = \"{}/{}\"",
+            escape_toml_string(repo),
+            escape_toml_string(name)
+        ));
+    }
     if let Some(repo) = state.selected_repo.as_ref() {
         lines.push(format!(
             "selected_repo = \"{}\"",
@@ -260,7 +791,22 @@ pub(crate) fn save_restore_state(state: &RestoreState) -> io::Result<()> {
     if !output.is_empty() {
         output.push('\n');
     }
-    fs::write(path, output)
+    write_atomically(&path, &output)
+}
+
+/// Writes `<path>.tmp` and renames it over `path`, so a crash leaves either the old or the new
+/// file rather than a truncated one.
+fn write_atomically(path: &Path, contents: &str) -> io::Result<()> {
+    // Follow a symlinked config (for example into a dotfiles repo) instead of replacing it.
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut tmp = path.as_os_str().to_os_string();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    let mut file = fs::File::create(&tmp)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()?;
+    drop(file);
+    fs::rename(&tmp, &path)
 }
 
 fn config_path() -> Result<PathBuf, bbq::BbqError> {
@@ -272,21 +818,32 @@ fn restore_path() -> Result<PathBuf, bbq::BbqError> {
 }
 
 fn set_config_value(key: &str, value: &str) -> io::Result<()> {
-    let path = config_path().map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+    set_config_raw(key, &format!("\"{}\"", escape_toml_string(value)))
+}
+
+/// Writes `key = <rendered>` with the value already in TOML form.
+fn set_config_raw(key: &str, rendered: &str) -> io::Result<()> {
+    // Held across the read-modify-write so concurrent writers can't drop each other's keys.
+    let _lock = acquire_state_lock().map_err(io::Error::other)?;
+    let path = config_path().map_err(io::Error::other)?;
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)?;
     }
 
     let mut lines = Vec::new();
     let mut found = false;
+    let mut first_section = None;
 
     if let Ok(contents) = fs::read_to_string(&path) {
         for line in contents.lines() {
             let trimmed = line.trim();
-            if !trimmed.is_empty() && !trimmed.starts_with('#') && !trimmed.starts_with('[') {
+            if trimmed.starts_with('[') && first_section.is_none() {
+                first_section = Some(lines.len());
+            }
+            if first_section.is_none() && !trimmed.is_empty() && !trimmed.starts_with('#') {
                 if let Some((existing, _)) = trimmed.split_once('=') {
                     if existing.trim() == key {
-                        lines.push(format!("{key} = \"{value}\""));
+                        lines.push(format!("{key} = {rendered}"));
                         found = true;
                         continue;
                     }
@@ -297,22 +854,17 @@ fn set_config_value(key: &str, value: &str) -> io::Result<()> {
     }
 
     if !found {
-        lines.push(format!("{key} = \"{value}\""));
+        // Top-level keys must come before any table header such as `[keys]`.
+        let line = format!("{key} = {rendered}");
+        match first_section {
+            Some(index) => lines.insert(index, line),
+            None => lines.push(line),
+        }
     }
 
     let mut output = lines.join("\n");
     output.push('\n');
-    fs::write(path, output)
-}
-
-fn trim_quotes(value: &str) -> String {
-    let trimmed = value.trim();
-    let without = trimmed
-        .trim_start_matches('"')
-        .trim_end_matches('"')
-        .trim_start_matches('\'')
-        .trim_end_matches('\'');
-    without.to_string()
+    write_atomically(&path, &output)
 }
 
 fn escape_toml_string(value: &str) -> String {
@@ -336,10 +888,44 @@ fn parse_restore(contents: &str) -> RestoreState {
         let value = value.trim();
 
         match key {
-            "expanded" => state.expanded_repos = parse_string_list(value),
-            "selected_repo" => state.selected_repo = Some(trim_quotes(value)),
-            "selected_worktree_repo" => state.selected_worktree_repo = Some(trim_quotes(value)),
-            "selected_worktree_name" => state.selected_worktree_name = Some(trim_quotes(value)),
+            "expanded" => state.expanded_repos = parse_config_list(value),
+            "favorites" => {
+                state.favorite_worktrees = parse_config_list(value)
+                    .into_iter()
+                    .filter_map(|item| {
+                        let (repo, name) = item.split_once('/')?;
+                        Some((repo.to_string(), name.to_string()))
+                    })
+                    .collect();
+            }
+            "labels" => {
+                for item in parse_config_list(value) {
+                    let Some((repo, rest)) = item.split_once('/') else {
+                        continue;
+                    };
+                    let Some((name, label)) = rest.split_once(':') else {
+                        continue;
+                    };
+                    state
+                        .worktree_labels
+                        .entry((repo.to_string(), name.to_string()))
+                        .or_default()
+                        .push(label.to_string());
+                }
+            }
+            "show_env" => state.show_env = parse_bool(value),
+            "last_opened" => {
+                state.last_opened = parse_config_string(value)
+                    .split_once('/')
+                    .map(|(repo, name)| (repo.to_string(), name.to_string()));
+            }
+            "selected_repo" => state.selected_repo = Some(parse_config_string(value)),
+            "selected_worktree_repo" => {
+                state.selected_worktree_repo = Some(parse_config_string(value));
+            }
+            "selected_worktree_name" => {
+                state.selected_worktree_name = Some(parse_config_string(value));
+            }
             _ => {}
         }
     }
@@ -347,17 +933,24 @@ fn parse_restore(contents: &str) -> RestoreState {
     state
 }
 
-fn parse_string_list(value: &str) -> Vec<String> {
-    let trimmed = value.trim();
-    if !(trimmed.starts_with('[') && trimmed.ends_with(']')) {
-        return Vec::new();
+/// Splits user input on whitespace and commas into sorted, de-duplicated worktree labels.
+pub(crate) fn parse_labels(input: &str) -> Result<Vec<String>, String> {
+    let mut labels = Vec::new();
+    for label in input.split(|ch: char| ch.is_whitespace() || ch == ',') {
+        if label.is_empty() {
+            continue;
+        }
+        if !label
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' || ch == '.')
+        {
+            return Err(format!("Label '{label}' can only use letters, numbers, '-', '_', or '.'"));
+        }
+        labels.push(label.to_string());
     }
-    let inner = &trimmed[1..trimmed.len() - 1];
-    inner
-        .split(',')
-        .map(|item| trim_quotes(item))
-        .filter(|item| !item.is_empty())
-        .collect()
+    labels.sort();
+    labels.dedup();
+    Ok(labels)
 }
 
 static GH_USERNAME_CACHE: OnceLock<Mutex<Option<String>>> = OnceLock::new();
@@ -377,6 +970,9 @@ fn gh_username() -> Option<String> {
 }
 
 fn gh_username_uncached() -> Option<String> {
+    if bbq::offline_enabled() {
+        return None;
+    }
     let output = Command::new("gh")
         .args(["api", "user", "-q", ".login"])
         .output()
@@ -403,7 +999,7 @@ fn clear_github_username_cache() {
 }
 
 fn parse_bool(value: &str) -> Option<bool> {
-    let normalized = trim_quotes(value).trim().to_ascii_lowercase();
+    let normalized = parse_config_string(value).trim().to_ascii_lowercase();
     match normalized.as_str() {
         "true" | "yes" | "on" | "1" => Some(true),
         "false" | "no" | "off" | "0" => Some(false),
@@ -415,13 +1011,18 @@ fn parse_bool(value: &str) -> Option<bool> {
 mod tests {
     use super::{
         clear_github_username_cache, default_branch_name,
-        default_worktree_name_is_configured, load_default_worktree_name_mode,
+        default_worktree_name_is_configured, github_prefix_enabled, load_config,
+        load_default_worktree_name_mode, load_key_bindings, load_repo_env, load_repo_git_config,
+        load_restore_state, parse_labels, record_last_opened, save_github_prefix,
+        save_restore_state, save_root_dir, save_theme_name, set_config_value, ticket_branch_name,
+        RestoreState,
     };
     use bbq::DefaultWorktreeNameMode;
     use std::ffi::OsString;
     use std::fs;
     use std::path::{Path, PathBuf};
-    use std::sync::Mutex;
+    use std::sync::{Arc, Barrier, Mutex};
+    use std::thread;
     use std::time::{SystemTime, UNIX_EPOCH};
 
     static TEST_MUTEX: Mutex<()> = Mutex::new(());
@@ -500,6 +1101,250 @@ mod tests {
         cleanup_root(&root);
     }
 
+    #[test]
+    fn key_bindings_load_from_keys_section() {
+        let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+        let root = unique_root("key_bindings_load_from_keys_section");
+        let home = root.join("home");
+        fs::create_dir_all(&home).expect("create home");
+        let _home_env = EnvGuard::set("HOME", &home);
+
+        write_config(&home, "editor = \"zed\"\n\n[keys]\ndelete = \"x\"\n");
+        save_theme_name("blue").expect("save theme");
+
+        let bindings = load_key_bindings();
+        assert_eq!(bindings, vec![("delete".to_string(), "x".to_string())]);
+        let config = load_config();
+        assert_eq!(config.theme.as_deref(), Some("blue"));
+        assert_eq!(config.editor.as_deref(), Some("zed"));
+
+        cleanup_root(&root);
+    }
+
+    #[test]
+    fn save_github_prefix_persists_toggle() {
+        let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+        let root = unique_root("save_github_prefix_persists_toggle");
+        let home = root.join("home");
+        fs::create_dir_all(&home).expect("create home");
+        let _home_env = EnvGuard::set("HOME", &home);
+
+        write_config(&home, "theme = \"blue\"\ngithub_user_prefix = true\n");
+        assert!(github_prefix_enabled());
+
+        save_github_prefix(false).expect("save github prefix");
+        assert!(!github_prefix_enabled());
+        assert_eq!(load_config().theme.as_deref(), Some("blue"));
+
+        save_github_prefix(true).expect("save github prefix");
+        assert!(github_prefix_enabled());
+
+        cleanup_root(&root);
+    }
+
+    #[test]
+    fn save_root_dir_writes_config_and_moves_repos_root() {
+        let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+        let root = unique_root("save_root_dir_writes_config_and_moves_repos_root");
+        let home = root.join("home");
+        fs::create_dir_all(&home).expect("create home");
+        let _home_env = EnvGuard::set("HOME", &home);
+        let _root_env = EnvGuard::unset("BBQ_ROOT_DIR");
+
+        save_root_dir("~/code/bbq").expect("save root dir");
+
+        let contents =
+            fs::read_to_string(home.join(".bbq").join("config.toml")).expect("read config");
+        assert!(contents.contains("root_dir = \"~/code/bbq\""));
+        let repos_root = bbq::paths::repos_root().expect("repos root");
+        assert_eq!(repos_root, home.join("code").join("bbq").join("repos"));
+        assert!(repos_root.is_dir());
+
+        assert!(save_root_dir("relative/path").is_err());
+
+        cleanup_root(&root);
+    }
+
+    #[test]
+    fn concurrent_config_writes_keep_both_keys() {
+        let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+        let root = unique_root("concurrent_config_writes_keep_both_keys");
+        let home = root.join("home");
+        fs::create_dir_all(&home).expect("create home");
+        let _home_env = EnvGuard::set("HOME", &home);
+
+        let barrier = Arc::new(Barrier::new(2));
+        let writers: Vec<_> = ["alpha", "beta"]
+            .into_iter()
+            .map(|key| {
+                let barrier = Arc::clone(&barrier);
+                thread::spawn(move || {
+                    barrier.wait();
+                    for round in 0..20 {
+                        set_config_value(key, &round.to_string()).expect("set config value");
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().expect("join writer");
+        }
+
+        let contents =
+            fs::read_to_string(home.join(".bbq").join("config.toml")).expect("read config");
+        assert!(contents.contains("alpha = \"19\""), "{contents}");
+        assert!(contents.contains("beta = \"19\""), "{contents}");
+        drop(bbq::acquire_state_lock().expect("lock is free again"));
+
+        cleanup_root(&root);
+    }
+
+    #[test]
+    fn config_writes_go_through_temp_file() {
+        let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+        let root = unique_root("config_writes_go_through_temp_file");
+        let home = root.join("home");
+        fs::create_dir_all(&home).expect("create home");
+        let _home_env = EnvGuard::set("HOME", &home);
+        let config = home.join(".bbq").join("config.toml");
+        let tmp = home.join(".bbq").join("config.toml.tmp");
+
+        write_config(&home, "theme = \"blue\"\neditor = \"zed\"\n");
+        // A leftover temp file from an interrupted write is replaced, not appended to.
+        fs::write(&tmp, "garbage").expect("write stale temp file");
+        save_theme_name("orange").expect("save theme");
+        let contents = fs::read_to_string(&config).expect("read config");
+        assert_eq!(contents, "theme = \"orange\"\neditor = \"zed\"\n");
+        assert!(!tmp.exists());
+
+        // When the temp file can't be written, the existing config is left untouched.
+        fs::create_dir_all(&tmp).expect("block temp file");
+        assert!(save_theme_name("blue").is_err());
+        assert_eq!(fs::read_to_string(&config).expect("read config"), contents);
+
+        cleanup_root(&root);
+    }
+
+    #[test]
+    fn worktree_labels_round_trip_through_restore_state() {
+        let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+        let root = unique_root("worktree_labels_round_trip_through_restore_state");
+        let home = root.join("home");
+        fs::create_dir_all(&home).expect("create home");
+        let _home_env = EnvGuard::set("HOME", &home);
+
+        let mut state = RestoreState {
+            favorite_worktrees: vec![("app".to_string(), "lima".to_string())],
+            ..RestoreState::default()
+        };
+        state.worktree_labels.insert(
+            ("app".to_string(), "lima".to_string()),
+            vec!["review".to_string(), "spike".to_string()],
+        );
+        state
+            .worktree_labels
+            .insert(("web".to_string(), "oslo".to_string()), vec!["spike".to_string()]);
+        save_restore_state(&state).expect("save restore state");
+
+        let loaded = load_restore_state();
+        assert_eq!(loaded.worktree_labels, state.worktree_labels);
+        assert_eq!(loaded.favorite_worktrees, state.favorite_worktrees);
+
+        cleanup_root(&root);
+    }
+
+    #[test]
+    fn saving_restore_state_keeps_the_recorded_last_opened() {
+        let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+        let root = unique_root("saving_restore_state_keeps_the_recorded_last_opened");
+        let home = root.join("home");
+        fs::create_dir_all(&home).expect("create home");
+        let _home_env = EnvGuard::set("HOME", &home);
+
+        record_last_opened("app", "lima").expect("record last opened");
+        let stale = RestoreState {
+            expanded_repos: vec!["app".to_string()],
+            last_opened: Some(("web".to_string(), "oslo".to_string())),
+            ..RestoreState::default()
+        };
+        save_restore_state(&stale).expect("save restore state");
+
+        let loaded = load_restore_state();
+        assert_eq!(loaded.last_opened, Some(("app".to_string(), "lima".to_string())));
+        assert_eq!(loaded.expanded_repos, vec!["app".to_string()]);
+
+        cleanup_root(&root);
+    }
+
+    #[test]
+    fn ticket_branch_name_joins_prefix_key_and_name() {
+        assert_eq!(
+            ticket_branch_name(Some("team"), " ABC-123 ", "feature"),
+            "team/ABC-123-feature"
+        );
+        assert_eq!(ticket_branch_name(None, "ABC-123", "feature"), "ABC-123-feature");
+    }
+
+    #[test]
+    fn labels_parse_from_space_or_comma_separated_input() {
+        assert_eq!(
+            parse_labels("spike, review  spike"),
+            Ok(vec!["review".to_string(), "spike".to_string()])
+        );
+        assert_eq!(parse_labels("  "), Ok(Vec::new()));
+        assert!(parse_labels("bad:label").is_err());
+    }
+
+    #[test]
+    fn repo_env_reads_env_table() {
+        let root = unique_root("repo_env_reads_env_table");
+        let config_dir = root.join(".bbq");
+        fs::create_dir_all(&config_dir).expect("create repo config dir");
+        fs::write(
+            config_dir.join("repo.toml"),
+            "name = \"ignored\"\n\n[env]\nDATABASE_URL = \"postgres://localhost/app\"\n\
+             not-valid = \"x\"\nRUST_LOG = 'debug'\n",
+        )
+        .expect("write repo config");
+
+        let env = load_repo_env(&root);
+        assert_eq!(
+            env,
+            vec![
+                ("DATABASE_URL".to_string(), "postgres://localhost/app".to_string()),
+                ("RUST_LOG".to_string(), "debug".to_string()),
+            ]
+        );
+        assert!(load_repo_env(&root.join("missing")).is_empty());
+
+        cleanup_root(&root);
+    }
+
+    #[test]
+    fn repo_git_config_skips_invalid_keys() {
+        let root = unique_root("repo_git_config_skips_invalid_keys");
+        let config_dir = root.join(".bbq");
+        fs::create_dir_all(&config_dir).expect("create repo config dir");
+        fs::write(
+            config_dir.join("repo.toml"),
+            "[git-config]\n\"user.email\" = \"me@work.example\"\n\
+             \"url.git@host:.insteadOf\" = \"https://host/\"\nuser = \"x\"\n\
+             \"core.1bad\" = \"x\"\n\"core.sshCommand\" = \"x\"\n\"gpg.program\" = \"x\"\n\
+             \"gpg.format\" = \"ssh\"\n\n[env]\nFOO = \"bar\"\n",
+        )
+        .expect("write repo config");
+
+        assert_eq!(
+            load_repo_git_config(&root),
+            vec![
+                ("user.email".to_string(), "me@work.example".to_string()),
+                ("gpg.format".to_string(), "ssh".to_string()),
+            ]
+        );
+
+        cleanup_root(&root);
+    }
+
     #[test]
     fn default_worktree_name_is_configured_when_set() {
         let _guard = TEST_MUTEX.lock().expect("lock test mutex");
@@ -598,6 +1443,12 @@ mod tests {
             std::env::set_var(key, value);
             Self { key, prev }
         }
+
+        fn unset(key: &'static str) -> Self {
+            let prev = std::env::var_os(key);
+            std::env::remove_var(key);
+            Self { key, prev }
+        }
     }
 
     impl Drop for EnvGuard {
@@ -609,6 +1460,4 @@ mod tests {
             }
         }
     }
-
-    // No additional helpers needed beyond set and set_str.
 }
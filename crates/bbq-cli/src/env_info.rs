@@ -0,0 +1,136 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use bbq::{list_repos, paths};
+use serde_json::{json, Value};
+
+use crate::config::expand_paths_enabled;
+
+pub(crate) fn home_dir_path() -> Option<PathBuf> {
+    dirs::home_dir()
+}
+
+pub(crate) fn display_path_with_tilde(path: &Path, home: &Path) -> String {
+    if path == home {
+        return "~".to_string();
+    }
+    if !path.starts_with(home) {
+        // Either side may sit behind a symlink; compare resolved paths before giving up.
+        if let (Ok(path), Ok(home)) = (path.canonicalize(), home.canonicalize()) {
+            if path.starts_with(&home) {
+                return display_path_with_tilde(&path, &home);
+            }
+        }
+    }
+    if let Ok(suffix) = path.strip_prefix(home) {
+        let suffix = suffix.to_string_lossy();
+        if suffix.is_empty() {
+            "~".to_string()
+        } else {
+            format!("~/{}", suffix)
+        }
+    } else {
+        path.display().to_string()
+    }
+}
+
+/// How the TUI shows a path: absolute with `expand_paths`, otherwise with the home directory
+/// folded to `~`.
+pub(crate) fn display_path(path: &Path, home: Option<&Path>, expand: bool) -> String {
+    match home {
+        Some(home) if !expand => display_path_with_tilde(path, home),
+        _ => path.display().to_string(),
+    }
+}
+
+pub(crate) fn bbq_root_display() -> Option<String> {
+    let bbq_root = paths::bbq_root().ok()?;
+    Some(display_path(&bbq_root, home_dir_path().as_deref(), expand_paths_enabled()))
+}
+
+/// The config file as named in hints and messages, with the home directory folded to `~`.
+pub(crate) fn config_path_display() -> String {
+    match paths::config_path() {
+        Ok(path) => tilde_path(&path),
+        Err(_) => "config.toml".to_string(),
+    }
+}
+
+pub(crate) fn tilde_path(path: &Path) -> String {
+    match home_dir_path() {
+        Some(home) => display_path_with_tilde(path, &home),
+        None => path.display().to_string(),
+    }
+}
+
+pub(crate) fn command_version(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    extract_version(&stdout)
+}
+
+fn extract_version(output: &str) -> Option<String> {
+    for raw in output.split_whitespace() {
+        let trimmed = raw.trim_matches(|ch: char| ch == ',' || ch == ';');
+        let trimmed = trimmed.trim_start_matches('v');
+        if !trimmed.chars().any(|ch| ch.is_ascii_digit()) {
+            continue;
+        }
+        let mut cleaned = String::new();
+        for ch in trimmed.chars() {
+            if ch.is_ascii_digit() || ch == '.' || ch == '-' {
+                cleaned.push(ch);
+            } else {
+                break;
+            }
+        }
+        if cleaned.chars().any(|ch| ch.is_ascii_digit()) {
+            return Some(cleaned);
+        }
+    }
+    None
+}
+
+pub(crate) fn gh_authenticated() -> bool {
+    Command::new("gh")
+        .args(["auth", "status"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+pub(crate) fn env_report() -> bbq::Result<Value> {
+    let root = paths::bbq_root()?;
+    let repos_count = list_repos()?.len();
+    let gh_version = command_version("gh", &["--version"]);
+    let gh_authenticated = gh_version.is_some() && gh_authenticated();
+    Ok(json!({
+        "root": root.to_string_lossy(),
+        "git_version": command_version("git", &["--version"]),
+        "gh_version": gh_version,
+        "gh_authenticated": gh_authenticated,
+        "repos_count": repos_count,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::display_path;
+
+    #[test]
+    fn display_path_folds_home_unless_expanded() {
+        let home = Path::new("/home/ada");
+        let path = Path::new("/home/ada/.bbq/worktrees/api/lima");
+        assert_eq!(display_path(path, Some(home), false), "~/.bbq/worktrees/api/lima");
+        assert_eq!(display_path(path, Some(home), true), "/home/ada/.bbq/worktrees/api/lima");
+        assert_eq!(display_path(path, None, false), "/home/ada/.bbq/worktrees/api/lima");
+    }
+}
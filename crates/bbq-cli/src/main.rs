@@ -1,5 +1,9 @@
 mod cli;
+mod clipboard;
+mod color;
+mod completion;
 mod config;
+mod env_info;
 mod open;
 mod theme;
 mod tui;
@@ -8,12 +12,24 @@ mod update;
 use std::io::{self, IsTerminal};
 
 use clap::{CommandFactory, Parser};
+use clap_complete::CompleteEnv;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    CompleteEnv::with_factory(cli::Cli::command)
+        .var(completion::COMPLETE_VAR)
+        .complete();
     let cli = cli::Cli::parse();
+    bbq::set_offline(cli.offline || env_flag("BBQ_OFFLINE"));
 
     if let Some(command) = cli.command {
-        return cli::run_command(command);
+        bbq::set_verbose(cli.verbose || env_flag("BBQ_VERBOSE"));
+        color::set_color_choice(cli.color);
+        let result = cli::run_command(command);
+        if let (Err(err), cli::ErrorFormat::Json) = (&result, cli.error_format) {
+            eprintln!("{}", cli::error_json(err.as_ref()));
+            std::process::exit(1);
+        }
+        return result;
     }
 
     let is_tty = io::stdin().is_terminal() && io::stdout().is_terminal();
@@ -25,3 +41,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         Ok(())
     }
 }
+
+fn env_flag(key: &str) -> bool {
+    let Ok(value) = std::env::var(key) else {
+        return false;
+    };
+    matches!(
+        value.trim().to_ascii_lowercase().as_str(),
+        "1" | "true" | "yes" | "on"
+    )
+}
@@ -1,5 +1,6 @@
+use std::ffi::OsString;
 use std::io;
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
 use std::process::{Command, Stdio};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -30,6 +31,14 @@ impl OpenTarget {
         }
     }
 
+    /// Flags that make the editor open a fresh window instead of reusing the last one.
+    pub(crate) fn new_window_args(self) -> &'static [&'static str] {
+        match self {
+            OpenTarget::Zed => &["--new"],
+            OpenTarget::Cursor | OpenTarget::VsCode => &["--new-window"],
+        }
+    }
+
     pub(crate) fn from_config(value: &str) -> Option<Self> {
         let normalized = normalize_target(value);
         match normalized.as_str() {
@@ -41,6 +50,52 @@ impl OpenTarget {
     }
 }
 
+/// A file inside a worktree to open instead of the worktree directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FileTarget {
+    pub(crate) path: PathBuf,
+    pub(crate) line: Option<u32>,
+}
+
+/// Parses `relpath[:line]` and checks that it names an existing file inside `worktree`.
+pub(crate) fn resolve_file_target(worktree: &Path, spec: &str) -> io::Result<FileTarget> {
+    let spec = spec.trim();
+    let (relative, line) = match spec.rsplit_once(':') {
+        Some((relative, line))
+            if !line.is_empty() && line.chars().all(|ch| ch.is_ascii_digit()) =>
+        {
+            (relative, line.parse::<u32>().ok())
+        }
+        _ => (spec, None),
+    };
+    let relative = Path::new(relative);
+    let escapes = relative
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir));
+    if relative.as_os_str().is_empty() || escapes {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("file must be a relative path inside the worktree: {spec}"),
+        ));
+    }
+
+    let root = worktree.canonicalize()?;
+    let path = root.join(relative).canonicalize().map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("file not found in worktree: {}", relative.display()),
+        )
+    })?;
+    if !path.starts_with(&root) || !path.is_file() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("not a file inside the worktree: {}", relative.display()),
+        ));
+    }
+
+    Ok(FileTarget { path, line })
+}
+
 pub(crate) fn detect_open_targets() -> Vec<OpenTarget> {
     OpenTarget::all()
         .into_iter()
@@ -48,6 +103,47 @@ pub(crate) fn detect_open_targets() -> Vec<OpenTarget> {
         .collect()
 }
 
+/// Available targets with the configured `order` first; targets missing from `order` keep their
+/// default position after it.
+pub(crate) fn detect_open_targets_in_order(order: &[OpenTarget]) -> Vec<OpenTarget> {
+    let available = detect_open_targets();
+    order_open_targets(&available, order)
+}
+
+/// How to open a worktree when no `editor` is configured.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum OpenCommand {
+    Target(OpenTarget),
+    /// A `$VISUAL`/`$EDITOR` command, run inside a new terminal window.
+    TerminalEditor(String),
+}
+
+/// The first available GUI target, or else `$VISUAL` then `$EDITOR` as looked up by `var`.
+pub(crate) fn resolve_open_command(
+    available: &[OpenTarget],
+    var: impl Fn(&str) -> Option<String>,
+) -> Option<OpenCommand> {
+    if let Some(target) = available.first() {
+        return Some(OpenCommand::Target(*target));
+    }
+    ["VISUAL", "EDITOR"]
+        .into_iter()
+        .filter_map(var)
+        .map(|value| value.trim().to_string())
+        .find(|value| !value.is_empty())
+        .map(OpenCommand::TerminalEditor)
+}
+
+fn order_open_targets(available: &[OpenTarget], order: &[OpenTarget]) -> Vec<OpenTarget> {
+    let mut ordered: Vec<OpenTarget> = Vec::new();
+    for target in order.iter().chain(OpenTarget::all().iter()) {
+        if available.contains(target) && !ordered.contains(target) {
+            ordered.push(*target);
+        }
+    }
+    ordered
+}
+
 fn command_available(program: &str) -> bool {
     Command::new("sh")
         .args(["-lc", &format!("command -v {}", program)])
@@ -66,42 +162,366 @@ pub(crate) fn normalize_target(value: &str) -> String {
         .collect()
 }
 
-pub(crate) fn open_in_target(target: OpenTarget, path: &Path) -> io::Result<()> {
-    let mut command = Command::new(target.command());
-    command.arg(path);
+pub(crate) fn open_in_target(
+    target: OpenTarget,
+    path: &Path,
+    env: &[(String, String)],
+    new_window: bool,
+) -> io::Result<()> {
+    spawn_editor(editor_command(target.command(), target, path, None, new_window), env)
+}
+
+pub(crate) fn open_file_in_target(
+    target: OpenTarget,
+    file: &FileTarget,
+    env: &[(String, String)],
+    new_window: bool,
+) -> io::Result<()> {
+    let command = editor_command(target.command(), target, &file.path, file.line, new_window);
+    spawn_editor(command, env)
+}
+
+pub(crate) fn open_in_editor(
+    command: &str,
+    path: &Path,
+    terminal: Option<&str>,
+    env: &[(String, String)],
+    new_window: bool,
+) -> io::Result<()> {
+    if new_window {
+        if let Some((program, target)) = known_editor(command) {
+            return spawn_editor(editor_command(program, target, path, None, true), env);
+        }
+    }
+    if is_terminal_editor(command) {
+        return open_in_terminal_editor(command, path, None, terminal, env);
+    }
+    run_command_with_path(command, path, env).map_err(|err| editor_spawn_error(command, err))
+}
+
+/// Terminal editors need a terminal of their own, so they open in a new `terminal` rooted at
+/// `worktree`.
+pub(crate) fn open_file_in_editor(
+    command: &str,
+    worktree: &Path,
+    file: &FileTarget,
+    terminal: Option<&str>,
+    env: &[(String, String)],
+    new_window: bool,
+) -> io::Result<()> {
+    if let Some((program, target)) = known_editor(command) {
+        let command = editor_command(program, target, &file.path, file.line, new_window);
+        return spawn_editor(command, env);
+    }
+    if is_terminal_editor(command) {
+        return open_in_terminal_editor(command, worktree, Some(file), terminal, env);
+    }
+    run_command_with_path(command, &file.path, env)
+        .map_err(|err| editor_spawn_error(command, err))
+}
+
+/// The program and matching target when `command` is a bare zed/cursor/code executable.
+fn known_editor(command: &str) -> Option<(&str, OpenTarget)> {
+    let program = single_program(command)?;
+    OpenTarget::from_config(&program_name(program)).map(|target| (program, target))
+}
+
+fn single_program(command: &str) -> Option<&str> {
+    let program = command.trim();
+    if program.is_empty() || program.chars().any(|ch| ch.is_whitespace()) {
+        return None;
+    }
+    Some(program)
+}
+
+fn program_name(program: &str) -> String {
+    Path::new(program)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Editors that run inside a terminal rather than opening a window of their own.
+fn is_terminal_editor(command: &str) -> bool {
+    let program = command.split_whitespace().next().map(program_name);
+    matches!(program.as_deref(), Some("vi" | "vim" | "nvim" | "hx" | "nano"))
+}
+
+fn editor_command(
+    program: &str,
+    target: OpenTarget,
+    path: &Path,
+    line: Option<u32>,
+    new_window: bool,
+) -> Command {
+    let mut command = Command::new(program);
+    if new_window {
+        command.args(target.new_window_args());
+    }
+    match (target, line) {
+        (OpenTarget::Cursor | OpenTarget::VsCode, Some(line)) => {
+            command.arg("--goto").arg(path_with_line(path, line));
+        }
+        (OpenTarget::Zed, Some(line)) => {
+            command.arg(path_with_line(path, line));
+        }
+        (_, None) => {
+            command.arg(path);
+        }
+    }
+    command
+}
+
+fn path_with_line(path: &Path, line: u32) -> OsString {
+    let mut value = path.as_os_str().to_os_string();
+    value.push(format!(":{line}"));
+    value
+}
+
+fn spawn_editor(mut command: Command, env: &[(String, String)]) -> io::Result<()> {
+    command.envs(env.iter().map(|(key, value)| (key, value)));
     command.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null());
-    command.spawn()?;
+    command
+        .spawn()
+        .map_err(|err| editor_spawn_error(&command.get_program().to_string_lossy(), err))?;
     Ok(())
 }
 
-pub(crate) fn open_in_editor(command: &str, path: &Path) -> io::Result<()> {
-    run_command_with_path(command, path)
+/// A missing editor binary otherwise surfaces as a bare "No such file or directory".
+fn editor_spawn_error(command: &str, err: io::Error) -> io::Error {
+    missing_program_error("editor", command, err)
+}
+
+fn terminal_spawn_error(command: &str, err: io::Error) -> io::Error {
+    missing_program_error("terminal", command, err)
+}
+
+fn missing_program_error(key: &str, command: &str, err: io::Error) -> io::Error {
+    if err.kind() != io::ErrorKind::NotFound {
+        return err;
+    }
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!(
+            "{key} `{}` not found; pick another in settings (`,` in the TUI) or with \
+             `bbq config set {key} <command>`",
+            command_program(command)
+        ),
+    )
+}
+
+/// The program a command line runs, skipping leading `VAR=value` assignments.
+fn command_program(command: &str) -> &str {
+    command
+        .split_whitespace()
+        .find(|word| !word.contains('='))
+        .unwrap_or(command)
+}
+
+/// Sessions are named `{repo}_{name}` so same-named worktrees of different repos don't share
+/// one. tmux rejects `.` and `:` in session names, so both parts are sanitized first.
+pub(crate) fn tmux_session_name(repo: &str, name: &str) -> String {
+    format!("{repo}_{name}")
+        .chars()
+        .map(|ch| if matches!(ch, '.' | ':') { '-' } else { ch })
+        .collect()
+}
+
+/// `tmux new-session` arguments for a detached session rooted at `path`, with the repo's
+/// `[env]` set inside it.
+fn tmux_new_session_args(session: &str, path: &Path, env: &[(String, String)]) -> Vec<OsString> {
+    let mut args: Vec<OsString> = ["new-session", "-d", "-s", session, "-c"]
+        .iter()
+        .map(OsString::from)
+        .collect();
+    args.push(path.as_os_str().to_os_string());
+    for (key, value) in env {
+        args.push(OsString::from("-e"));
+        args.push(OsString::from(format!("{key}={value}")));
+    }
+    args
+}
+
+/// Attaches to a tmux session named after the repo and worktree, creating it in `path` first
+/// if it doesn't exist. Inside tmux this switches the current client instead of nesting.
+pub(crate) fn open_in_tmux(
+    repo: &str,
+    name: &str,
+    path: &Path,
+    env: &[(String, String)],
+) -> io::Result<()> {
+    if !command_available("tmux") {
+        return Err(io::Error::new(io::ErrorKind::NotFound, "tmux not found"));
+    }
+    let session = tmux_session_name(repo, name);
+    let exists = Command::new("tmux")
+        .args(["has-session", "-t", &format!("={session}")])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()?
+        .success();
+    if !exists {
+        let status = Command::new("tmux")
+            .args(tmux_new_session_args(&session, path, env))
+            .status()?;
+        if !status.success() {
+            return Err(io::Error::other(format!("tmux new-session failed: {status}")));
+        }
+    }
+    let attach = if std::env::var_os("TMUX").is_some() {
+        "switch-client"
+    } else {
+        "attach-session"
+    };
+    Command::new("tmux")
+        .args([attach, "-t", &format!("={session}")])
+        .status()?;
+    Ok(())
 }
 
 pub(crate) fn open_terminal_at_path_with_config(
     path: &Path,
     terminal_command: Option<&str>,
+    env: &[(String, String)],
 ) -> io::Result<()> {
     let Some(command) = terminal_command.map(str::trim).filter(|value| !value.is_empty()) else {
-        return open_terminal_at_path(path);
+        return open_terminal_at_path(path, env);
     };
 
-    if open_app_with_path(command, path)? {
+    if open_app_with_path(command, path, env)? {
         return Ok(());
     }
 
-    run_command_with_path(command, path)
+    run_command_with_path(command, path, env).map_err(|err| terminal_spawn_error(command, err))
+}
+
+/// Opens a terminal in `path` running `editor` on `file`, or on the directory itself. That is
+/// the configured `terminal` when there is one, and the first detected terminal otherwise.
+pub(crate) fn open_in_terminal_editor(
+    editor: &str,
+    path: &Path,
+    file: Option<&FileTarget>,
+    terminal: Option<&str>,
+    env: &[(String, String)],
+) -> io::Result<()> {
+    let command_line = terminal_editor_command_line(editor, path, file, env)?;
+    match terminal.map(str::trim).filter(|value| !value.is_empty()) {
+        Some(terminal) => open_configured_terminal_running(terminal, path, env, &command_line)
+            .map_err(|err| terminal_spawn_error(terminal, err)),
+        None => open_terminal_running(path, env, &command_line),
+    }
+}
+
+/// Runs `command_line` in the configured `terminal`, after the emulator's flag for running a
+/// command: the one listed in `TERMINAL_EMULATORS`, or `-e` for emulators not listed there.
+fn open_configured_terminal_running(
+    terminal: &str,
+    path: &Path,
+    env: &[(String, String)],
+    command_line: &str,
+) -> io::Result<()> {
+    let program = command_program(terminal);
+    if !command_available(program) {
+        return Err(io::Error::new(io::ErrorKind::NotFound, format!("{program} not found")));
+    }
+    let name = program_name(program);
+    let exec_args = TERMINAL_EMULATORS
+        .iter()
+        .find(|(emulator, ..)| *emulator == name)
+        .map_or(&["-e"][..], |(_, _, exec_args)| *exec_args);
+    let mut full = terminal.to_string();
+    for arg in exec_args {
+        full.push(' ');
+        full.push_str(arg);
+    }
+    full.push_str(&format!(" sh -lc {}", shell_escape(command_line)));
+    Command::new("sh")
+        .args(["-lc", &full])
+        .current_dir(path)
+        .envs(env.iter().map(|(key, value)| (key, value)))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    Ok(())
+}
+
+/// `editor` is a shell command line like `$EDITOR` itself, so it is spliced in unescaped.
+fn terminal_editor_command_line(
+    editor: &str,
+    path: &Path,
+    file: Option<&FileTarget>,
+    env: &[(String, String)],
+) -> io::Result<String> {
+    let mut command_line = format!("{} && exec {}", terminal_command_line(path, env)?, editor);
+    match file {
+        Some(file) => {
+            let path = utf8_path(&file.path)?;
+            let program = editor.split_whitespace().next().map(program_name);
+            match (file.line, program.as_deref()) {
+                // Helix takes the line as a `path:line` suffix rather than a `+line` flag.
+                (Some(line), Some("hx")) => {
+                    command_line.push_str(&format!(" {}", shell_escape(&format!("{path}:{line}"))));
+                }
+                (Some(line), Some("vi" | "vim" | "nvim" | "nano" | "emacs")) => {
+                    command_line.push_str(&format!(" +{line} {}", shell_escape(path)));
+                }
+                _ => command_line.push_str(&format!(" {}", shell_escape(path))),
+            }
+        }
+        None => command_line.push_str(" ."),
+    }
+    Ok(command_line)
+}
+
+/// Shell command line that exports `env` and then changes into `path`.
+fn terminal_command_line(path: &Path, env: &[(String, String)]) -> io::Result<String> {
+    let mut command_line = String::new();
+    for (key, value) in env {
+        command_line.push_str(&format!("export {}={}; ", key, shell_escape(value)));
+    }
+    command_line.push_str(&format!("cd {}", shell_escape(utf8_path(path)?)));
+    Ok(command_line)
+}
+
+/// Paths spliced into shell command lines must be UTF-8; a lossy conversion would `cd` into
+/// the wrong directory.
+fn utf8_path(path: &Path) -> io::Result<&str> {
+    path.to_str().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("path is not valid UTF-8: {}", path.display()),
+        )
+    })
 }
 
 #[cfg(target_os = "macos")]
-fn open_terminal_at_path(path: &Path) -> io::Result<()> {
-    let command_line = format!("cd {}", shell_escape(&path.to_string_lossy()));
+fn open_terminal_at_path(path: &Path, env: &[(String, String)]) -> io::Result<()> {
+    let command_line = terminal_command_line(path, env)?;
     open_terminal_command_line(&command_line)
 }
 
 #[cfg(not(target_os = "macos"))]
-fn open_terminal_at_path(path: &Path) -> io::Result<()> {
-    open_terminal_at_path_unix(path)
+fn open_terminal_at_path(path: &Path, env: &[(String, String)]) -> io::Result<()> {
+    open_terminal_at_path_unix(path, env, None)
+}
+
+#[cfg(target_os = "macos")]
+fn open_terminal_running(
+    _path: &Path,
+    _env: &[(String, String)],
+    command_line: &str,
+) -> io::Result<()> {
+    open_terminal_command_line(command_line)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn open_terminal_running(
+    path: &Path,
+    env: &[(String, String)],
+    command_line: &str,
+) -> io::Result<()> {
+    open_terminal_at_path_unix(path, env, Some(command_line))
 }
 
 #[cfg(target_os = "macos")]
@@ -122,23 +542,36 @@ fn open_terminal_command_line(command_line: &str) -> io::Result<()> {
     ))
 }
 
+/// Terminal emulators bbq looks for, in order: each one's working-directory flag, then the flag
+/// that runs a command in it.
+const TERMINAL_EMULATORS: &[(&str, &[&str], &[&str])] = &[
+    ("wezterm", &["start", "--cwd"], &["--"]),
+    ("alacritty", &["--working-directory"], &["-e"]),
+    ("kitty", &["--directory"], &[]),
+    ("gnome-terminal", &["--working-directory"], &["--"]),
+    ("konsole", &["--workdir"], &["-e"]),
+    ("xfce4-terminal", &["--working-directory"], &["-x"]),
+    ("x-terminal-emulator", &["--working-directory"], &["-e"]),
+];
+
+/// Opens a terminal emulator in `path`, running `command_line` through `sh` instead of a plain
+/// shell when given.
 #[cfg(not(target_os = "macos"))]
-fn open_terminal_at_path_unix(path: &Path) -> io::Result<()> {
-    let candidates: &[(&str, &[&str])] = &[
-        ("wezterm", &["start", "--cwd"]),
-        ("alacritty", &["--working-directory"]),
-        ("kitty", &["--directory"]),
-        ("gnome-terminal", &["--working-directory"]),
-        ("konsole", &["--workdir"]),
-        ("xfce4-terminal", &["--working-directory"]),
-        ("x-terminal-emulator", &["--working-directory"]),
-    ];
-
-    for (command, args) in candidates {
+fn open_terminal_at_path_unix(
+    path: &Path,
+    env: &[(String, String)],
+    command_line: Option<&str>,
+) -> io::Result<()> {
+    for (command, args, exec_args) in TERMINAL_EMULATORS {
         if command_available(command) {
             let mut cmd = Command::new(command);
             cmd.args(*args);
             cmd.arg(path);
+            if let Some(command_line) = command_line {
+                cmd.args(*exec_args);
+                cmd.args(["sh", "-lc", command_line]);
+            }
+            cmd.envs(env.iter().map(|(key, value)| (key, value)));
             cmd.stdin(Stdio::null())
                 .stdout(Stdio::null())
                 .stderr(Stdio::null());
@@ -148,12 +581,13 @@ fn open_terminal_at_path_unix(path: &Path) -> io::Result<()> {
     }
 
     if command_available("xterm") {
-        let shell = std::env::var("SHELL").unwrap_or_else(|_| "sh".to_string());
-        let command_line = format!(
-            "cd {} && exec {}",
-            shell_escape(&path.to_string_lossy()),
-            shell
-        );
+        let command_line = match command_line {
+            Some(command_line) => command_line.to_string(),
+            None => {
+                let shell = std::env::var("SHELL").unwrap_or_else(|_| "sh".to_string());
+                format!("{} && exec {}", terminal_command_line(path, env)?, shell)
+            }
+        };
         Command::new("xterm")
             .args(["-e", "sh", "-lc", &command_line])
             .stdin(Stdio::null())
@@ -165,11 +599,44 @@ fn open_terminal_at_path_unix(path: &Path) -> io::Result<()> {
 
     Err(io::Error::new(
         io::ErrorKind::NotFound,
-        "no terminal emulator found; configure terminal in ~/.bbq/config.toml",
+        format!(
+            "no terminal emulator found; configure terminal in {}",
+            crate::env_info::config_path_display()
+        ),
     ))
 }
 
-fn run_command_with_path(command: &str, path: &Path) -> io::Result<()> {
+/// Linux file managers to try, in order; each takes the directory as its only argument.
+const LINUX_FILE_MANAGERS: [&str; 3] = ["xdg-open", "nautilus", "dolphin"];
+
+fn pick_file_manager(macos: bool, available: impl Fn(&str) -> bool) -> Option<&'static str> {
+    if macos {
+        return Some("open");
+    }
+    LINUX_FILE_MANAGERS.into_iter().find(|program| available(program))
+}
+
+pub(crate) fn reveal_in_file_manager(path: &Path) -> io::Result<()> {
+    let program = pick_file_manager(cfg!(target_os = "macos"), command_available).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            "no file manager found; install xdg-open, nautilus, or dolphin",
+        )
+    })?;
+    Command::new(program)
+        .arg(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    Ok(())
+}
+
+fn run_command_with_path(
+    command: &str,
+    path: &Path,
+    env: &[(String, String)],
+) -> io::Result<()> {
     let command = command.trim();
     if command.is_empty() {
         return Err(io::Error::new(
@@ -179,14 +646,15 @@ fn run_command_with_path(command: &str, path: &Path) -> io::Result<()> {
     }
 
     if command.chars().any(|ch| ch.is_whitespace()) {
-        if open_app_with_path(command, path)? {
+        if open_app_with_path(command, path, env)? {
             return Ok(());
         }
-        return run_shell_command_with_path(command, path);
+        return run_shell_command_with_path(command, path, env);
     }
 
     Command::new(command)
         .arg(path)
+        .envs(env.iter().map(|(key, value)| (key, value)))
         .stdin(Stdio::null())
         .stdout(Stdio::null())
         .stderr(Stdio::null())
@@ -195,8 +663,12 @@ fn run_command_with_path(command: &str, path: &Path) -> io::Result<()> {
 }
 
 #[cfg(target_os = "macos")]
-fn open_app_with_path(app: &str, path: &Path) -> io::Result<bool> {
-    let status = Command::new("open")
+fn open_app_with_path(app: &str, path: &Path, env: &[(String, String)]) -> io::Result<bool> {
+    let mut command = Command::new("open");
+    for (key, value) in env {
+        command.arg("--env").arg(format!("{key}={value}"));
+    }
+    let status = command
         .arg("-a")
         .arg(app)
         .arg(path)
@@ -207,14 +679,25 @@ fn open_app_with_path(app: &str, path: &Path) -> io::Result<bool> {
 }
 
 #[cfg(not(target_os = "macos"))]
-fn open_app_with_path(_app: &str, _path: &Path) -> io::Result<bool> {
+fn open_app_with_path(_app: &str, _path: &Path, _env: &[(String, String)]) -> io::Result<bool> {
     Ok(false)
 }
 
-fn run_shell_command_with_path(command: &str, path: &Path) -> io::Result<()> {
-    let full = format!("{} {}", command, shell_escape(&path.to_string_lossy()));
+fn run_shell_command_with_path(
+    command: &str,
+    path: &Path,
+    env: &[(String, String)],
+) -> io::Result<()> {
+    // The shell is always there, so check the program up front; `sh` would only report a
+    // missing one after it had been detached.
+    let program = command_program(command);
+    if !command_available(program) {
+        return Err(io::Error::new(io::ErrorKind::NotFound, format!("{program} not found")));
+    }
+    let full = format!("{} {}", command, shell_escape(utf8_path(path)?));
     Command::new("sh")
         .args(["-lc", &full])
+        .envs(env.iter().map(|(key, value)| (key, value)))
         .stdin(Stdio::null())
         .stdout(Stdio::null())
         .stderr(Stdio::null())
@@ -222,7 +705,7 @@ fn run_shell_command_with_path(command: &str, path: &Path) -> io::Result<()> {
     Ok(())
 }
 
-fn shell_escape(value: &str) -> String {
+pub(crate) fn shell_escape(value: &str) -> String {
     if value.is_empty() {
         return "''".to_string();
     }
@@ -249,3 +732,179 @@ fn shell_escape(value: &str) -> String {
 fn escape_applescript(value: &str) -> String {
     value.replace('\\', "\\\\").replace('"', "\\\"")
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use std::fs;
+
+    use super::{
+        editor_command, order_open_targets, pick_file_manager, resolve_file_target,
+        resolve_open_command, terminal_command_line, terminal_editor_command_line,
+        tmux_new_session_args, tmux_session_name, FileTarget, OpenCommand, OpenTarget,
+    };
+
+    fn args_for(
+        target: OpenTarget,
+        path: &str,
+        line: Option<u32>,
+        new_window: bool,
+    ) -> Vec<String> {
+        editor_command(target.command(), target, Path::new(path), line, new_window)
+            .get_args()
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect()
+    }
+
+    fn command_args(target: OpenTarget, new_window: bool) -> Vec<String> {
+        args_for(target, "/tmp/wt", None, new_window)
+    }
+
+    #[test]
+    fn file_manager_uses_open_on_macos_and_falls_back_in_order_on_linux() {
+        assert_eq!(pick_file_manager(true, |_| false), Some("open"));
+        assert_eq!(pick_file_manager(false, |_| true), Some("xdg-open"));
+        assert_eq!(pick_file_manager(false, |program| program != "xdg-open"), Some("nautilus"));
+        assert_eq!(pick_file_manager(false, |program| program == "dolphin"), Some("dolphin"));
+        assert_eq!(pick_file_manager(false, |_| false), None);
+    }
+
+    #[test]
+    fn file_with_line_uses_goto_for_vscode() {
+        assert_eq!(
+            args_for(OpenTarget::VsCode, "/tmp/wt/src/main.rs", Some(42), false),
+            vec!["--goto", "/tmp/wt/src/main.rs:42"]
+        );
+        assert_eq!(
+            args_for(OpenTarget::Zed, "/tmp/wt/src/main.rs", Some(42), false),
+            vec!["/tmp/wt/src/main.rs:42"]
+        );
+        assert_eq!(
+            args_for(OpenTarget::Cursor, "/tmp/wt/src/main.rs", None, true),
+            vec!["--new-window", "/tmp/wt/src/main.rs"]
+        );
+    }
+
+    #[test]
+    fn tmux_session_starts_detached_in_worktree() {
+        let env = vec![("RUST_LOG".to_string(), "debug".to_string())];
+        let session = tmux_session_name("web.app", "fix.login:v2");
+        assert_eq!(session, "web-app_fix-login-v2");
+        let args: Vec<String> = tmux_new_session_args(&session, Path::new("/tmp/wt"), &env)
+            .iter()
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect();
+        assert_eq!(
+            args,
+            vec![
+                "new-session",
+                "-d",
+                "-s",
+                "web-app_fix-login-v2",
+                "-c",
+                "/tmp/wt",
+                "-e",
+                "RUST_LOG=debug"
+            ]
+        );
+    }
+
+    #[test]
+    fn file_target_must_stay_inside_worktree() {
+        let root = std::env::temp_dir().join(format!("bbq-file-target-{}", std::process::id()));
+        let worktree = root.join("wt");
+        fs::create_dir_all(worktree.join("src")).expect("create worktree");
+        fs::write(worktree.join("src/main.rs"), "fn main() {}\n").expect("write file");
+        fs::write(root.join("secret.txt"), "nope").expect("write outside file");
+
+        let file = resolve_file_target(&worktree, "src/main.rs:12").expect("resolve file");
+        assert!(file.path.ends_with("src/main.rs"));
+        assert_eq!(file.line, Some(12));
+        let file = resolve_file_target(&worktree, "src/main.rs").expect("resolve file");
+        assert_eq!(file.line, None);
+
+        assert!(resolve_file_target(&worktree, "../secret.txt").is_err());
+        assert!(resolve_file_target(&worktree, "/etc/passwd").is_err());
+        assert!(resolve_file_target(&worktree, "src").is_err());
+        assert!(resolve_file_target(&worktree, "missing.rs:3").is_err());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn new_window_adds_target_specific_flag() {
+        assert_eq!(command_args(OpenTarget::VsCode, true), vec!["--new-window", "/tmp/wt"]);
+        assert_eq!(command_args(OpenTarget::Cursor, true), vec!["--new-window", "/tmp/wt"]);
+        assert_eq!(command_args(OpenTarget::Zed, true), vec!["--new", "/tmp/wt"]);
+        assert_eq!(command_args(OpenTarget::VsCode, false), vec!["/tmp/wt"]);
+    }
+
+    #[test]
+    fn terminal_command_line_exports_repo_env() {
+        let env = vec![
+            ("DATABASE_URL".to_string(), "postgres://localhost/app dev".to_string()),
+            ("RUST_LOG".to_string(), "debug".to_string()),
+        ];
+        let line = terminal_command_line(Path::new("/tmp/my worktree"), &env).expect("line");
+        assert_eq!(
+            line,
+            "export DATABASE_URL='postgres://localhost/app dev'; export RUST_LOG=debug; \
+             cd '/tmp/my worktree'"
+        );
+        let line = terminal_command_line(Path::new("/tmp/wt"), &[]).expect("line");
+        assert_eq!(line, "cd /tmp/wt");
+    }
+
+    #[test]
+    fn editor_env_var_opens_in_terminal_without_gui_targets() {
+        let editor_only = |name: &str| (name == "EDITOR").then(|| "vi".to_string());
+        assert_eq!(
+            resolve_open_command(&[], editor_only),
+            Some(OpenCommand::TerminalEditor("vi".to_string()))
+        );
+        assert_eq!(
+            resolve_open_command(&[OpenTarget::Zed], editor_only),
+            Some(OpenCommand::Target(OpenTarget::Zed))
+        );
+        let both = |name: &str| Some(format!("{} --flag", name.to_lowercase()));
+        assert_eq!(
+            resolve_open_command(&[], both),
+            Some(OpenCommand::TerminalEditor("visual --flag".to_string()))
+        );
+        assert_eq!(resolve_open_command(&[], |_| Some(" ".to_string())), None);
+
+        let file = FileTarget {
+            path: Path::new("/tmp/wt/src/main.rs").to_path_buf(),
+            line: Some(7),
+        };
+        let line = terminal_editor_command_line("vi", Path::new("/tmp/wt"), Some(&file), &[])
+            .expect("command line");
+        assert_eq!(line, "cd /tmp/wt && exec vi +7 /tmp/wt/src/main.rs");
+        let line = terminal_editor_command_line("hx", Path::new("/tmp/wt"), Some(&file), &[])
+            .expect("command line");
+        assert_eq!(line, "cd /tmp/wt && exec hx /tmp/wt/src/main.rs:7");
+        let line = terminal_editor_command_line("vi", Path::new("/tmp/wt"), None, &[])
+            .expect("command line");
+        assert_eq!(line, "cd /tmp/wt && exec vi .");
+    }
+
+    #[test]
+    fn configured_order_picks_first_available_target() {
+        let available = [OpenTarget::Zed, OpenTarget::Cursor];
+        let order = [OpenTarget::VsCode, OpenTarget::Cursor];
+        let ordered = order_open_targets(&available, &order);
+        assert_eq!(ordered, vec![OpenTarget::Cursor, OpenTarget::Zed]);
+    }
+
+    #[test]
+    fn unspecified_targets_keep_default_order() {
+        let available = OpenTarget::all();
+        let ordered = order_open_targets(&available, &[OpenTarget::VsCode]);
+        assert_eq!(
+            ordered,
+            vec![OpenTarget::VsCode, OpenTarget::Zed, OpenTarget::Cursor]
+        );
+        assert_eq!(order_open_targets(&available, &[]), available.to_vec());
+    }
+}
@@ -1,23 +1,42 @@
 use ratatui::style::Color;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ThemeColor {
+    Rgb(u8, u8, u8),
+    /// One of the 16 palette colors, so the terminal's own scheme decides the exact shade.
+    Ansi(Color),
+}
+
 #[derive(Debug, Clone, Copy)]
 pub(crate) struct Theme {
     pub(crate) name: &'static str,
-    pub(crate) rgb: (u8, u8, u8),
+    pub(crate) value: ThemeColor,
 }
 
 impl Theme {
     pub(crate) const fn new(name: &'static str, rgb: (u8, u8, u8)) -> Self {
-        Self { name, rgb }
+        Self {
+            name,
+            value: ThemeColor::Rgb(rgb.0, rgb.1, rgb.2),
+        }
+    }
+
+    pub(crate) const fn ansi(name: &'static str, color: Color) -> Self {
+        Self {
+            name,
+            value: ThemeColor::Ansi(color),
+        }
     }
 
     pub(crate) fn color(&self) -> Color {
-        let (r, g, b) = self.rgb;
-        Color::Rgb(r, g, b)
+        match self.value {
+            ThemeColor::Rgb(r, g, b) => Color::Rgb(r, g, b),
+            ThemeColor::Ansi(color) => color,
+        }
     }
 }
 
-pub(crate) const THEMES: [Theme; 13] = [
+pub(crate) const THEMES: [Theme; 21] = [
     Theme::new("green", (0, 255, 0)),
     Theme::new("red", (255, 0, 0)),
     Theme::new("blue", (0, 0, 255)),
@@ -31,6 +50,14 @@ pub(crate) const THEMES: [Theme; 13] = [
     Theme::new("orange", (255, 165, 0)),
     Theme::new("violet", (148, 0, 211)),
     Theme::new("pink", (255, 105, 180)),
+    Theme::ansi("ansi-red", Color::Red),
+    Theme::ansi("ansi-green", Color::Green),
+    Theme::ansi("ansi-yellow", Color::Yellow),
+    Theme::ansi("ansi-blue", Color::Blue),
+    Theme::ansi("ansi-magenta", Color::Magenta),
+    Theme::ansi("ansi-cyan", Color::Cyan),
+    Theme::ansi("ansi-white", Color::White),
+    Theme::ansi("ansi-gray", Color::Gray),
 ];
 
 pub(crate) fn default_theme_index() -> usize {
@@ -45,3 +72,18 @@ pub(crate) fn theme_index_by_name(name: &str) -> Option<usize> {
         .iter()
         .position(|theme| theme.name.eq_ignore_ascii_case(name))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{theme_index_by_name, THEMES};
+    use ratatui::style::Color;
+
+    #[test]
+    fn ansi_theme_uses_named_color() {
+        let index = theme_index_by_name("ansi-green").expect("ansi-green theme");
+        assert_eq!(THEMES[index].color(), Color::Green);
+
+        let index = theme_index_by_name("green").expect("green theme");
+        assert_eq!(THEMES[index].color(), Color::Rgb(0, 255, 0));
+    }
+}
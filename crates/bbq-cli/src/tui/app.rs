@@ -1,36 +1,53 @@
 use std::collections::{HashMap, HashSet};
 use std::io;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc;
 use std::time::{Duration, Instant};
 
-use bbq::{Repo, Worktree};
+use bbq::{paths, ConflictState, Repo, Worktree};
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::widgets::ListState;
 
+use crate::clipboard::{
+    classify_clipboard, git_worktree_add_command, read_clipboard, ClipboardContent,
+};
 use crate::config::{
-    check_updates_enabled, default_branch_name, default_worktree_name_is_configured,
-    editor_is_configured, force_upgrade_prompt_enabled, known_latest_version,
-    load_default_worktree_name_mode, load_editor_command,
-    load_restore_state, load_terminal_command, load_theme_index, preload_github_username,
-    save_check_updates, save_default_worktree_name_mode, save_editor_command,
-    save_known_latest_version, save_restore_state, save_terminal_command, save_theme_name,
-    terminal_is_configured, RestoreState,
+    branch_prefix_interactive_enabled, check_updates_enabled, config_file_exists,
+    confirm_deletes_enabled, default_branch_name, default_ticket_branch_name,
+    default_worktree_name_is_configured, editor_is_configured, editor_new_window_enabled,
+    force_upgrade_prompt_enabled, github_prefix_enabled, hide_empty_repos_enabled,
+    known_latest_version, load_default_worktree_name_mode, load_editor_command, load_key_bindings,
+    load_open_order, load_repo_env, load_restore_state, load_terminal_command, load_theme_index,
+    load_tree_indent, load_worktree_name_template, notify_on_complete_enabled, parse_labels,
+    preload_github_username, record_last_opened, save_check_updates,
+    save_default_worktree_name_mode, save_editor_command, save_github_prefix,
+    save_known_latest_version, save_restore_state, save_root_dir, save_terminal_command,
+    save_theme_name, show_env_enabled, terminal_is_configured, theme_is_configured,
+    tree_glyphs_ascii_enabled, tree_show_churn_enabled, RestoreState,
 };
+use crate::env_info::tilde_path;
 use crate::open::{
-    detect_open_targets, open_in_editor, open_in_target, open_terminal_at_path_with_config,
+    detect_open_targets, detect_open_targets_in_order, open_in_editor, open_in_target,
+    open_in_terminal_editor, open_terminal_at_path_with_config, resolve_open_command,
+    reveal_in_file_manager, OpenCommand,
+};
+use crate::theme::{default_theme_index, theme_index_by_name, Theme, THEMES};
+use crate::tui::constants::{
+    ACTIVITY_LOG_MAX, ACTIVITY_LOG_TTL_MS, NOTIFY_AFTER_MS, STATUS_MAX_MS, STATUS_MIN_MS,
+    STATUS_PER_CHAR_MS, UNDO_STACK_MAX,
 };
-use crate::theme::{Theme, THEMES};
-use crate::tui::constants::{STATUS_MAX_MS, STATUS_MIN_MS, STATUS_PER_CHAR_MS};
+use crate::tui::keymap::{KeyAction, KeyMap};
 use crate::tui::worker::start_background_tasks;
 use crate::update;
-use bbq::{suggest_worktree_name, DefaultWorktreeNameMode};
+use bbq::{
+    suggest_worktree_name_with_template, worktree_name_from_branch, DefaultWorktreeNameMode,
+};
 use semver::Version;
 
 use super::types::{
-    EnvInfo, Focus, InputKind, InputState, LoadingGroup, LoadingMessage, LoadingPriority,
-    StatusMessage, StatusTone, TreeItem, TreeItemKind, TreeKey, WorkerEvent, WorkerRequest,
-    WorktreeEntry,
+    ActivityLog, BranchPicker, DeletedWorktree, EnvInfo, Focus, InputKind, InputState,
+    LoadingGroup, LoadingMessage, LoadingPriority, StatusMessage, StatusTone, TreeGlyphs,
+    TreeItem, TreeItemKind, TreeKey, WorkerEvent, WorkerRequest, WorktreeEntry,
 };
 
 const DEFAULT_SOURCE_BRANCH: &str = "origin/main";
@@ -43,14 +60,30 @@ pub(crate) struct App {
     repo_worktrees: HashMap<String, Vec<WorktreeEntry>>,
     pub(crate) repo_display: HashMap<String, String>,
     expanded_repos: HashSet<String>,
+    pub(crate) marked: HashSet<TreeKey>,
+    favorites: HashSet<TreeKey>,
+    /// Labels per `(repo, worktree name)`, persisted in `restore.toml`.
+    pub(crate) labels: HashMap<(String, String), Vec<String>>,
+    last_opened: Option<(String, String)>,
+    pub(crate) tree_glyphs: TreeGlyphs,
+    hide_empty_repos: bool,
+    /// Show each worktree's `+added -removed` line totals in the tree.
+    pub(crate) tree_show_churn: bool,
     focus: Focus,
     pub(crate) input: Option<InputState>,
+    pub(crate) branch_picker: Option<BranchPicker>,
     pub(crate) status: Option<StatusMessage>,
     loading: Vec<LoadingMessage>,
     theme_index: usize,
     editor_command: Option<String>,
     terminal_command: Option<String>,
     default_worktree_name_mode: Option<DefaultWorktreeNameMode>,
+    notify_on_complete: bool,
+    confirm_deletes: bool,
+    pub(crate) show_env: bool,
+    show_env_config: bool,
+    /// In the compact layout, show the worktree detail in place of the tree.
+    pub(crate) show_detail: bool,
     pub(crate) env_info: EnvInfo,
     worker_tx: mpsc::Sender<WorkerRequest>,
     worker_rx: mpsc::Receiver<WorkerEvent>,
@@ -61,13 +94,40 @@ pub(crate) struct App {
     desired_worktree_selection: Option<(String, String)>,
     setup: Option<SetupState>,
     setup_steps: Vec<SetupStep>,
+    settings: Option<SettingsState>,
     update_prompt: Option<UpdatePromptState>,
+    pending_undo: Option<DeletedWorktree>,
+    /// The undone delete being restored, whose favorite and labels go back on once it exists.
+    pending_restore: Option<DeletedWorktree>,
+    undo_stack: Vec<DeletedWorktree>,
+    pub(crate) keymap: KeyMap,
+    pub(crate) activity: ActivityLog,
+    script_started: Option<(String, Instant)>,
+    /// Set when something on screen may have changed since the last draw.
+    dirty: bool,
 }
 
 impl App {
     pub(crate) fn new() -> Self {
         preload_github_username();
         let (worker_tx, worker_rx) = start_background_tasks();
+        let mut app = Self::with_worker(worker_tx, worker_rx);
+        app.init_update_prompt();
+        app.init_setup_state();
+        app.apply_restore_state();
+        app.request_env_info();
+        app.request_update_check();
+        app.request_all_data(false);
+        app
+    }
+
+    /// The app with its settings loaded, talking to a worker over `worker_tx` and `worker_rx`.
+    /// `new` starts the real worker and asks it for the first data.
+    fn with_worker(
+        worker_tx: mpsc::Sender<WorkerRequest>,
+        worker_rx: mpsc::Receiver<WorkerEvent>,
+    ) -> Self {
+        let (keymap, keymap_warnings) = KeyMap::from_bindings(&load_key_bindings());
         let mut app = Self {
             repos: Vec::new(),
             tree_items: Vec::new(),
@@ -75,14 +135,31 @@ impl App {
             repo_worktrees: HashMap::new(),
             repo_display: HashMap::new(),
             expanded_repos: HashSet::new(),
+            marked: HashSet::new(),
+            favorites: HashSet::new(),
+            labels: HashMap::new(),
+            last_opened: None,
+            tree_glyphs: if tree_glyphs_ascii_enabled() {
+                TreeGlyphs::ascii(load_tree_indent())
+            } else {
+                TreeGlyphs::unicode(load_tree_indent())
+            },
+            hide_empty_repos: hide_empty_repos_enabled(),
+            tree_show_churn: tree_show_churn_enabled(),
             focus: Focus::List,
             input: None,
+            branch_picker: None,
             status: None,
             loading: Vec::new(),
             theme_index: load_theme_index(),
             editor_command: load_editor_command(),
             terminal_command: load_terminal_command(),
             default_worktree_name_mode: load_default_worktree_name_mode(),
+            notify_on_complete: notify_on_complete_enabled(),
+            confirm_deletes: confirm_deletes_enabled(),
+            show_env: show_env_enabled(),
+            show_env_config: show_env_enabled(),
+            show_detail: false,
             env_info: EnvInfo::default(),
             worker_tx,
             worker_rx,
@@ -93,37 +170,40 @@ impl App {
             desired_worktree_selection: None,
             setup: None,
             setup_steps: Vec::new(),
+            settings: None,
             update_prompt: None,
+            pending_undo: None,
+            pending_restore: None,
+            undo_stack: Vec::new(),
+            keymap,
+            activity: ActivityLog::new(
+                ACTIVITY_LOG_MAX,
+                Duration::from_millis(ACTIVITY_LOG_TTL_MS),
+            ),
+            script_started: None,
+            dirty: true,
         };
-
-        app.init_update_prompt();
-        app.init_setup_state();
-        app.apply_restore_state();
-        app.request_env_info();
-        app.request_update_check();
-        app.request_all_data(false);
+        if !keymap_warnings.is_empty() {
+            app.set_error(keymap_warnings.join("; "));
+        }
         app
     }
 
     fn init_setup_state(&mut self) {
-        self.setup_steps.clear();
-        if !default_worktree_name_is_configured() {
-            self.setup_steps.push(SetupStep::DefaultWorktreeName);
-        }
-        if !editor_is_configured() {
-            self.setup_steps.push(SetupStep::Editor);
-        }
-        if !terminal_is_configured() {
-            self.setup_steps.push(SetupStep::Terminal);
-        }
+        self.setup_steps = pending_setup_steps(|step| match step {
+            SetupStep::RootDir => {
+                config_file_exists() || std::env::var_os("BBQ_ROOT_DIR").is_some()
+            }
+            SetupStep::Theme => theme_is_configured(),
+            SetupStep::DefaultWorktreeName => default_worktree_name_is_configured(),
+            SetupStep::Editor => editor_is_configured(),
+            SetupStep::Terminal => terminal_is_configured(),
+        });
         self.start_setup_step();
     }
 
     fn init_update_prompt(&mut self) {
-        if !check_updates_enabled() {
-            return;
-        }
-        if !force_upgrade_prompt_enabled() && !update::is_homebrew_install() {
+        if !update_check_allowed(bbq::offline_enabled()) {
             return;
         }
         let Some(latest) = known_latest_version() else {
@@ -140,6 +220,16 @@ impl App {
     fn apply_restore_state(&mut self) {
         let state = load_restore_state();
         self.expanded_repos = state.expanded_repos.into_iter().collect();
+        self.favorites = state
+            .favorite_worktrees
+            .into_iter()
+            .map(|(repo, name)| TreeKey::Worktree { repo, name })
+            .collect();
+        self.labels = state.worktree_labels;
+        if let Some(show_env) = state.show_env {
+            self.show_env = show_env;
+        }
+        self.last_opened = state.last_opened;
         self.desired_repo_selection = None;
         self.desired_worktree_selection = None;
         if let (Some(repo), Some(name)) = (state.selected_worktree_repo, state.selected_worktree_name)
@@ -158,7 +248,32 @@ impl App {
         self.setup = Some(SetupState::from_step(step));
     }
 
+    /// Handles a key in the tree view and returns whether to quit. Keys that do nothing there
+    /// leave the screen as it was.
     pub(crate) fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let is_quit = key.modifiers.contains(KeyModifiers::CONTROL)
+            && key.code == KeyCode::Char('c')
+            && self.keymap.action_for(&key).is_none();
+        if is_quit {
+            return true;
+        }
+        if self.run_list_key(key) {
+            self.dirty = true;
+        }
+        false
+    }
+
+    /// Runs whatever `key` is bound to in the tree view, returning whether it was bound.
+    fn run_list_key(&mut self, key: KeyEvent) -> bool {
+        if is_abort_key(&key) && self.loading_message(LoadingGroup::Action).is_some() {
+            self.abort_action();
+            return true;
+        }
+        if let Some(action) = self.keymap.action_for(&key) {
+            self.run_key_action(action);
+            return true;
+        }
+
         match key.code {
             KeyCode::Left => self.collapse_selected(),
             KeyCode::Right | KeyCode::Tab => self.expand_selected(),
@@ -172,31 +287,77 @@ impl App {
                 }
             }
             KeyCode::Char(' ') if key.modifiers.is_empty() => self.toggle_selected_repo(),
-            KeyCode::Char('c') if key.modifiers.is_empty() => self.open_checkout_prompt(),
-            KeyCode::Char('n') if key.modifiers.is_empty() => self.open_worktree_prompt(),
-            KeyCode::Char('d') if key.modifiers.is_empty() => self.open_delete_prompt(),
-            KeyCode::Char('t') if key.modifiers.is_empty() => self.open_selected_in_terminal(),
-            KeyCode::Char('h') if key.modifiers.is_empty() => self.cycle_theme(1),
-            KeyCode::Char('h') if key.modifiers.contains(KeyModifiers::SHIFT) => {
-                self.cycle_theme(-1)
-            }
-            KeyCode::Char('H') => self.cycle_theme(-1),
             KeyCode::Esc => self.clear_status(),
-            _ => {
-                if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
-                    return true;
-                }
-            }
+            _ => return false,
         }
+        true
+    }
 
-        false
+    /// Only clones and scripts can be aborted; other actions keep running. The worker reports
+    /// an aborted one as a failed action, which clears the loading message.
+    fn abort_action(&mut self) {
+        if bbq::abort_running_command() {
+            self.set_loading(LoadingGroup::Action, "Aborting", LoadingPriority::Action);
+            self.dirty = true;
+        }
+    }
+
+    fn run_key_action(&mut self, action: KeyAction) {
+        match action {
+            KeyAction::Clone => self.open_checkout_prompt(),
+            KeyAction::New => self.open_worktree_prompt(),
+            KeyAction::FromClipboard => self.open_clipboard_worktree_prompt(),
+            KeyAction::Delete => self.open_delete_prompt(),
+            KeyAction::Terminal => self.open_selected_in_terminal(),
+            KeyAction::Editor => self.open_selected_in_editor(),
+            KeyAction::Reveal => self.reveal_selected_in_file_manager(),
+            KeyAction::OpenRecent => self.open_recent(),
+            KeyAction::CopyGitCommand => self.copy_git_worktree_command(),
+            KeyAction::RenameBranch => self.open_rename_branch_prompt(),
+            KeyAction::Mark => self.toggle_selected_mark(),
+            KeyAction::Favorite => self.toggle_selected_favorite(),
+            KeyAction::Label => self.open_labels_prompt(),
+            KeyAction::FetchAll => self.fetch_all_repos(),
+            KeyAction::Settings => self.open_settings(),
+            KeyAction::ToggleEnv => self.toggle_env_box(),
+            KeyAction::ToggleDetail => self.toggle_detail_pane(),
+            KeyAction::Undo => self.undo_last_delete(),
+            KeyAction::ThemeNext => self.cycle_theme(1),
+            KeyAction::ThemePrev => self.cycle_theme(-1),
+            KeyAction::PrevRepo => self.jump_to_repo(false),
+            KeyAction::NextRepo => self.jump_to_repo(true),
+        }
     }
 
     pub(crate) fn handle_setup_key(&mut self, key: KeyEvent) -> bool {
+        self.dirty = true;
         if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
             return true;
         }
 
+        if let Some(buffer) = self
+            .setup
+            .as_mut()
+            .and_then(|setup| setup.custom_input.as_mut())
+        {
+            match key.code {
+                KeyCode::Esc => {
+                    if let Some(setup) = self.setup.as_mut() {
+                        setup.custom_input = None;
+                    }
+                }
+                KeyCode::Enter => self.apply_custom_root_dir(),
+                KeyCode::Backspace => {
+                    buffer.pop();
+                }
+                KeyCode::Char(ch) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    buffer.push(ch);
+                }
+                _ => {}
+            }
+            return false;
+        }
+
         match key.code {
             KeyCode::Up => {
                 if let Some(setup) = self.setup.as_mut() {
@@ -215,7 +376,45 @@ impl App {
         false
     }
 
+    pub(crate) fn handle_settings_key(&mut self, key: KeyEvent) -> bool {
+        self.dirty = true;
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return true;
+        }
+        let Some(settings) = self.settings.as_mut() else {
+            return false;
+        };
+
+        let change = match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.settings = None;
+                return false;
+            }
+            KeyCode::Char(ch) if ch == self.keymap.key_for(KeyAction::Settings) => {
+                self.settings = None;
+                return false;
+            }
+            KeyCode::Up => {
+                settings.move_selection(-1);
+                None
+            }
+            KeyCode::Down => {
+                settings.move_selection(1);
+                None
+            }
+            KeyCode::Left => settings.cycle(-1),
+            KeyCode::Right | KeyCode::Enter | KeyCode::Char(' ') => settings.cycle(1),
+            _ => None,
+        };
+        if let Some((field, value)) = change {
+            self.apply_setting(field, value);
+        }
+
+        false
+    }
+
     pub(crate) fn handle_update_prompt_key(&mut self, key: KeyEvent) -> bool {
+        self.dirty = true;
         if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
             return true;
         }
@@ -243,8 +442,13 @@ impl App {
     }
 
     pub(crate) fn handle_input(&mut self, key: KeyEvent) {
+        self.dirty = true;
+        if self.handle_branch_picker_key(&key) {
+            return;
+        }
         match key.code {
             KeyCode::Esc => {
+                self.branch_picker = None;
                 if let Some(input) = self.input.take() {
                     self.focus = input.origin;
                 }
@@ -261,17 +465,51 @@ impl App {
                     input.buffer.pop();
                 }
             }
-            KeyCode::Char(ch) => {
-                if !key.modifiers.contains(KeyModifiers::CONTROL) {
-                    if let Some(input) = self.input.as_mut() {
-                        input.buffer.push(ch);
-                    }
+            KeyCode::Char(ch) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                if let Some(input) = self.input.as_mut() {
+                    input.buffer.push(ch);
                 }
             }
             _ => {}
         }
     }
 
+    /// Up/down move through the branch list, tab copies the highlighted (or first) branch into
+    /// the prompt, and enter uses the highlighted branch. Typing clears the highlight.
+    fn handle_branch_picker_key(&mut self, key: &KeyEvent) -> bool {
+        let Some((matches, selected)) = self.branch_matches() else {
+            return false;
+        };
+        let count = matches.len();
+        let chosen = selected
+            .or(matches!(key.code, KeyCode::Tab).then_some(0))
+            .and_then(|index| matches.get(index))
+            .map(|branch| branch.to_string());
+        let Some(picker) = self.branch_picker.as_mut() else {
+            return false;
+        };
+        match key.code {
+            KeyCode::Up => picker.move_selection(-1, count),
+            KeyCode::Down => picker.move_selection(1, count),
+            KeyCode::Tab | KeyCode::Enter => {
+                let Some(branch) = chosen else {
+                    return false;
+                };
+                picker.selected = None;
+                if let Some(input) = self.input.as_mut() {
+                    input.buffer = branch;
+                }
+                return key.code == KeyCode::Tab;
+            }
+            KeyCode::Char(_) | KeyCode::Backspace => {
+                picker.selected = None;
+                return false;
+            }
+            _ => return false,
+        }
+        true
+    }
+
     fn toggle_selected_repo(&mut self) {
         let repo_name = match self.selected_tree_item() {
             Some(TreeItem {
@@ -333,12 +571,17 @@ impl App {
             return;
         };
 
-        let default_source = default_source_branch(&repo);
+        let Some(default_source) = self.default_source_or_error(&repo) else {
+            return;
+        };
         let existing_names = self.worktree_names_for_repo(&repo);
-        let default_name = suggest_worktree_name(
+        let template = load_worktree_name_template();
+        let default_name = suggest_worktree_name_with_template(
+            &repo.name,
             &default_source,
             &default_source,
             self.default_worktree_name_mode,
+            template.as_deref(),
             &existing_names,
         );
         self.input = Some(InputState {
@@ -349,6 +592,87 @@ impl App {
         self.focus = Focus::Input;
     }
 
+    fn default_source_or_error(&mut self, repo: &Repo) -> Option<String> {
+        match default_source_branch(repo) {
+            Ok(branch) => Some(branch),
+            Err(err) => {
+                let message = format!(
+                    "Failed to read the default branch of {}: {}",
+                    self.display_repo_name(&repo.name),
+                    err
+                );
+                self.set_error(message);
+                None
+            }
+        }
+    }
+
+    fn open_clipboard_worktree_prompt(&mut self) {
+        let Some(repo) = self.selected_repo().cloned() else {
+            self.set_error("Select a repo first");
+            return;
+        };
+        let text = match read_clipboard() {
+            Ok(text) => text,
+            Err(err) => {
+                self.set_error(format!("Failed to read clipboard: {err}"));
+                return;
+            }
+        };
+
+        let branch = match classify_clipboard(&text) {
+            ClipboardContent::Branch(branch) => branch,
+            ClipboardContent::PullRequest { repo, number } => {
+                self.set_error(format!(
+                    "{repo}#{number} is a pull request; copy its branch name instead"
+                ));
+                return;
+            }
+            ClipboardContent::Invalid => {
+                self.set_error("Clipboard does not contain a branch name");
+                return;
+            }
+        };
+
+        let template = load_worktree_name_template();
+        let base = worktree_name_from_branch(&repo.name, &branch, template.as_deref());
+        let existing_names = self.worktree_names_for_repo(&repo);
+        let mut name = base.clone();
+        let mut suffix = 2;
+        while existing_names.contains(&name) {
+            name = format!("{base}-{suffix}");
+            suffix += 1;
+        }
+
+        self.open_source_prompt(repo, name, branch, self.focus);
+        self.focus = Focus::Input;
+    }
+
+    /// Prompts for the source branch and asks the worker for the repo's branches to pick from.
+    fn open_source_prompt(&mut self, repo: Repo, name: String, buffer: String, origin: Focus) {
+        self.branch_picker = Some(BranchPicker::new(&repo.name));
+        let _ = self.worker_tx.send(WorkerRequest::ListBranches { repo: repo.clone() });
+        self.input = Some(InputState {
+            kind: InputKind::CreateWorktreeSource { repo, name },
+            buffer,
+            origin,
+        });
+    }
+
+    /// Branches to offer for the current source prompt, if any.
+    pub(crate) fn branch_matches(&self) -> Option<(Vec<&str>, Option<usize>)> {
+        let picker = self.branch_picker.as_ref()?;
+        let input = self.input.as_ref()?;
+        if !matches!(input.kind, InputKind::CreateWorktreeSource { .. }) {
+            return None;
+        }
+        let matches = picker.matches(&input.buffer);
+        if matches.is_empty() {
+            return None;
+        }
+        Some((matches, picker.selected))
+    }
+
     fn open_delete_prompt(&mut self) {
         let Some(item) = self.selected_tree_item() else {
             self.set_error("Select a repo or worktree to delete");
@@ -377,62 +701,453 @@ impl App {
             self.set_error("Select a worktree first");
             return;
         };
+        let origin = self.focus;
+        if let Some(focus) =
+            self.advance_worktree_delete(repo, worktree.display_name(), false, origin)
+        {
+            self.focus = focus;
+        }
+    }
+
+    /// Moves a worktree delete along: a `yes` prompt unless already `confirmed` (or
+    /// `confirm_deletes` is off), then a `discard` prompt if work would be lost, then the delete.
+    /// Returns the focus to use while a prompt is open.
+    fn advance_worktree_delete(
+        &mut self,
+        repo: Repo,
+        name: String,
+        confirmed: bool,
+        origin: Focus,
+    ) -> Option<Focus> {
+        let conflict_state = self.worktree_conflict_state(&repo, &name);
+        let change_count = self.worktree_change_count(&repo, &name).unwrap_or(0);
+        let dirty = conflict_state.is_some() || change_count > 0;
+        let label = self.format_worktree_label(&repo.name, &name);
+        match worktree_delete_step(self.confirm_deletes, confirmed, dirty) {
+            DeleteStep::ConfirmYes => {
+                self.input = Some(InputState {
+                    kind: InputKind::DeleteWorktree { repo, name },
+                    buffer: String::new(),
+                    origin,
+                });
+                Some(Focus::Input)
+            }
+            DeleteStep::ConfirmDiscard => {
+                if let Some(state) = conflict_state {
+                    self.set_error(format!(
+                        "{} has a {}. Type 'discard' to delete it anyway.",
+                        label,
+                        state.label()
+                    ));
+                } else {
+                    let file_label = if change_count == 1 {
+                        "1 changed file".to_string()
+                    } else {
+                        format!("{change_count} changed files")
+                    };
+                    self.set_error(format!(
+                        "{} has {}. Type 'discard' to delete and lose those changes.",
+                        label, file_label
+                    ));
+                }
+                self.input = Some(InputState {
+                    kind: InputKind::DeleteWorktreeForce { repo, name },
+                    buffer: String::new(),
+                    origin,
+                });
+                Some(Focus::Input)
+            }
+            DeleteStep::Delete => {
+                self.set_loading(
+                    LoadingGroup::Action,
+                    format!("Deleting worktree {}", label),
+                    LoadingPriority::Action,
+                );
+                let key = (repo.name.clone(), name.clone());
+                let favorite = self.favorites.contains(&TreeKey::Worktree {
+                    repo: key.0.clone(),
+                    name: key.1.clone(),
+                });
+                let labels = self.labels.get(&key).cloned().unwrap_or_default();
+                self.pending_undo = self
+                    .worktree_branch(&repo, &name)
+                    .map(|branch| DeletedWorktree {
+                        repo: repo.clone(),
+                        name: name.clone(),
+                        branch,
+                        favorite,
+                        labels,
+                    });
+                let _ = self.worker_tx.send(WorkerRequest::DeleteWorktree {
+                    repo,
+                    name,
+                    force: false,
+                });
+                None
+            }
+        }
+    }
+
+    fn open_rename_branch_prompt(&mut self) {
+        let Some(repo) = self.selected_repo().cloned() else {
+            self.set_error("Select a repo first");
+            return;
+        };
+        let Some(worktree) = self.selected_worktree().cloned() else {
+            self.set_error("Select a worktree first");
+            return;
+        };
+        let Some(branch) = worktree.branch.clone() else {
+            self.set_error(format!(
+                "{} has a detached HEAD; check out a branch first",
+                worktree.display_name()
+            ));
+            return;
+        };
         self.input = Some(InputState {
-            kind: InputKind::DeleteWorktree {
-                repo,
-                name: worktree.display_name(),
-            },
-            buffer: String::new(),
+            kind: InputKind::RenameBranch { repo, worktree },
+            buffer: branch,
             origin: self.focus,
         });
         self.focus = Focus::Input;
     }
 
-    fn open_selected_in_editor(&mut self) {
-        let Some(worktree) = self.selected_worktree() else {
+    fn fetch_all_repos(&mut self) {
+        if self.repos.is_empty() {
+            self.set_error("No repos to fetch");
+            return;
+        }
+        if self.loading_message(LoadingGroup::Fetch).is_some() {
+            self.set_error("A fetch is already running");
+            return;
+        }
+
+        let count = self.repos.len();
+        self.set_loading(
+            LoadingGroup::Fetch,
+            format!("Fetching {} repo{}", count, if count == 1 { "" } else { "s" }),
+            LoadingPriority::Action,
+        );
+        let _ = self.worker_tx.send(WorkerRequest::FetchAll);
+    }
+
+    fn open_settings(&mut self) {
+        self.clear_status();
+        self.settings = Some(SettingsState::new(settings_rows(
+            self.editor_command.as_deref(),
+            self.terminal_command.as_deref(),
+            self.theme_name(),
+            self.default_worktree_name_mode,
+            github_prefix_enabled(),
+            check_updates_enabled(),
+        )));
+    }
+
+    fn apply_setting(&mut self, field: SettingField, value: Option<String>) {
+        let value = value.unwrap_or_default();
+        let result = match field {
+            SettingField::Editor => save_editor_command(&value).map(|_| {
+                self.editor_command = Some(value.clone()).filter(|value| !value.is_empty());
+            }),
+            SettingField::Terminal => save_terminal_command(&value).map(|_| {
+                self.terminal_command = Some(value.clone()).filter(|value| !value.is_empty());
+            }),
+            SettingField::Theme => save_theme_name(&value).map(|_| {
+                if let Some(index) = theme_index_by_name(&value) {
+                    self.theme_index = index;
+                }
+            }),
+            SettingField::DefaultWorktreeName => {
+                let mode = DefaultWorktreeNameMode::from_config(&value);
+                save_default_worktree_name_mode(mode).map(|_| {
+                    self.default_worktree_name_mode = mode;
+                })
+            }
+            SettingField::GithubPrefix => save_github_prefix(value == "true"),
+            SettingField::CheckUpdates => save_check_updates(value == "true"),
+        };
+        if let Err(err) = result {
+            self.set_error(format!("Failed to save {}: {err}", field.label()));
+        }
+    }
+
+    fn toggle_selected_favorite(&mut self) {
+        let Some(key) = self.selected_tree_key() else {
             self.set_error("Select a worktree first");
             return;
         };
+        let TreeKey::Worktree { ref name, .. } = key else {
+            self.set_error("Only worktrees can be favorited");
+            return;
+        };
 
-        let label = self.worktree_label_for_repo(self.selected_repo(), worktree);
-        let (result, target_label) = if let Some(command) = self.editor_command.as_deref() {
-            (open_in_editor(command, &worktree.path), "editor".to_string())
+        let name = name.clone();
+        toggle_mark(&mut self.favorites, key.clone());
+        if self.favorites.contains(&key) {
+            self.set_status(format!("Favorited {name}"));
         } else {
-            let available = detect_open_targets();
-            let selected = available.first().copied();
-            let Some(selected) = selected else {
-                let err = io::Error::new(
-                    io::ErrorKind::NotFound,
-                    "no editor configured; set editor in config.toml",
-                );
-                self.set_error(format!("Failed to open editor: {}", err));
-                return;
-            };
-            (
-                open_in_target(selected, &worktree.path),
-                selected.label().to_string(),
-            )
+            self.set_status(format!("Unfavorited {name}"));
+        }
+        self.rebuild_tree_items(Some(key));
+        self.persist_restore_state();
+    }
+
+    fn open_labels_prompt(&mut self) {
+        let Some(TreeKey::Worktree { repo, name }) = self.selected_tree_key() else {
+            self.set_error("Select a worktree first");
+            return;
         };
+        let buffer = self
+            .labels
+            .get(&(repo.clone(), name.clone()))
+            .map(|labels| labels.join(" "))
+            .unwrap_or_default();
+        self.input = Some(InputState {
+            kind: InputKind::EditLabels { repo, name },
+            buffer,
+            origin: self.focus,
+        });
+        self.focus = Focus::Input;
+    }
 
+    fn toggle_env_box(&mut self) {
+        self.show_env = !self.show_env;
+        self.persist_restore_state();
+    }
+
+    fn toggle_detail_pane(&mut self) {
+        self.show_detail = !self.show_detail;
+    }
+
+    fn toggle_selected_mark(&mut self) {
+        let Some(key) = self.selected_tree_key() else {
+            self.set_error("Select a worktree first");
+            return;
+        };
+        if !matches!(key, TreeKey::Worktree { .. }) {
+            self.set_error("Only worktrees can be marked");
+            return;
+        }
+
+        toggle_mark(&mut self.marked, key);
+        match self.marked.len() {
+            0 => self.set_status("Cleared marks"),
+            1 => self.set_status("1 worktree marked"),
+            count => self.set_status(format!("{} worktrees marked", count)),
+        }
+    }
+
+    fn open_selected_in_editor(&mut self) {
+        if !self.marked.is_empty() {
+            self.open_marked_worktrees(false);
+            return;
+        }
+        let Some(worktree) = self.selected_worktree() else {
+            self.set_error("Select a worktree first");
+            return;
+        };
+
+        let label = self.worktree_label_for_repo(self.selected_repo(), worktree);
+        let name = worktree.display_name();
+        let (result, target_label) = self.open_path_in_editor(&worktree.path);
         match result {
-            Ok(()) => self.set_status(format!("Opened {} in {}", label, target_label)),
+            Ok(()) => {
+                self.set_status(format!("Opened {} in {}", label, target_label));
+                self.remember_opened(name);
+            }
             Err(err) => self.set_error(format!("Failed to open {}: {}", target_label, err)),
         }
     }
 
+    /// Records the selected repo's worktree `name` as last opened, for `o` and `bbq open`.
+    fn remember_opened(&mut self, name: String) {
+        let Some(repo) = self.selected_repo_name().map(str::to_string) else {
+            return;
+        };
+        let _ = record_last_opened(&repo, &name);
+        self.last_opened = Some((repo, name));
+    }
+
+    /// Selects the most recently opened worktree, expanding its repo, and opens it again. An
+    /// open recorded by `bbq worktree open` since the TUI started wins over the TUI's own.
+    fn open_recent(&mut self) {
+        if let Some(recorded) = load_restore_state().last_opened {
+            self.last_opened = Some(recorded);
+        }
+        let Some((repo, name)) = self.last_opened.clone() else {
+            self.set_error("No recently opened worktree");
+            return;
+        };
+        self.marked.clear();
+        let key = TreeKey::Worktree {
+            repo: repo.clone(),
+            name: name.clone(),
+        };
+        self.expanded_repos.insert(repo.clone());
+        self.rebuild_tree_items(Some(key.clone()));
+        if !self.select_tree_key(&key) {
+            let label = self.format_worktree_label(&repo, &name);
+            self.set_error(format!("{label} no longer exists"));
+            return;
+        }
+        self.open_selected_in_editor();
+    }
+
+    fn copy_git_worktree_command(&mut self) {
+        let Some(entry) = self.selected_worktree_entry() else {
+            self.set_error("Select a worktree first");
+            return;
+        };
+        let text = git_worktree_add_command(&entry.worktree, entry.upstream.is_some());
+        let _ = self.worker_tx.send(WorkerRequest::CopyToClipboard { text });
+    }
+
+    fn open_path_in_editor(&self, path: &Path) -> (io::Result<()>, String) {
+        let env = load_repo_env(path);
+        let new_window = editor_new_window_enabled();
+        let terminal = self.terminal_command.as_deref();
+        if let Some(command) = self.editor_command.as_deref() {
+            let result = open_in_editor(command, path, terminal, &env, new_window);
+            return (result, "editor".to_string());
+        }
+
+        let available = detect_open_targets_in_order(&load_open_order());
+        match resolve_open_command(&available, |name| std::env::var(name).ok()) {
+            Some(OpenCommand::Target(selected)) => (
+                open_in_target(selected, path, &env, new_window),
+                selected.label().to_string(),
+            ),
+            Some(OpenCommand::TerminalEditor(editor)) => {
+                (open_in_terminal_editor(&editor, path, None, terminal, &env), editor)
+            }
+            None => (
+                Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "no editor configured; set editor in config.toml or $EDITOR",
+                )),
+                "editor".to_string(),
+            ),
+        }
+    }
+
     fn open_selected_in_terminal(&mut self) {
+        if !self.marked.is_empty() {
+            self.open_marked_worktrees(true);
+            return;
+        }
         let Some(worktree) = self.selected_worktree() else {
             self.set_error("Select a worktree first");
             return;
         };
 
         let label = self.worktree_label_for_repo(self.selected_repo(), worktree);
-        match open_terminal_at_path_with_config(&worktree.path, self.terminal_command.as_deref()) {
-            Ok(()) => self.set_status(format!("Opened {} in terminal", label)),
+        let name = worktree.display_name();
+        match self.open_path_in_terminal(&worktree.path) {
+            Ok(()) => {
+                self.set_status(format!("Opened {} in terminal", label));
+                self.remember_opened(name);
+            }
             Err(err) => self.set_error(format!("Failed to open terminal: {}", err)),
         }
     }
 
+    fn reveal_selected_in_file_manager(&mut self) {
+        let Some(worktree) = self.selected_worktree() else {
+            self.set_error("Select a worktree first");
+            return;
+        };
+
+        let label = self.worktree_label_for_repo(self.selected_repo(), worktree);
+        match reveal_in_file_manager(&worktree.path) {
+            Ok(()) => self.set_status(format!("Revealed {} in file manager", label)),
+            Err(err) => self.set_error(format!("Failed to open file manager: {}", err)),
+        }
+    }
+
+    fn open_path_in_terminal(&self, path: &Path) -> io::Result<()> {
+        let env = load_repo_env(path);
+        open_terminal_at_path_with_config(path, self.terminal_command.as_deref(), &env)
+    }
+
+    fn open_marked_worktrees(&mut self, in_terminal: bool) {
+        let paths = marked_worktree_paths(&self.repos, &self.repo_worktrees, &self.marked);
+        self.marked.clear();
+        if paths.is_empty() {
+            self.set_error("Marked worktrees no longer exist");
+            return;
+        }
+
+        let mut target_label = "terminal".to_string();
+        let mut opened = 0;
+        let mut first_error = None;
+        for path in &paths {
+            let result = if in_terminal {
+                self.open_path_in_terminal(path)
+            } else {
+                let (result, label) = self.open_path_in_editor(path);
+                target_label = label;
+                result
+            };
+            match result {
+                Ok(()) => opened += 1,
+                Err(err) => {
+                    first_error.get_or_insert(err);
+                }
+            }
+        }
+
+        match first_error {
+            None => self.set_status(format!("Opened {} worktrees in {}", opened, target_label)),
+            Some(err) => self.set_error(format!(
+                "Failed to open {} of {} worktrees in {}: {}",
+                paths.len() - opened,
+                paths.len(),
+                target_label,
+                err
+            )),
+        }
+    }
+
+    fn undo_last_delete(&mut self) {
+        let Some(deleted) = self.undo_stack.pop() else {
+            self.set_error("Nothing to undo");
+            return;
+        };
+
+        let label = self.format_worktree_label(&deleted.repo.name, &deleted.name);
+        self.set_loading(
+            LoadingGroup::Action,
+            format!("Restoring worktree {}", label),
+            LoadingPriority::Action,
+        );
+        self.pending_restore = Some(deleted.clone());
+        let _ = self.worker_tx.send(WorkerRequest::RestoreWorktree {
+            repo: deleted.repo,
+            name: deleted.name,
+            branch: deleted.branch,
+        });
+    }
+
+    fn push_undo(&mut self, deleted: DeletedWorktree) {
+        self.undo_stack.push(deleted);
+        if self.undo_stack.len() > UNDO_STACK_MAX {
+            self.undo_stack.remove(0);
+        }
+    }
+
+    pub(crate) fn can_undo_delete(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    fn worktree_branch(&self, repo: &Repo, name: &str) -> Option<String> {
+        self.repo_worktrees.get(&repo.name).and_then(|entries| {
+            entries
+                .iter()
+                .find(|entry| entry.worktree.display_name() == name)
+                .and_then(|entry| entry.worktree.branch.clone())
+        })
+    }
+
     fn worktree_names_for_repo(&self, repo: &Repo) -> HashSet<String> {
         self.repo_worktrees
             .get(&repo.name)
@@ -450,7 +1165,16 @@ impl App {
             entries
                 .iter()
                 .find(|entry| entry.worktree.display_name() == name)
-                .map(|entry| entry.changed_files.len())
+                .map(|entry| entry.changed_total)
+        })
+    }
+
+    fn worktree_conflict_state(&self, repo: &Repo, name: &str) -> Option<ConflictState> {
+        self.repo_worktrees.get(&repo.name).and_then(|entries| {
+            entries
+                .iter()
+                .find(|entry| entry.worktree.display_name() == name)
+                .and_then(|entry| entry.conflict_state)
         })
     }
 
@@ -481,15 +1205,8 @@ impl App {
                     return Some(Focus::Input);
                 }
 
-                let default_source = default_source_branch(&repo);
-                self.input = Some(InputState {
-                    kind: InputKind::CreateWorktreeSource {
-                        repo,
-                        name: name.to_string(),
-                    },
-                    buffer: default_source,
-                    origin: input.origin,
-                });
+                let default_source = self.default_source_or_error(&repo)?;
+                self.open_source_prompt(repo, name.to_string(), default_source, input.origin);
                 return Some(Focus::Input);
             }
             InputKind::CreateWorktreeSource { repo, name } => {
@@ -503,11 +1220,23 @@ impl App {
                     });
                     return Some(Focus::Input);
                 }
+                self.branch_picker = None;
 
-                let default_branch = default_branch_name(&name);
-                let default_source = default_source_branch(&repo);
+                let default_source = self.default_source_or_error(&repo)?;
+                if source_branch == default_source && branch_prefix_interactive_enabled() {
+                    self.input = Some(InputState {
+                        kind: InputKind::CreateWorktreeTicket {
+                            repo,
+                            name,
+                            source_branch: source_branch.to_string(),
+                        },
+                        buffer: String::new(),
+                        origin: input.origin,
+                    });
+                    return Some(Focus::Input);
+                }
                 let default_branch = if source_branch == default_source {
-                    default_branch
+                    default_branch_name(&name)
                 } else {
                     source_branch.to_string()
                 };
@@ -522,6 +1251,44 @@ impl App {
                 });
                 return Some(Focus::Input);
             }
+            InputKind::CreateWorktreeTicket {
+                repo,
+                name,
+                source_branch,
+            } => {
+                let key = input.buffer.trim();
+                let branch = if key.is_empty() {
+                    Ok(default_branch_name(&name))
+                } else {
+                    default_ticket_branch_name(&name, key)
+                };
+                let branch = match branch {
+                    Ok(branch) => branch,
+                    Err(message) => {
+                        self.set_error(message);
+                        self.input = Some(InputState {
+                            kind: InputKind::CreateWorktreeTicket {
+                                repo,
+                                name,
+                                source_branch,
+                            },
+                            buffer: input.buffer,
+                            origin: input.origin,
+                        });
+                        return Some(Focus::Input);
+                    }
+                };
+                self.input = Some(InputState {
+                    kind: InputKind::CreateWorktreeBranch {
+                        repo,
+                        name,
+                        source_branch,
+                    },
+                    buffer: branch,
+                    origin: input.origin,
+                });
+                return Some(Focus::Input);
+            }
             InputKind::CreateWorktreeBranch {
                 repo,
                 name,
@@ -574,25 +1341,12 @@ impl App {
                     self.set_status("Delete canceled");
                     return None;
                 }
-
-                let change_count = self.worktree_change_count(&repo, &name).unwrap_or(0);
-                if change_count > 0 {
-                    let label = self.format_worktree_label(&repo.name, &name);
-                    let file_label = if change_count == 1 {
-                        "1 changed file".to_string()
-                    } else {
-                        format!("{change_count} changed files")
-                    };
-                    self.set_error(format!(
-                        "{} has {}. Type 'discard' to delete and lose those changes.",
-                        label, file_label
-                    ));
-                    self.input = Some(InputState {
-                        kind: InputKind::DeleteWorktreeForce { repo, name },
-                        buffer: String::new(),
-                        origin: input.origin,
-                    });
-                    return Some(Focus::Input);
+                return self.advance_worktree_delete(repo, name, true, input.origin);
+            }
+            InputKind::DeleteWorktreeForce { repo, name } => {
+                if !discard_confirmed(&input.buffer) {
+                    self.set_status("Delete canceled");
+                    return None;
                 }
 
                 let label = self.format_worktree_label(&repo.name, &name);
@@ -601,30 +1355,62 @@ impl App {
                     format!("Deleting worktree {}", label),
                     LoadingPriority::Action,
                 );
+                self.pending_undo = None;
                 let _ = self.worker_tx.send(WorkerRequest::DeleteWorktree {
                     repo,
                     name,
-                    force: false,
+                    force: true,
                 });
             }
-            InputKind::DeleteWorktreeForce { repo, name } => {
-                if !discard_confirmed(&input.buffer) {
-                    self.set_status("Delete canceled");
+            InputKind::RenameBranch { repo, worktree } => {
+                let branch = input.buffer.trim();
+                if let Err(message) = bbq::validate_branch_name(branch) {
+                    self.set_error(message);
+                    self.input = Some(InputState {
+                        kind: InputKind::RenameBranch { repo, worktree },
+                        buffer: input.buffer,
+                        origin: input.origin,
+                    });
+                    return Some(Focus::Input);
+                }
+                if worktree.branch.as_deref() == Some(branch) {
+                    self.set_status("Branch unchanged");
                     return None;
                 }
 
-                let label = self.format_worktree_label(&repo.name, &name);
                 self.set_loading(
                     LoadingGroup::Action,
-                    format!("Deleting worktree {}", label),
+                    format!("Renaming branch to {}", branch),
                     LoadingPriority::Action,
                 );
-                let _ = self.worker_tx.send(WorkerRequest::DeleteWorktree {
+                let _ = self.worker_tx.send(WorkerRequest::RenameBranch {
                     repo,
-                    name,
-                    force: true,
+                    worktree,
+                    branch: branch.to_string(),
                 });
             }
+            InputKind::EditLabels { repo, name } => {
+                let labels = match parse_labels(&input.buffer) {
+                    Ok(labels) => labels,
+                    Err(message) => {
+                        self.set_error(message);
+                        self.input = Some(InputState {
+                            kind: InputKind::EditLabels { repo, name },
+                            buffer: input.buffer,
+                            origin: input.origin,
+                        });
+                        return Some(Focus::Input);
+                    }
+                };
+                if labels.is_empty() {
+                    self.labels.remove(&(repo, name.clone()));
+                    self.set_status(format!("Cleared labels on {name}"));
+                } else {
+                    self.set_status(format!("Labeled {name}: {}", labels.join(", ")));
+                    self.labels.insert((repo, name), labels);
+                }
+                self.persist_restore_state();
+            }
         }
         None
     }
@@ -644,10 +1430,7 @@ impl App {
     }
 
     fn request_update_check(&mut self) {
-        if !check_updates_enabled() {
-            return;
-        }
-        if !force_upgrade_prompt_enabled() && !update::is_homebrew_install() {
+        if !update_check_allowed(bbq::offline_enabled()) {
             return;
         }
         let _ = self.worker_tx.send(WorkerRequest::CheckForUpdate);
@@ -674,6 +1457,7 @@ impl App {
 
     pub(crate) fn handle_worker_events(&mut self) {
         while let Ok(event) = self.worker_rx.try_recv() {
+            self.dirty = true;
             match event {
                 WorkerEvent::AllDataLoaded { request_id, result } => {
                     if self.pending_all_request != Some(request_id) {
@@ -774,6 +1558,7 @@ impl App {
                         self.clear_loading(LoadingGroup::Action);
                         let label = self.display_repo_name(&repo.name).to_string();
                         self.set_status(format!("Checked out {}", label));
+                        self.record_activity(format!("Cloned {}", label));
                         self.desired_repo_selection = Some(repo.name);
                         self.request_all_data(false);
                     }
@@ -783,6 +1568,7 @@ impl App {
                     }
                 },
                 WorkerEvent::WorktreeScriptStarted { kind, path } => {
+                    self.script_started = Some((kind.clone(), Instant::now()));
                     self.set_loading(
                         LoadingGroup::Action,
                         format!("Running {kind} script {path}"),
@@ -799,10 +1585,13 @@ impl App {
                         self.clear_loading(LoadingGroup::Action);
                         let label = self.format_worktree_label(&repo_name, &worktree_name);
                         self.set_status(format!("Created worktree {}", label));
+                        self.record_script_activity();
+                        self.record_activity(format!("Created worktree {}", label));
                         self.desired_worktree_selection = Some((repo_name, selection_key));
                         self.request_all_data(false);
                     }
                     Err(err) => {
+                        self.script_started = None;
                         self.clear_loading(LoadingGroup::Action);
                         self.set_error(err);
                     }
@@ -812,6 +1601,9 @@ impl App {
                         self.clear_loading(LoadingGroup::Action);
                         let label = self.display_repo_name(&name).to_string();
                         self.set_status(format!("Deleted repo {}", label));
+                        self.record_activity(format!("Deleted repo {}", label));
+                        forget_repo(&mut self.favorites, &mut self.labels, &name);
+                        self.persist_restore_state();
                         self.request_all_data(false);
                     }
                     Err(err) => {
@@ -823,11 +1615,79 @@ impl App {
                     repo_name,
                     worktree_name,
                     result,
+                } => match result {
+                    Ok(()) => {
+                        self.clear_loading(LoadingGroup::Action);
+                        if let Some(deleted) = self.pending_undo.take() {
+                            if deleted.repo.name == repo_name && deleted.name == worktree_name {
+                                self.push_undo(deleted);
+                            }
+                        }
+                        let label = self.format_worktree_label(&repo_name, &worktree_name);
+                        forget_worktree(
+                            &mut self.favorites,
+                            &mut self.labels,
+                            &repo_name,
+                            &worktree_name,
+                        );
+                        self.persist_restore_state();
+                        self.record_script_activity();
+                        self.record_activity(format!("Deleted worktree {}", label));
+                        if self.can_undo_delete() {
+                            self.set_status(format!("Deleted worktree {} (u to undo)", label));
+                        } else {
+                            self.set_status(format!("Deleted worktree {}", label));
+                        }
+                        self.request_all_data(false);
+                    }
+                    Err(err) => {
+                        self.script_started = None;
+                        self.pending_undo = None;
+                        self.clear_loading(LoadingGroup::Action);
+                        self.set_error(err);
+                    }
+                },
+                WorkerEvent::RestoreWorktreeResult { repo_name, result } => match result {
+                    Ok(worktree) => {
+                        let worktree_name = worktree.display_name();
+                        self.clear_loading(LoadingGroup::Action);
+                        if let Some(deleted) = self.pending_restore.take() {
+                            if deleted.repo.name == repo_name {
+                                restore_worktree_marks(
+                                    &mut self.favorites,
+                                    &mut self.labels,
+                                    deleted,
+                                    &worktree_name,
+                                );
+                                self.persist_restore_state();
+                            }
+                        }
+                        let label = self.format_worktree_label(&repo_name, &worktree_name);
+                        self.set_status(format!("Restored {}", label));
+                        self.record_script_activity();
+                        self.record_activity(format!("Restored {}", label));
+                        self.desired_worktree_selection = Some((repo_name, worktree_name));
+                        self.request_all_data(false);
+                    }
+                    Err(err) => {
+                        self.script_started = None;
+                        self.pending_restore = None;
+                        self.clear_loading(LoadingGroup::Action);
+                        self.set_error(err);
+                    }
+                },
+                WorkerEvent::RenameBranchResult {
+                    repo_name,
+                    worktree_name,
+                    branch,
+                    result,
                 } => match result {
                     Ok(()) => {
                         self.clear_loading(LoadingGroup::Action);
                         let label = self.format_worktree_label(&repo_name, &worktree_name);
-                        self.set_status(format!("Deleted worktree {}", label));
+                        self.set_status(format!("Renamed {} branch to {}", label, branch));
+                        self.record_activity(format!("Renamed {} branch to {}", label, branch));
+                        self.desired_worktree_selection = Some((repo_name, worktree_name));
                         self.request_all_data(false);
                     }
                     Err(err) => {
@@ -835,6 +1695,34 @@ impl App {
                         self.set_error(err);
                     }
                 },
+                WorkerEvent::BranchesLoaded { repo_name, result } => {
+                    if let Some(picker) = self.branch_picker.as_mut() {
+                        if picker.repo_name == repo_name {
+                            picker.branches = result.unwrap_or_default();
+                            picker.selected = None;
+                        }
+                    }
+                }
+                WorkerEvent::ClipboardWritten { text, result } => match result {
+                    Ok(()) => self.set_status(format!("Copied: {text}")),
+                    Err(err) => self.set_error(format!("Failed to copy to clipboard: {err}")),
+                },
+                WorkerEvent::FetchAllResult { fetched, result } => {
+                    self.clear_loading(LoadingGroup::Fetch);
+                    match result {
+                        Ok(()) => {
+                            let text = format!(
+                                "Fetched {} repo{}",
+                                fetched,
+                                if fetched == 1 { "" } else { "s" }
+                            );
+                            self.set_status(text.clone());
+                            self.record_activity(text);
+                        }
+                        Err(err) => self.set_error(err),
+                    }
+                    self.request_all_data(true);
+                }
             }
         }
     }
@@ -858,12 +1746,27 @@ impl App {
         }
     }
 
+    fn jump_to_repo(&mut self, forward: bool) {
+        let current = self.tree_state.selected();
+        if let Some(idx) = adjacent_repo_index(&self.tree_items, current, forward) {
+            self.tree_state.select(Some(idx));
+        }
+    }
+
     fn rebuild_tree_items(&mut self, preferred: Option<TreeKey>) {
+        // The repo about to be selected (a fresh clone, say) stays even when it's empty.
+        let keep = preferred.clone().or_else(|| self.selected_tree_key());
+        let keep = keep.as_ref().map(|key| match key {
+            TreeKey::Repo(name) | TreeKey::Worktree { repo: name, .. } => name.as_str(),
+        });
+        let repos = visible_repos(&self.repos, &self.repo_worktrees, self.hide_empty_repos, keep);
         self.tree_items = build_tree_items(
-            &self.repos,
+            &repos,
             &self.repo_worktrees,
             &self.repo_display,
             &self.expanded_repos,
+            &self.favorites,
+            &self.tree_glyphs,
         );
         Self::clamp_selection(&mut self.tree_state, self.tree_items.len());
         if let Some(key) = preferred {
@@ -940,6 +1843,18 @@ impl App {
         self.setup.is_some()
     }
 
+    pub(crate) fn has_recent_worktree(&self) -> bool {
+        self.last_opened.is_some()
+    }
+
+    pub(crate) fn is_settings_mode(&self) -> bool {
+        self.settings.is_some()
+    }
+
+    pub(crate) fn settings_state(&self) -> Option<&SettingsState> {
+        self.settings.as_ref()
+    }
+
     pub(crate) fn is_update_prompt_mode(&self) -> bool {
         self.update_prompt.is_some()
     }
@@ -1037,6 +1952,16 @@ impl App {
     }
 
     fn clear_loading(&mut self, group: LoadingGroup) {
+        if self.notify_on_complete {
+            let finished = self.loading.iter().find(|item| {
+                item.group == group
+                    && item.priority == LoadingPriority::Action
+                    && should_notify(item.started_at.elapsed())
+            });
+            if let Some(item) = finished {
+                notify_completion(&item.text);
+            }
+        }
         self.loading.retain(|item| item.group != group);
     }
 
@@ -1065,23 +1990,68 @@ impl App {
         self.loading.iter().find(|item| item.group == group)
     }
 
+    fn record_activity(&mut self, text: impl Into<String>) {
+        self.activity.push(format!("✓ {}", text.into()));
+    }
+
+    fn record_script_activity(&mut self) {
+        if let Some((kind, started_at)) = self.script_started.take() {
+            let elapsed = started_at.elapsed().as_secs_f32();
+            self.record_activity(format!("{kind} ok ({elapsed:.1}s)"));
+        }
+    }
+
     pub(crate) fn update_status(&mut self) {
+        let activity_count = self.activity.len();
+        self.activity.prune(Instant::now());
+        if self.activity.len() != activity_count {
+            self.dirty = true;
+        }
         let Some(deadline) = self.status.as_ref().map(|status| status.deadline) else {
             return;
         };
         if Instant::now() >= deadline {
             self.status = None;
+            self.dirty = true;
         }
     }
 
+    pub(crate) fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// A spinner or status countdown is on screen, so the event loop should wake often.
+    pub(crate) fn is_animating(&self) -> bool {
+        !self.loading.is_empty() || self.status.is_some()
+    }
+
+    /// Whether the next loop iteration should draw: after a change, or every tick while a
+    /// spinner or a status countdown is animating.
+    pub(crate) fn needs_redraw(&mut self) -> bool {
+        std::mem::take(&mut self.dirty) || self.is_animating()
+    }
+
     pub(crate) fn persist_restore_state(&self) {
         let mut expanded: Vec<String> = self.expanded_repos.iter().cloned().collect();
         expanded.sort();
+        let mut favorites: Vec<(String, String)> = self
+            .favorites
+            .iter()
+            .filter_map(|key| match key {
+                TreeKey::Worktree { repo, name } => Some((repo.clone(), name.clone())),
+                TreeKey::Repo(_) => None,
+            })
+            .collect();
+        favorites.sort();
         let mut state = RestoreState {
             expanded_repos: expanded,
             selected_repo: None,
             selected_worktree_repo: None,
             selected_worktree_name: None,
+            favorite_worktrees: favorites,
+            show_env: (self.show_env != self.show_env_config).then_some(self.show_env),
+            last_opened: None,
+            worktree_labels: self.labels.clone(),
         };
 
         if let Some(key) = self.selected_tree_key() {
@@ -1109,6 +2079,25 @@ impl App {
         };
 
         match step {
+            SetupStep::RootDir => {
+                if choice.value.as_deref() == Some(CUSTOM_ROOT_DIR_OPTION) {
+                    if let Some(setup) = self.setup.as_mut() {
+                        setup.custom_input = Some("~/".to_string());
+                    }
+                    return;
+                }
+            }
+            SetupStep::Theme => {
+                if let Some(value) = choice.value {
+                    if let Err(err) = save_theme_name(&value) {
+                        self.set_error(format!("Failed to save theme: {err}"));
+                        return;
+                    }
+                    if let Some(index) = theme_index_by_name(&value) {
+                        self.theme_index = index;
+                    }
+                }
+            }
             SetupStep::DefaultWorktreeName => {
                 let value = choice.value.unwrap_or_default();
                 let mode = if value.trim().eq_ignore_ascii_case("cities") {
@@ -1150,6 +2139,27 @@ impl App {
         self.start_setup_step();
     }
 
+    fn apply_custom_root_dir(&mut self) {
+        let Some(path) = self
+            .setup
+            .as_ref()
+            .and_then(|setup| setup.custom_input.clone())
+        else {
+            return;
+        };
+        if let Err(err) = save_root_dir(&path) {
+            self.set_error(format!("Failed to use {}: {err}", path.trim()));
+            return;
+        }
+
+        self.clear_status();
+        self.request_all_data(false);
+        if !self.setup_steps.is_empty() {
+            self.setup_steps.remove(0);
+        }
+        self.start_setup_step();
+    }
+
     fn apply_update_selection(&mut self) {
         let Some(prompt) = self.update_prompt.as_mut() else {
             return;
@@ -1192,12 +2202,53 @@ fn move_state(state: &mut ListState, len: usize, delta: i32) {
     state.select(Some(next as usize));
 }
 
+/// Index of the next (or previous) repo row from `current`, wrapping around the tree.
+fn adjacent_repo_index(items: &[TreeItem], current: Option<usize>, forward: bool) -> Option<usize> {
+    let len = items.len();
+    if len == 0 {
+        return None;
+    }
+    let start = current.unwrap_or(0).min(len - 1);
+    (1..=len)
+        .map(|step| {
+            if forward {
+                (start + step) % len
+            } else {
+                (start + len - step) % len
+            }
+        })
+        .find(|&idx| matches!(items[idx].kind, TreeItemKind::Repo { .. }))
+}
+
 fn status_duration(message: &str) -> Duration {
     let chars = message.chars().count() as u64;
     let millis = STATUS_MIN_MS.saturating_add(STATUS_PER_CHAR_MS.saturating_mul(chars));
     Duration::from_millis(millis.min(STATUS_MAX_MS))
 }
 
+/// `Esc` or `Ctrl-X` while an action is running cancels it.
+fn is_abort_key(key: &KeyEvent) -> bool {
+    key.code == KeyCode::Esc
+        || (key.code == KeyCode::Char('x') && key.modifiers.contains(KeyModifiers::CONTROL))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DeleteStep {
+    ConfirmYes,
+    ConfirmDiscard,
+    Delete,
+}
+
+fn worktree_delete_step(confirm_deletes: bool, confirmed: bool, dirty: bool) -> DeleteStep {
+    if confirm_deletes && !confirmed {
+        DeleteStep::ConfirmYes
+    } else if dirty {
+        DeleteStep::ConfirmDiscard
+    } else {
+        DeleteStep::Delete
+    }
+}
+
 fn delete_confirmed(input: &str) -> bool {
     let trimmed = input.trim();
     if trimmed.is_empty() {
@@ -1218,6 +2269,50 @@ fn discard_confirmed(input: &str) -> bool {
     "discard".starts_with(normalized.as_str())
 }
 
+/// Update checks need the network, and only make sense for Homebrew installs unless the prompt
+/// is forced.
+fn update_check_allowed(offline: bool) -> bool {
+    update_check_wanted(
+        offline,
+        check_updates_enabled(),
+        force_upgrade_prompt_enabled() || update::is_homebrew_install(),
+    )
+}
+
+fn update_check_wanted(offline: bool, enabled: bool, applies: bool) -> bool {
+    !offline && enabled && applies
+}
+
+/// Whether an action that ran for `elapsed` took long enough to be worth a notification.
+fn should_notify(elapsed: Duration) -> bool {
+    elapsed >= Duration::from_millis(NOTIFY_AFTER_MS)
+}
+
+/// Rings the terminal bell and, on macOS, posts a notification for a finished action.
+fn notify_completion(text: &str) {
+    use std::io::Write;
+
+    let mut stdout = io::stdout();
+    let _ = stdout.write_all(b"\x07");
+    let _ = stdout.flush();
+
+    #[cfg(target_os = "macos")]
+    {
+        let script = format!(
+            "display notification \"{} finished\" with title \"bbq\"",
+            text.replace('\\', "\\\\").replace('"', "\\\"")
+        );
+        let _ = std::process::Command::new("osascript")
+            .args(["-e", &script])
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn();
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = text;
+}
+
 fn is_newer_version(latest: &str, current: &str) -> bool {
     match (Version::parse(latest), Version::parse(current)) {
         (Ok(latest), Ok(current)) => latest > current,
@@ -1227,6 +2322,8 @@ fn is_newer_version(latest: &str, current: &str) -> bool {
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum SetupStep {
+    RootDir,
+    Theme,
     DefaultWorktreeName,
     Editor,
     Terminal,
@@ -1243,6 +2340,173 @@ pub(crate) struct SetupState {
     pub(crate) step: SetupStep,
     pub(crate) options: Vec<SetupOption>,
     pub(crate) selected: usize,
+    pub(crate) custom_input: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SettingField {
+    Editor,
+    Terminal,
+    Theme,
+    DefaultWorktreeName,
+    GithubPrefix,
+    CheckUpdates,
+}
+
+impl SettingField {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            SettingField::Editor => "editor",
+            SettingField::Terminal => "terminal",
+            SettingField::Theme => "theme",
+            SettingField::DefaultWorktreeName => "default worktree names",
+            SettingField::GithubPrefix => "github user prefix",
+            SettingField::CheckUpdates => "check for updates",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct SettingRow {
+    pub(crate) field: SettingField,
+    pub(crate) options: Vec<SetupOption>,
+    pub(crate) current: usize,
+}
+
+impl SettingRow {
+    fn new(field: SettingField, mut options: Vec<SetupOption>, value: Option<&str>) -> Self {
+        let value = value.unwrap_or_default();
+        let current = match options
+            .iter()
+            .position(|option| option.value.as_deref().unwrap_or_default() == value)
+        {
+            Some(index) => index,
+            None => {
+                // Keep hand-edited values such as a custom editor command selectable.
+                let label = if value.is_empty() { "default" } else { value };
+                options.insert(
+                    0,
+                    SetupOption {
+                        label: label.to_string(),
+                        value: Some(value.to_string()),
+                    },
+                );
+                0
+            }
+        };
+        Self {
+            field,
+            options,
+            current,
+        }
+    }
+
+    pub(crate) fn current_label(&self) -> &str {
+        self.options
+            .get(self.current)
+            .map(|option| option.label.as_str())
+            .unwrap_or_default()
+    }
+}
+
+/// The settings screen: one row per setting, each cycling through its options.
+#[derive(Debug, Clone)]
+pub(crate) struct SettingsState {
+    pub(crate) rows: Vec<SettingRow>,
+    pub(crate) selected: usize,
+}
+
+impl SettingsState {
+    fn new(rows: Vec<SettingRow>) -> Self {
+        Self { rows, selected: 0 }
+    }
+
+    fn move_selection(&mut self, delta: i32) {
+        let len = self.rows.len() as i32;
+        if len == 0 {
+            return;
+        }
+        self.selected = (self.selected as i32 + delta).rem_euclid(len) as usize;
+    }
+
+    /// Moves the selected row to its next/previous option and returns the value to save.
+    fn cycle(&mut self, delta: i32) -> Option<(SettingField, Option<String>)> {
+        let row = self.rows.get_mut(self.selected)?;
+        let len = row.options.len() as i32;
+        if len < 2 {
+            return None;
+        }
+        row.current = (row.current as i32 + delta).rem_euclid(len) as usize;
+        Some((row.field, row.options[row.current].value.clone()))
+    }
+}
+
+fn settings_rows(
+    editor: Option<&str>,
+    terminal: Option<&str>,
+    theme: &str,
+    default_worktree_name: Option<DefaultWorktreeNameMode>,
+    github_prefix: bool,
+    check_updates: bool,
+) -> Vec<SettingRow> {
+    let mut editors = vec![SetupOption {
+        label: "auto-detect".to_string(),
+        value: Some(String::new()),
+    }];
+    editors.extend(
+        detect_open_targets()
+            .into_iter()
+            .map(|target| SetupOption {
+                label: target.label().to_string(),
+                value: Some(target.command().to_string()),
+            }),
+    );
+    let default_worktree_name = match default_worktree_name {
+        Some(DefaultWorktreeNameMode::Cities) => "cities",
+        None => "",
+    };
+
+    vec![
+        SettingRow::new(SettingField::Editor, editors, editor),
+        SettingRow::new(SettingField::Terminal, terminal_options(), terminal),
+        SettingRow::new(SettingField::Theme, theme_options(), Some(theme)),
+        SettingRow::new(
+            SettingField::DefaultWorktreeName,
+            default_worktree_name_options(),
+            Some(default_worktree_name),
+        ),
+        SettingRow::new(
+            SettingField::GithubPrefix,
+            on_off_options(),
+            Some(bool_value(github_prefix)),
+        ),
+        SettingRow::new(
+            SettingField::CheckUpdates,
+            on_off_options(),
+            Some(bool_value(check_updates)),
+        ),
+    ]
+}
+
+fn on_off_options() -> Vec<SetupOption> {
+    vec![
+        SetupOption {
+            label: "on".to_string(),
+            value: Some("true".to_string()),
+        },
+        SetupOption {
+            label: "off".to_string(),
+            value: Some("false".to_string()),
+        },
+    ]
+}
+
+fn bool_value(enabled: bool) -> &'static str {
+    if enabled {
+        "true"
+    } else {
+        "false"
+    }
 }
 
 const UPDATE_PROMPT_OPTIONS: [&str; 3] = [
@@ -1263,6 +2527,8 @@ pub(crate) struct UpdatePromptState {
 impl SetupState {
     fn from_step(step: SetupStep) -> Self {
         let mut options = match step {
+            SetupStep::RootDir => root_dir_options(),
+            SetupStep::Theme => theme_options(),
             SetupStep::DefaultWorktreeName => default_worktree_name_options(),
             SetupStep::Editor => editor_options(),
             SetupStep::Terminal => terminal_options(),
@@ -1273,16 +2539,22 @@ impl SetupState {
                 value: None,
             });
         }
-        let selected = 0;
+        let selected = match step {
+            SetupStep::Theme => default_theme_index(),
+            _ => 0,
+        };
         Self {
             step,
             options,
             selected,
+            custom_input: None,
         }
     }
 
     pub(crate) fn question(&self) -> &'static str {
         match self.step {
+            SetupStep::RootDir => "Where should bbq keep repos and worktrees?",
+            SetupStep::Theme => "Which accent color do you want?",
             SetupStep::DefaultWorktreeName => {
                 "Would you like to use default worktree names?"
             }
@@ -1338,6 +2610,48 @@ impl UpdatePromptState {
     }
 }
 
+const SETUP_STEP_ORDER: [SetupStep; 5] = [
+    SetupStep::RootDir,
+    SetupStep::Theme,
+    SetupStep::DefaultWorktreeName,
+    SetupStep::Editor,
+    SetupStep::Terminal,
+];
+
+fn pending_setup_steps(is_configured: impl Fn(SetupStep) -> bool) -> Vec<SetupStep> {
+    SETUP_STEP_ORDER
+        .into_iter()
+        .filter(|step| !is_configured(*step))
+        .collect()
+}
+
+const CUSTOM_ROOT_DIR_OPTION: &str = "custom";
+
+fn root_dir_options() -> Vec<SetupOption> {
+    vec![
+        SetupOption {
+            label: paths::bbq_root()
+                .map(|root| format!("{} (default)", tilde_path(&root)))
+                .unwrap_or_else(|_| "default".to_string()),
+            value: None,
+        },
+        SetupOption {
+            label: "somewhere else".to_string(),
+            value: Some(CUSTOM_ROOT_DIR_OPTION.to_string()),
+        },
+    ]
+}
+
+fn theme_options() -> Vec<SetupOption> {
+    THEMES
+        .iter()
+        .map(|theme| SetupOption {
+            label: theme.name.to_string(),
+            value: Some(theme.name.to_string()),
+        })
+        .collect()
+}
+
 fn editor_options() -> Vec<SetupOption> {
     let targets = detect_open_targets();
     let mut options: Vec<SetupOption> = targets
@@ -1442,11 +2756,27 @@ fn terminal_candidates() -> Vec<TerminalCandidate> {
     ]
 }
 
-fn default_source_branch(repo: &Repo) -> String {
-    bbq::default_branch(repo)
-        .ok()
-        .flatten()
-        .unwrap_or_else(|| DEFAULT_SOURCE_BRANCH.to_string())
+fn default_source_branch(repo: &Repo) -> bbq::Result<String> {
+    Ok(bbq::default_branch(repo)?.unwrap_or_else(|| DEFAULT_SOURCE_BRANCH.to_string()))
+}
+
+/// The repos the tree shows: all of them, or with `hide_empty` only those with worktrees plus
+/// `keep`. Repos still loading have no entry yet; only the ones known to be empty are hidden.
+fn visible_repos(
+    repos: &[Repo],
+    repo_worktrees: &HashMap<String, Vec<WorktreeEntry>>,
+    hide_empty: bool,
+    keep: Option<&str>,
+) -> Vec<Repo> {
+    repos
+        .iter()
+        .filter(|repo| {
+            !hide_empty
+                || keep == Some(repo.name.as_str())
+                || !repo_worktrees.get(&repo.name).is_some_and(Vec::is_empty)
+        })
+        .cloned()
+        .collect()
 }
 
 fn build_tree_items(
@@ -1454,6 +2784,8 @@ fn build_tree_items(
     repo_worktrees: &HashMap<String, Vec<WorktreeEntry>>,
     repo_display: &HashMap<String, String>,
     expanded_repos: &HashSet<String>,
+    favorites: &HashSet<TreeKey>,
+    glyphs: &TreeGlyphs,
 ) -> Vec<TreeItem> {
     let mut items = Vec::new();
     for repo in repos {
@@ -1478,15 +2810,25 @@ fn build_tree_items(
 
         if expanded {
             if let Some(entries) = repo_worktrees.get(&repo.name) {
+                let is_favorite = |entry: &WorktreeEntry| {
+                    favorites.contains(&TreeKey::Worktree {
+                        repo: repo.name.clone(),
+                        name: entry.worktree.display_name(),
+                    })
+                };
+                let mut entries: Vec<&WorktreeEntry> = entries.iter().collect();
+                // Stable sort keeps the existing order within favorites and non-favorites.
+                entries.sort_by_key(|entry| !is_favorite(entry));
                 for entry in entries {
                     let branch = entry.worktree.branch.as_deref().unwrap_or("detached");
                     let display = entry.worktree.display_name();
+                    let marker = glyphs.worktree_prefix(is_favorite(entry));
                     items.push(TreeItem {
-                        left: format!("  {}", branch),
+                        left: format!("{marker}{branch}"),
                         right: display.to_string(),
                         kind: TreeItemKind::Worktree {
                             repo: repo.name.clone(),
-                            entry: entry.clone(),
+                            entry: Box::new(entry.clone()),
                         },
                     });
                 }
@@ -1496,6 +2838,79 @@ fn build_tree_items(
     items
 }
 
+/// Drops a removed worktree's favorite and labels, so a later worktree reusing the name doesn't
+/// inherit them.
+fn forget_worktree(
+    favorites: &mut HashSet<TreeKey>,
+    labels: &mut HashMap<(String, String), Vec<String>>,
+    repo: &str,
+    name: &str,
+) {
+    favorites.remove(&TreeKey::Worktree {
+        repo: repo.to_string(),
+        name: name.to_string(),
+    });
+    labels.remove(&(repo.to_string(), name.to_string()));
+}
+
+fn forget_repo(
+    favorites: &mut HashSet<TreeKey>,
+    labels: &mut HashMap<(String, String), Vec<String>>,
+    repo: &str,
+) {
+    favorites.retain(|key| !matches!(key, TreeKey::Worktree { repo: owner, .. } if owner == repo));
+    labels.retain(|(owner, _), _| owner != repo);
+}
+
+/// Puts a deleted worktree's favorite and labels back under `name`, the name it was restored as.
+fn restore_worktree_marks(
+    favorites: &mut HashSet<TreeKey>,
+    labels: &mut HashMap<(String, String), Vec<String>>,
+    deleted: DeletedWorktree,
+    name: &str,
+) {
+    let repo = deleted.repo.name;
+    if deleted.favorite {
+        favorites.insert(TreeKey::Worktree {
+            repo: repo.clone(),
+            name: name.to_string(),
+        });
+    }
+    if !deleted.labels.is_empty() {
+        labels.insert((repo, name.to_string()), deleted.labels);
+    }
+}
+
+fn toggle_mark(marked: &mut HashSet<TreeKey>, key: TreeKey) {
+    if !marked.remove(&key) {
+        marked.insert(key);
+    }
+}
+
+/// Paths for marked worktrees in tree order, including those under collapsed repos.
+fn marked_worktree_paths(
+    repos: &[Repo],
+    repo_worktrees: &HashMap<String, Vec<WorktreeEntry>>,
+    marked: &HashSet<TreeKey>,
+) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    for repo in repos {
+        let Some(entries) = repo_worktrees.get(&repo.name) else {
+            continue;
+        };
+        for entry in entries {
+            let key = TreeKey::Worktree {
+                repo: repo.name.clone(),
+                name: entry.worktree.display_name(),
+            };
+            if marked.contains(&key) {
+                paths.push(entry.worktree.path.clone());
+            }
+        }
+    }
+    paths
+}
+
 fn tree_item_key(item: &TreeItem) -> TreeKey {
     match &item.kind {
         TreeItemKind::Repo { name, .. } => TreeKey::Repo(name.clone()),
@@ -1526,3 +2941,419 @@ fn tree_item_matches_key(item: &TreeItem, key: &TreeKey) -> bool {
         _ => false,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use std::collections::{HashMap, HashSet};
+    use std::path::PathBuf;
+    use std::sync::mpsc;
+
+    use bbq::{Repo, Worktree};
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use super::{
+        adjacent_repo_index, build_tree_items, forget_repo, forget_worktree, marked_worktree_paths,
+        on_off_options, pending_setup_steps, restore_worktree_marks, should_notify, theme_options,
+        toggle_mark, update_check_wanted, visible_repos, worktree_delete_step, App, DeleteStep,
+        SettingField, SettingRow, SettingsState, SetupOption, SetupState, SetupStep,
+    };
+    use crate::tui::constants::ACTIVITY_LOG_MAX;
+    use crate::tui::types::{
+        ActivityLog, DeletedWorktree, StatusMessage, StatusTone, TreeGlyphs, TreeKey,
+        WorkerEvent, WorkerRequest, WorktreeEntry,
+    };
+
+    /// An app wired to channels instead of a worker: requests it sends show up on the returned
+    /// receiver, and events sent on the returned sender reach it in `handle_worker_events`.
+    fn test_app() -> (App, mpsc::Receiver<WorkerRequest>, mpsc::Sender<WorkerEvent>) {
+        let (worker_tx, requests) = mpsc::channel();
+        let (events, worker_rx) = mpsc::channel();
+        (App::with_worker(worker_tx, worker_rx), requests, events)
+    }
+
+    fn worktree_entry(path: &str, branch: &str) -> WorktreeEntry {
+        WorktreeEntry {
+            worktree: Worktree {
+                path: PathBuf::from(path),
+                branch: Some(branch.to_string()),
+                head: None,
+            },
+            head_author: None,
+            head_message: None,
+            upstream: None,
+            sync_status: String::new(),
+            worktree_path: path.to_string(),
+            changed_files: Vec::new(),
+            changed_total: 0,
+            conflict_state: None,
+        }
+    }
+
+    /// Gives `app` repo `repo` with one clean worktree `name`, expanded and selected.
+    fn select_clean_worktree(app: &mut App, repo: &str, name: &str) {
+        app.repos = vec![Repo {
+            name: repo.to_string(),
+            path: PathBuf::from(format!("/repos/{repo}.git")),
+        }];
+        let entry = worktree_entry(&format!("/worktrees/{repo}/{name}"), name);
+        app.repo_worktrees.insert(repo.to_string(), vec![entry]);
+        app.expanded_repos.insert(repo.to_string());
+        app.rebuild_tree_items(Some(worktree_key(repo, name)));
+    }
+
+    fn worktree_key(repo: &str, name: &str) -> TreeKey {
+        TreeKey::Worktree {
+            repo: repo.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn clean_worktrees_skip_the_yes_prompt_when_confirmation_is_off() {
+        assert_eq!(worktree_delete_step(true, false, false), DeleteStep::ConfirmYes);
+        assert_eq!(worktree_delete_step(true, true, false), DeleteStep::Delete);
+        assert_eq!(worktree_delete_step(false, false, false), DeleteStep::Delete);
+        assert_eq!(worktree_delete_step(false, false, true), DeleteStep::ConfirmDiscard);
+        assert_eq!(worktree_delete_step(true, true, true), DeleteStep::ConfirmDiscard);
+    }
+
+    #[test]
+    fn delete_key_sends_clean_worktrees_straight_to_the_worker_when_confirmation_is_off() {
+        let (mut app, requests, _events) = test_app();
+        app.confirm_deletes = false;
+        select_clean_worktree(&mut app, "api", "lima");
+
+        app.handle_key(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE));
+        assert!(app.input.is_none());
+        match requests.try_recv() {
+            Ok(WorkerRequest::DeleteWorktree { repo, name, force }) => {
+                assert_eq!(repo.name, "api");
+                assert_eq!(name, "lima");
+                assert!(!force);
+            }
+            other => panic!("expected a delete request, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn ctrl_c_quits_from_the_custom_root_dir_input() {
+        let (mut app, _requests, _events) = test_app();
+        let mut setup = SetupState::from_step(SetupStep::RootDir);
+        setup.custom_input = Some("~/".to_string());
+        app.setup = Some(setup);
+
+        assert!(!app.handle_setup_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::NONE)));
+        let buffer = app.setup.as_ref().and_then(|setup| setup.custom_input.as_deref());
+        assert_eq!(buffer, Some("~/c"));
+        assert!(app.handle_setup_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)));
+    }
+
+    #[test]
+    fn theme_step_enqueued_only_when_theme_unset() {
+        let steps = pending_setup_steps(|_| false);
+        assert_eq!(steps.first(), Some(&SetupStep::RootDir));
+        assert!(steps.contains(&SetupStep::Theme));
+
+        let steps = pending_setup_steps(|step| step == SetupStep::Theme);
+        assert!(!steps.contains(&SetupStep::Theme));
+        assert_eq!(
+            steps,
+            vec![
+                SetupStep::RootDir,
+                SetupStep::DefaultWorktreeName,
+                SetupStep::Editor,
+                SetupStep::Terminal
+            ]
+        );
+    }
+
+    #[test]
+    fn settings_screen_moves_between_rows_and_cycles_values() {
+        let mut settings = SettingsState::new(vec![
+            SettingRow::new(SettingField::Theme, theme_options(), Some("blue")),
+            SettingRow::new(SettingField::GithubPrefix, on_off_options(), Some("true")),
+            SettingRow::new(SettingField::CheckUpdates, on_off_options(), Some("false")),
+        ]);
+        assert_eq!(settings.rows[0].current_label(), "blue");
+        assert_eq!(settings.rows[2].current_label(), "off");
+
+        settings.move_selection(-1);
+        assert_eq!(settings.selected, 2);
+        settings.move_selection(1);
+        settings.move_selection(1);
+        assert_eq!(settings.selected, 1);
+
+        assert_eq!(
+            settings.cycle(1),
+            Some((SettingField::GithubPrefix, Some("false".to_string())))
+        );
+        assert_eq!(settings.rows[1].current_label(), "off");
+        assert_eq!(
+            settings.cycle(-1),
+            Some((SettingField::GithubPrefix, Some("true".to_string())))
+        );
+    }
+
+    #[test]
+    fn settings_row_keeps_unlisted_value() {
+        let options = vec![SetupOption {
+            label: "Zed".to_string(),
+            value: Some("zed".to_string()),
+        }];
+        let row = SettingRow::new(SettingField::Editor, options, Some("nvim --remote"));
+        assert_eq!(row.current, 0);
+        assert_eq!(row.current_label(), "nvim --remote");
+        assert_eq!(row.options.len(), 2);
+    }
+
+    #[test]
+    fn only_bound_keys_mark_the_tree_view_dirty() {
+        let (mut app, _requests, _events) = test_app();
+        let press = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        for (code, bound) in [
+            (KeyCode::Char('z'), false),
+            (KeyCode::F(5), false),
+            (KeyCode::Down, true),
+            (KeyCode::Char('d'), true),
+        ] {
+            app.dirty = false;
+            assert!(!app.handle_key(press(code)));
+            assert_eq!(app.dirty, bound, "{code:?}");
+        }
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert!(app.handle_key(ctrl_c));
+    }
+
+    #[test]
+    fn update_check_runs_only_online_when_enabled_and_applicable() {
+        assert!(update_check_wanted(false, true, true));
+        assert!(!update_check_wanted(true, true, true));
+        assert!(!update_check_wanted(false, false, true));
+        assert!(!update_check_wanted(false, true, false));
+    }
+
+    #[test]
+    fn notifies_only_after_threshold() {
+        assert!(!should_notify(Duration::from_millis(0)));
+        assert!(!should_notify(Duration::from_millis(4999)));
+        assert!(should_notify(Duration::from_secs(5)));
+        assert!(should_notify(Duration::from_secs(90)));
+    }
+
+    #[test]
+    fn activity_log_records_completions_and_stays_bounded() {
+        let mut log = ActivityLog::new(ACTIVITY_LOG_MAX, Duration::from_secs(60));
+        log.push("✓ Created worktree lima");
+        assert_eq!(log.len(), 1);
+
+        for idx in 0..ACTIVITY_LOG_MAX + 2 {
+            log.push(format!("✓ Deleted worktree {idx}"));
+        }
+        assert_eq!(log.len(), ACTIVITY_LOG_MAX);
+        let texts: Vec<&str> = log.entries().map(|entry| entry.text.as_str()).collect();
+        assert_eq!(texts.last(), Some(&"✓ Deleted worktree 4"));
+        assert!(!texts.contains(&"✓ Created worktree lima"));
+
+        log.prune(Instant::now() + Duration::from_secs(61));
+        assert_eq!(log.len(), 0);
+    }
+
+    #[test]
+    fn finished_actions_from_the_worker_land_in_the_activity_log() {
+        let (mut app, _requests, events) = test_app();
+        let repo = Repo {
+            name: "api".to_string(),
+            path: PathBuf::from("/repos/api.git"),
+        };
+        events
+            .send(WorkerEvent::CheckoutRepoResult { result: Ok(repo) })
+            .expect("send event");
+
+        app.handle_worker_events();
+        let texts: Vec<&str> = app.activity.entries().map(|entry| entry.text.as_str()).collect();
+        assert_eq!(texts, vec!["✓ Cloned api"]);
+    }
+
+    #[test]
+    fn favorite_worktrees_sort_first_within_repo() {
+        let repos = vec![Repo {
+            name: "api".to_string(),
+            path: PathBuf::from("/repos/api.git"),
+        }];
+        let mut repo_worktrees = HashMap::new();
+        repo_worktrees.insert(
+            "api".to_string(),
+            vec![
+                worktree_entry("/worktrees/api/lima", "lima"),
+                worktree_entry("/worktrees/api/oslo", "oslo"),
+                worktree_entry("/worktrees/api/rome", "rome"),
+            ],
+        );
+        let expanded: HashSet<String> = ["api".to_string()].into_iter().collect();
+        let favorites: HashSet<TreeKey> =
+            [worktree_key("api", "rome"), worktree_key("api", "oslo")].into_iter().collect();
+
+        let items = build_tree_items(
+            &repos,
+            &repo_worktrees,
+            &HashMap::new(),
+            &expanded,
+            &favorites,
+            &TreeGlyphs::unicode(2),
+        );
+        let lefts: Vec<&str> = items.iter().map(|item| item.left.as_str()).collect();
+        assert_eq!(lefts, vec!["api", "★ oslo", "★ rome", "  lima"]);
+    }
+
+    #[test]
+    fn status_countdown_rounds_remaining_time_up() {
+        let now = Instant::now();
+        let status = StatusMessage {
+            text: "saved".to_string(),
+            tone: StatusTone::Success,
+            deadline: now + Duration::from_millis(2_400),
+        };
+        assert_eq!(status.remaining_secs(now), 3);
+        assert_eq!(status.remaining_secs(now + Duration::from_millis(1_400)), 1);
+        assert_eq!(status.remaining_secs(now + Duration::from_millis(2_400)), 0);
+        assert_eq!(status.remaining_secs(now + Duration::from_secs(5)), 0);
+    }
+
+    #[test]
+    fn repo_jumps_skip_worktrees_and_wrap() {
+        let repo = |name: &str| Repo {
+            name: name.to_string(),
+            path: PathBuf::from(format!("/repos/{name}.git")),
+        };
+        let repos = vec![repo("api"), repo("docs"), repo("web")];
+        let mut repo_worktrees = HashMap::new();
+        repo_worktrees.insert(
+            "api".to_string(),
+            vec![
+                worktree_entry("/worktrees/api/lima", "lima"),
+                worktree_entry("/worktrees/api/oslo", "oslo"),
+            ],
+        );
+        let expanded: HashSet<String> = ["api".to_string()].into_iter().collect();
+        let items = build_tree_items(
+            &repos,
+            &repo_worktrees,
+            &HashMap::new(),
+            &expanded,
+            &HashSet::new(),
+            &TreeGlyphs::unicode(2),
+        );
+
+        assert_eq!(adjacent_repo_index(&items, Some(0), true), Some(3));
+        assert_eq!(adjacent_repo_index(&items, Some(1), true), Some(3));
+        assert_eq!(adjacent_repo_index(&items, Some(4), true), Some(0));
+        assert_eq!(adjacent_repo_index(&items, Some(2), false), Some(0));
+        assert_eq!(adjacent_repo_index(&items, Some(0), false), Some(4));
+        assert_eq!(adjacent_repo_index(&[], None, true), None);
+    }
+
+    #[test]
+    fn hide_empty_repos_drops_repos_without_worktrees() {
+        let repo = |name: &str| Repo {
+            name: name.to_string(),
+            path: PathBuf::from(format!("/repos/{name}.git")),
+        };
+        let repos = vec![repo("api"), repo("docs"), repo("web")];
+        let mut repo_worktrees = HashMap::new();
+        repo_worktrees.insert(
+            "api".to_string(),
+            vec![worktree_entry("/worktrees/api/lima", "lima")],
+        );
+        repo_worktrees.insert("docs".to_string(), Vec::new());
+        let names = |hide_empty, keep| {
+            let repos = visible_repos(&repos, &repo_worktrees, hide_empty, keep);
+            repos.into_iter().map(|repo| repo.name).collect::<Vec<_>>()
+        };
+
+        assert_eq!(names(false, None), vec!["api", "docs", "web"]);
+        // `web` hasn't loaded its worktrees yet, so it stays.
+        assert_eq!(names(true, None), vec!["api", "web"]);
+        // A just-cloned or selected repo stays until something else is selected.
+        assert_eq!(names(true, Some("docs")), vec!["api", "docs", "web"]);
+    }
+
+    #[test]
+    fn deleted_worktrees_lose_their_marks_until_restored() {
+        let mut favorites: HashSet<TreeKey> =
+            [worktree_key("api", "lima"), worktree_key("web", "oslo")].into_iter().collect();
+        let mut labels = HashMap::new();
+        labels.insert(("api".to_string(), "lima".to_string()), vec!["review".to_string()]);
+        labels.insert(("web".to_string(), "oslo".to_string()), vec!["spike".to_string()]);
+
+        forget_worktree(&mut favorites, &mut labels, "api", "lima");
+        assert!(!favorites.contains(&worktree_key("api", "lima")));
+        assert!(!labels.contains_key(&("api".to_string(), "lima".to_string())));
+
+        let deleted = DeletedWorktree {
+            repo: Repo {
+                name: "api".to_string(),
+                path: PathBuf::from("/repos/api.git"),
+            },
+            name: "lima".to_string(),
+            branch: "lima".to_string(),
+            favorite: true,
+            labels: vec!["review".to_string()],
+        };
+        restore_worktree_marks(&mut favorites, &mut labels, deleted, "lima-2");
+        assert!(favorites.contains(&worktree_key("api", "lima-2")));
+        assert_eq!(
+            labels.get(&("api".to_string(), "lima-2".to_string())),
+            Some(&vec!["review".to_string()])
+        );
+
+        forget_repo(&mut favorites, &mut labels, "web");
+        assert!(!favorites.contains(&worktree_key("web", "oslo")));
+        assert!(!labels.contains_key(&("web".to_string(), "oslo".to_string())));
+        assert!(favorites.contains(&worktree_key("api", "lima-2")));
+    }
+
+    #[test]
+    fn marked_worktrees_toggle_and_collect_paths_in_tree_order() {
+        let repos = vec![
+            Repo {
+                name: "api".to_string(),
+                path: PathBuf::from("/repos/api.git"),
+            },
+            Repo {
+                name: "web".to_string(),
+                path: PathBuf::from("/repos/web.git"),
+            },
+        ];
+        let mut repo_worktrees = HashMap::new();
+        repo_worktrees.insert(
+            "api".to_string(),
+            vec![
+                worktree_entry("/worktrees/api/lima", "lima"),
+                worktree_entry("/worktrees/api/oslo", "oslo"),
+            ],
+        );
+        repo_worktrees.insert(
+            "web".to_string(),
+            vec![worktree_entry("/worktrees/web/lima", "lima")],
+        );
+
+        let mut marked = HashSet::new();
+        toggle_mark(&mut marked, worktree_key("web", "lima"));
+        toggle_mark(&mut marked, worktree_key("api", "oslo"));
+        toggle_mark(&mut marked, worktree_key("api", "lima"));
+        toggle_mark(&mut marked, worktree_key("api", "oslo"));
+        toggle_mark(&mut marked, worktree_key("api", "gone"));
+        assert_eq!(marked.len(), 3);
+
+        let paths = marked_worktree_paths(&repos, &repo_worktrees, &marked);
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("/worktrees/api/lima"),
+                PathBuf::from("/worktrees/web/lima"),
+            ]
+        );
+    }
+}
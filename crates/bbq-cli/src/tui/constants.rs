@@ -7,5 +7,22 @@ pub(crate) const STATUS_MIN_MS: u64 = 2000;
 pub(crate) const STATUS_PER_CHAR_MS: u64 = 30;
 pub(crate) const STATUS_MAX_MS: u64 = 8000;
 
+pub(crate) const UNDO_STACK_MAX: usize = 5;
+
+pub(crate) const ACTIVITY_LOG_MAX: usize = 3;
+pub(crate) const ACTIVITY_LOG_TTL_MS: u64 = 15000;
+
+pub(crate) const AUTO_FETCH_TICK_MS: u64 = 5000;
+
+pub(crate) const NOTIFY_AFTER_MS: u64 = 5000;
+
+pub(crate) const MIN_TERMINAL_WIDTH: u16 = 40;
+pub(crate) const MIN_TERMINAL_HEIGHT: u16 = 10;
+pub(crate) const COMPACT_LAYOUT_WIDTH: u16 = 80;
+
 pub(crate) const SPINNER_INTERVAL_MS: u128 = 120;
+pub(crate) const ACTIVE_POLL_MS: u64 = 120;
+pub(crate) const IDLE_POLL_MS: u64 = 1000;
 pub(crate) const SPINNER_FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+pub(crate) const BRANCH_PICKER_MAX_ROWS: usize = 8;
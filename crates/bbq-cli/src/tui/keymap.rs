@@ -0,0 +1,247 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum KeyAction {
+    Clone,
+    New,
+    FromClipboard,
+    Delete,
+    Terminal,
+    Editor,
+    Reveal,
+    OpenRecent,
+    CopyGitCommand,
+    RenameBranch,
+    Mark,
+    Favorite,
+    Label,
+    FetchAll,
+    Settings,
+    ToggleEnv,
+    ToggleDetail,
+    Undo,
+    ThemeNext,
+    ThemePrev,
+    PrevRepo,
+    NextRepo,
+}
+
+impl KeyAction {
+    const ALL: [KeyAction; 22] = [
+        KeyAction::Clone,
+        KeyAction::New,
+        KeyAction::FromClipboard,
+        KeyAction::Delete,
+        KeyAction::Terminal,
+        KeyAction::Editor,
+        KeyAction::Reveal,
+        KeyAction::OpenRecent,
+        KeyAction::CopyGitCommand,
+        KeyAction::RenameBranch,
+        KeyAction::Mark,
+        KeyAction::Favorite,
+        KeyAction::Label,
+        KeyAction::FetchAll,
+        KeyAction::Settings,
+        KeyAction::ToggleEnv,
+        KeyAction::ToggleDetail,
+        KeyAction::Undo,
+        KeyAction::ThemeNext,
+        KeyAction::ThemePrev,
+        KeyAction::PrevRepo,
+        KeyAction::NextRepo,
+    ];
+
+    fn config_name(self) -> &'static str {
+        match self {
+            KeyAction::Clone => "clone",
+            KeyAction::New => "new",
+            KeyAction::FromClipboard => "from_clipboard",
+            KeyAction::Delete => "delete",
+            KeyAction::Terminal => "terminal",
+            KeyAction::Editor => "editor",
+            KeyAction::Reveal => "reveal",
+            KeyAction::OpenRecent => "open_recent",
+            KeyAction::CopyGitCommand => "copy_git_command",
+            KeyAction::RenameBranch => "rename_branch",
+            KeyAction::Mark => "mark",
+            KeyAction::Favorite => "favorite",
+            KeyAction::Label => "label",
+            KeyAction::FetchAll => "fetch_all",
+            KeyAction::Settings => "settings",
+            KeyAction::ToggleEnv => "toggle_env",
+            KeyAction::ToggleDetail => "toggle_detail",
+            KeyAction::Undo => "undo",
+            KeyAction::ThemeNext => "theme_next",
+            KeyAction::ThemePrev => "theme_prev",
+            KeyAction::PrevRepo => "prev_repo",
+            KeyAction::NextRepo => "next_repo",
+        }
+    }
+
+    fn default_key(self) -> char {
+        match self {
+            KeyAction::Clone => 'c',
+            KeyAction::New => 'n',
+            KeyAction::FromClipboard => 'p',
+            KeyAction::Delete => 'd',
+            KeyAction::Terminal => 't',
+            KeyAction::Editor => 'e',
+            KeyAction::Reveal => 'R',
+            KeyAction::OpenRecent => 'o',
+            KeyAction::CopyGitCommand => 'y',
+            KeyAction::RenameBranch => 'r',
+            KeyAction::Mark => 'm',
+            KeyAction::Favorite => 'f',
+            KeyAction::Label => 'l',
+            KeyAction::FetchAll => 'F',
+            KeyAction::Settings => ',',
+            KeyAction::ToggleEnv => 'i',
+            KeyAction::ToggleDetail => 'v',
+            KeyAction::Undo => 'u',
+            KeyAction::ThemeNext => 'h',
+            KeyAction::ThemePrev => 'H',
+            KeyAction::PrevRepo => '{',
+            KeyAction::NextRepo => '}',
+        }
+    }
+
+    fn from_config_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|action| action.config_name() == name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct KeyMap {
+    bindings: Vec<(KeyAction, char)>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        Self {
+            bindings: KeyAction::ALL
+                .into_iter()
+                .map(|action| (action, action.default_key()))
+                .collect(),
+        }
+    }
+}
+
+impl KeyMap {
+    /// Builds a key map from `[keys]` config entries, returning warnings for entries that were
+    /// ignored or keys bound to more than one action.
+    pub(crate) fn from_bindings(entries: &[(String, String)]) -> (Self, Vec<String>) {
+        let mut keymap = Self::default();
+        let mut warnings = Vec::new();
+
+        for (name, value) in entries {
+            let Some(action) = KeyAction::from_config_name(name.trim()) else {
+                warnings.push(format!("Unknown key action: {}", name.trim()));
+                continue;
+            };
+            let mut chars = value.chars();
+            let (Some(ch), None) = (chars.next(), chars.next()) else {
+                warnings.push(format!("Key for {} must be a single character", name.trim()));
+                continue;
+            };
+            if ch.is_whitespace() || ch.is_control() {
+                warnings.push(format!("Key for {} must be a visible character", name.trim()));
+                continue;
+            }
+            keymap.set(action, ch);
+        }
+
+        for (idx, (action, ch)) in keymap.bindings.iter().enumerate() {
+            if let Some((other, _)) = keymap.bindings[..idx]
+                .iter()
+                .find(|(_, existing)| existing == ch)
+            {
+                warnings.push(format!(
+                    "Key '{}' is bound to both {} and {}; using {}",
+                    ch,
+                    other.config_name(),
+                    action.config_name(),
+                    other.config_name()
+                ));
+            }
+        }
+
+        (keymap, warnings)
+    }
+
+    fn set(&mut self, action: KeyAction, ch: char) {
+        if let Some(binding) = self.bindings.iter_mut().find(|(item, _)| *item == action) {
+            binding.1 = ch;
+        }
+    }
+
+    pub(crate) fn key_for(&self, action: KeyAction) -> char {
+        self.bindings
+            .iter()
+            .find(|(item, _)| *item == action)
+            .map(|(_, ch)| *ch)
+            .unwrap_or_else(|| action.default_key())
+    }
+
+    pub(crate) fn action_for(&self, key: &KeyEvent) -> Option<KeyAction> {
+        let KeyCode::Char(ch) = key.code else {
+            return None;
+        };
+        if key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) {
+            return None;
+        }
+        // Some terminals report Shift+h as 'h' with SHIFT rather than 'H'.
+        let ch = if key.modifiers.contains(KeyModifiers::SHIFT) {
+            ch.to_ascii_uppercase()
+        } else {
+            ch
+        };
+        self.bindings
+            .iter()
+            .find(|(_, bound)| *bound == ch)
+            .map(|(action, _)| *action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{KeyAction, KeyMap};
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    fn press(ch: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn remapped_delete_replaces_default_key() {
+        let entries = vec![("delete".to_string(), "x".to_string())];
+        let (keymap, warnings) = KeyMap::from_bindings(&entries);
+        assert!(warnings.is_empty(), "unexpected warnings: {warnings:?}");
+        assert_eq!(keymap.action_for(&press('x')), Some(KeyAction::Delete));
+        assert_eq!(keymap.action_for(&press('d')), None);
+    }
+
+    #[test]
+    fn shifted_letters_match_uppercase_bindings() {
+        let keymap = KeyMap::default();
+        let shifted = KeyEvent::new(KeyCode::Char('h'), KeyModifiers::SHIFT);
+        assert_eq!(keymap.action_for(&shifted), Some(KeyAction::ThemePrev));
+        assert_eq!(keymap.action_for(&press('h')), Some(KeyAction::ThemeNext));
+    }
+
+    #[test]
+    fn conflicting_and_invalid_bindings_warn() {
+        let entries = vec![
+            ("delete".to_string(), "t".to_string()),
+            ("launch".to_string(), "l".to_string()),
+            ("clone".to_string(), "cc".to_string()),
+        ];
+        let (keymap, warnings) = KeyMap::from_bindings(&entries);
+        assert_eq!(warnings.len(), 3, "unexpected warnings: {warnings:?}");
+        assert!(warnings.iter().any(|warning| warning.contains("bound to both")));
+        assert_eq!(keymap.action_for(&press('t')), Some(KeyAction::Delete));
+        assert_eq!(keymap.key_for(KeyAction::Clone), 'c');
+    }
+}
@@ -1,20 +1,35 @@
 mod app;
 mod constants;
+mod keymap;
 mod render;
 mod types;
 mod worker;
 
-use std::io::{self, Stdout};
+use std::io::{self, Stdout, Write};
+use std::panic;
+use std::time::Duration;
 
+use crossterm::cursor::Show;
 use crossterm::event::{self, DisableMouseCapture, EnableMouseCapture, Event};
 use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
 use crossterm::execute;
 use ratatui::prelude::*;
 
 use app::App;
+use constants::{ACTIVE_POLL_MS, IDLE_POLL_MS};
 use render::ui;
 
+use crate::config::load_poll_ms;
+
 pub(crate) fn run_tui() -> Result<(), Box<dyn std::error::Error>> {
+    // Without this a panic would print into the alternate screen and leave the shell in raw mode.
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        restore_terminal(&mut io::stdout());
+        default_hook(info);
+    }));
+
+    bbq::enable_abort();
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
@@ -26,13 +41,7 @@ pub(crate) fn run_tui() -> Result<(), Box<dyn std::error::Error>> {
     let app = App::new();
     let res = run_app(&mut terminal, app);
 
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+    restore_terminal(terminal.backend_mut());
 
     if let Err(err) = res {
         eprintln!("error: {err}");
@@ -41,14 +50,32 @@ pub(crate) fn run_tui() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Leaves raw mode and the alternate screen. Every step is best-effort and harmless to repeat,
+/// so this is safe to call from the panic hook even after a normal exit already ran it.
+fn restore_terminal(out: &mut impl Write) {
+    let _ = disable_raw_mode();
+    let _ = execute!(out, LeaveAlternateScreen, DisableMouseCapture, Show);
+}
+
 fn run_app(terminal: &mut Terminal<CrosstermBackend<Stdout>>, mut app: App) -> io::Result<()> {
+    let idle_poll = Duration::from_millis(load_poll_ms().unwrap_or(IDLE_POLL_MS));
     loop {
         app.update_status();
         app.handle_worker_events();
-        terminal.draw(|frame| ui(frame, &mut app))?;
+        if app.needs_redraw() {
+            terminal.draw(|frame| ui(frame, &mut app))?;
+        }
 
-        if event::poll(std::time::Duration::from_millis(200))? {
-            if let Event::Key(key) = event::read()? {
+        if event::poll(poll_interval(app.is_animating(), idle_poll))? {
+            let event = event::read()?;
+            if let Event::Resize(_, _) = event {
+                // Drop the old buffer so the next draw repaints every cell at the new size.
+                terminal.autoresize()?;
+                terminal.clear()?;
+                app.mark_dirty();
+                continue;
+            }
+            if let Event::Key(key) = event {
                 if app.is_update_prompt_mode() {
                     if app.handle_update_prompt_key(key) {
                         app.persist_restore_state();
@@ -59,6 +86,11 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<Stdout>>, mut app: App) -> i
                         app.persist_restore_state();
                         return Ok(());
                     }
+                } else if app.is_settings_mode() {
+                    if app.handle_settings_key(key) {
+                        app.persist_restore_state();
+                        return Ok(());
+                    }
                 } else if app.is_input_mode() {
                     app.handle_input(key);
                 } else if app.handle_key(key) {
@@ -69,3 +101,40 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<Stdout>>, mut app: App) -> i
         }
     }
 }
+
+/// Idle wakeups only pick up worker results, so they can be slow; an animating screen needs
+/// frames at the spinner's pace.
+fn poll_interval(animating: bool, idle: Duration) -> Duration {
+    if animating {
+        idle.min(Duration::from_millis(ACTIVE_POLL_MS))
+    } else {
+        idle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{poll_interval, restore_terminal};
+
+    #[test]
+    fn restore_terminal_can_run_twice() {
+        let mut first = Vec::new();
+        restore_terminal(&mut first);
+        let mut second = Vec::new();
+        restore_terminal(&mut second);
+        assert!(!first.is_empty());
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn poll_interval_is_short_only_while_animating() {
+        let idle = Duration::from_millis(1000);
+        assert_eq!(poll_interval(false, idle), idle);
+        assert_eq!(poll_interval(true, idle), Duration::from_millis(120));
+        let fast = Duration::from_millis(50);
+        assert_eq!(poll_interval(true, fast), fast);
+        assert_eq!(poll_interval(false, fast), fast);
+    }
+}
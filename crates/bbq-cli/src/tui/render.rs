@@ -1,16 +1,26 @@
+use bbq::ChangedFile;
 use ratatui::prelude::*;
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 use super::constants::{
+    BRANCH_PICKER_MAX_ROWS, COMPACT_LAYOUT_WIDTH, MIN_TERMINAL_HEIGHT, MIN_TERMINAL_WIDTH,
     SELECTED_SECONDARY, SELECTED_TEXT, SPINNER_FRAMES, SPINNER_INTERVAL_MS,
 };
-use super::types::{Focus, InputState, TreeItemKind, WorktreeEntry};
-use crate::tui::app::App;
+use super::keymap::KeyAction;
+use super::types::{Focus, InputState, TreeGlyphs, TreeItemKind, TreeKey, WorktreeEntry};
+use crate::env_info::config_path_display;
+use crate::theme::{theme_index_by_name, THEMES};
+use crate::tui::app::{App, SetupStep};
 
 const BBQ_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 pub(crate) fn ui(frame: &mut Frame, app: &mut App) {
+    if terminal_too_small(frame.size()) {
+        render_too_small(frame, app);
+        return;
+    }
     if app.is_update_prompt_mode() {
         render_update_prompt(frame, app);
         return;
@@ -19,52 +29,157 @@ pub(crate) fn ui(frame: &mut Frame, app: &mut App) {
         render_setup(frame, app);
         return;
     }
+    if app.is_settings_mode() {
+        render_settings(frame, app);
+        return;
+    }
 
     let size = frame.size();
     let inner = size;
     let footer_height = footer_height(app, inner.width).min(inner.height);
-    let chunks =
-        Layout::vertical([Constraint::Min(0), Constraint::Length(footer_height)]).split(inner);
-    let columns = Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
-        .split(chunks[0]);
-
-    if app.repos.is_empty() {
-        if let Some(loading) = app.loading_message(super::types::LoadingGroup::Repos) {
-            render_loading_column(
-                frame,
-                columns[0],
-                "Repos & Worktrees",
-                app.theme_color(),
-                loading.started_at,
-            );
-        } else {
-            render_empty_repos_column(frame, columns[0], app.theme_color());
+    let activity_height = (app.activity.len() as u16)
+        .min(inner.height.saturating_sub(footer_height) / 2);
+    let chunks = Layout::vertical([
+        Constraint::Min(0),
+        Constraint::Length(activity_height),
+        Constraint::Length(footer_height),
+    ])
+    .split(inner);
+    let tree_area = match layout_mode(size.width) {
+        LayoutMode::Split => {
+            let columns =
+                Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .split(chunks[0]);
+            render_tree_pane(frame, columns[0], app);
+            render_detail_pane(frame, columns[1], app);
+            columns[0]
         }
+        LayoutMode::Compact if app.show_detail => {
+            render_detail_pane(frame, chunks[0], app);
+            chunks[0]
+        }
+        LayoutMode::Compact => {
+            render_tree_pane(frame, chunks[0], app);
+            chunks[0]
+        }
+    };
+    if activity_height > 0 {
+        render_activity(frame, chunks[1], app);
+    }
+    if let Some((matches, selected)) = app.branch_matches() {
+        render_branch_picker(frame, tree_area, &matches, selected, app.theme_color());
+    }
+    if let Some(input) = app.input.as_ref() {
+        render_prompt_line(frame, chunks[2], input, app.theme_color());
     } else {
-        render_tree_list(frame, columns[0], app);
+        render_status(frame, chunks[2], app);
     }
-    let env_height = env_box_height(columns[1].height);
-    let right_chunks =
-        Layout::vertical([Constraint::Min(0), Constraint::Length(env_height)]).split(columns[1]);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LayoutMode {
+    /// Tree and worktree detail side by side.
+    Split,
+    /// One pane at a time; the detail replaces the tree when toggled.
+    Compact,
+}
+
+fn layout_mode(width: u16) -> LayoutMode {
+    if width < COMPACT_LAYOUT_WIDTH {
+        LayoutMode::Compact
+    } else {
+        LayoutMode::Split
+    }
+}
+
+fn render_tree_pane(frame: &mut Frame, area: Rect, app: &mut App) {
+    if !app.repos.is_empty() {
+        render_tree_list(frame, area, app);
+    } else if let Some(loading) = app.loading_message(super::types::LoadingGroup::Repos) {
+        render_loading_column(
+            frame,
+            area,
+            "Repos & Worktrees",
+            app.theme_color(),
+            loading.started_at,
+        );
+    } else {
+        render_empty_repos_column(frame, area, app.theme_color());
+    }
+}
+
+fn render_detail_pane(frame: &mut Frame, area: Rect, app: &App) {
+    let (_, env_height) = right_column_heights(area.height, app.show_env);
+    let chunks = Layout::vertical([Constraint::Min(0), Constraint::Length(env_height)]).split(area);
     if let Some(entry) = app.selected_worktree_entry() {
-        render_worktree_info(frame, right_chunks[0], entry, app);
+        render_worktree_info(frame, chunks[0], entry, app);
     } else {
         render_empty_column(
             frame,
-            right_chunks[0],
+            chunks[0],
             "Worktree",
             "No worktree selected",
             app.theme_color(),
         );
     }
     if env_height > 0 {
-        render_env_info(frame, right_chunks[1], app);
+        render_env_info(frame, chunks[1], app);
     }
-    if let Some(input) = app.input.as_ref() {
-        render_prompt_line(frame, chunks[1], input, app.theme_color());
-    } else {
-        render_status(frame, chunks[1], app);
+}
+
+/// Branch suggestions drawn over the bottom of the tree column while the source prompt is open.
+fn render_branch_picker(
+    frame: &mut Frame,
+    area: Rect,
+    branches: &[&str],
+    selected: Option<usize>,
+    color: Color,
+) {
+    let height = (branches.len().min(BRANCH_PICKER_MAX_ROWS) as u16 + 2).min(area.height);
+    if height < 3 || area.width < 4 {
+        return;
     }
+    let popup = Rect {
+        x: area.x,
+        y: area.y + area.height - height,
+        width: area.width,
+        height,
+    };
+    let items: Vec<ListItem> = branches
+        .iter()
+        .map(|branch| ListItem::new(format!(" {branch}")))
+        .collect();
+    let mut state = ListState::default();
+    state.select(selected);
+    frame.render_widget(Clear, popup);
+    render_list(
+        frame,
+        popup,
+        "Branches (↑/↓, tab)",
+        items,
+        &mut state,
+        color,
+        HighlightMode::Primary,
+    );
+}
+
+fn render_activity(frame: &mut Frame, area: Rect, app: &App) {
+    let area = inset_h(area, 1);
+    if area.width == 0 || area.height == 0 {
+        return;
+    }
+
+    let dim = Style::default()
+        .fg(app.theme_color())
+        .add_modifier(Modifier::DIM);
+    let skip = app.activity.len().saturating_sub(area.height as usize);
+    let lines: Vec<Line> = app
+        .activity
+        .entries()
+        .skip(skip)
+        .map(|entry| Line::from(Span::styled(entry.text.clone(), dim)))
+        .collect();
+    frame.render_widget(Paragraph::new(lines), area);
 }
 
 fn render_setup(frame: &mut Frame, app: &mut App) {
@@ -89,7 +204,17 @@ fn render_setup(frame: &mut Frame, app: &mut App) {
     for (idx, option) in setup.options.iter().enumerate() {
         let selected = idx == setup.selected;
         let marker = if selected { "◉" } else { "○" };
-        let style = if selected { highlight } else { normal };
+        let mut style = if selected { highlight } else { normal };
+        if setup.step == SetupStep::Theme {
+            let preview = option
+                .value
+                .as_deref()
+                .and_then(theme_index_by_name)
+                .and_then(|index| THEMES.get(index));
+            if let Some(theme) = preview {
+                style = style.fg(theme.color());
+            }
+        }
         lines.push(Line::from(Span::styled(
             format!("{indent}{marker} {}", option.label),
             style,
@@ -97,12 +222,85 @@ fn render_setup(frame: &mut Frame, app: &mut App) {
     }
 
     lines.push(Line::from(Span::raw("")));
+    if let Some(buffer) = setup.custom_input.as_deref() {
+        lines.push(Line::from(vec![
+            Span::styled(format!("{indent}path > "), highlight),
+            Span::styled(format!("{buffer}█"), normal),
+        ]));
+        lines.push(Line::from(Span::raw("")));
+        lines.push(Line::from(Span::styled(
+            format!("{indent}Enter to confirm, Esc to go back."),
+            dim,
+        )));
+    } else {
+        lines.push(Line::from(Span::styled(
+            format!("{indent}Use ↑/↓ to choose, Enter to confirm."),
+            dim,
+        )));
+    }
     lines.push(Line::from(Span::styled(
-        format!("{indent}Use ↑/↓ to choose, Enter to confirm."),
+        format!("{indent}You can edit {} later.", config_path_display()),
         dim,
     )));
+
+    if let Some(status) = app.status.as_ref() {
+        lines.push(Line::from(Span::raw("")));
+        lines.push(Line::from(Span::styled(
+            format!("{indent}{}", status.text),
+            normal.add_modifier(Modifier::BOLD),
+        )));
+    }
+
+    let paragraph = Paragraph::new(lines).wrap(Wrap { trim: false });
+    frame.render_widget(paragraph, area);
+}
+
+fn render_settings(frame: &mut Frame, app: &mut App) {
+    let Some(settings) = app.settings_state() else {
+        return;
+    };
+
+    let area = frame.size();
+    let color = app.theme_color();
+    let dim = Style::default().fg(color).add_modifier(Modifier::DIM);
+    let highlight = Style::default().fg(color).add_modifier(Modifier::BOLD);
+    let normal = Style::default().fg(color);
+    let indent = "  ";
+    let label_width = settings
+        .rows
+        .iter()
+        .map(|row| row.field.label().len())
+        .max()
+        .unwrap_or(0);
+
+    let mut lines = Vec::new();
+    lines.push(Line::from(Span::styled(format!("{indent}Settings"), highlight)));
+    lines.push(Line::from(Span::raw("")));
+
+    for (idx, row) in settings.rows.iter().enumerate() {
+        let selected = idx == settings.selected;
+        let marker = if selected { "◉" } else { "○" };
+        let value = if selected {
+            format!("‹ {} ›", row.current_label())
+        } else {
+            row.current_label().to_string()
+        };
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("{indent}{marker} {:<label_width$}  ", row.field.label()),
+                if selected { highlight } else { normal },
+            ),
+            Span::styled(value, if selected { highlight } else { dim }),
+        ]));
+    }
+
+    lines.push(Line::from(Span::raw("")));
     lines.push(Line::from(Span::styled(
-        format!("{indent}You can edit ~/.bbq/config.toml later."),
+        format!("{indent}Use ↑/↓ to choose, ←/→ to change, Esc to close."),
+        dim,
+    )));
+    lines.push(Line::from(Span::styled(
+        format!("{indent}Changes are saved to {} right away.", config_path_display()),
         dim,
     )));
 
@@ -226,11 +424,12 @@ fn render_tree_list(frame: &mut Frame, area: Rect, app: &mut App) {
                         repo_right_style
                     };
                     let mut right_parts = Vec::new();
-                    if !*expanded {
+                    if !*expanded || *worktree_count == 0 {
                         right_parts.push((count_text, count_style));
                         right_parts.push((" ".to_string(), count_style));
                     }
-                    right_parts.push((if *expanded { "↓" } else { "→" }.to_string(), arrow_style));
+                    let marker = app.tree_glyphs.repo_marker(*expanded, *worktree_count);
+                    right_parts.push((marker.to_string(), arrow_style));
                     list_item_with_right_parts(
                         &item.left,
                         if is_selected {
@@ -242,21 +441,58 @@ fn render_tree_list(frame: &mut Frame, area: Rect, app: &mut App) {
                         area.width,
                     )
                 }
-                TreeItemKind::Worktree { .. } => list_item_with_right_text(
-                    &item.left,
-                    &item.right,
-                    if is_selected {
+                TreeItemKind::Worktree { repo, entry } => {
+                    let key = TreeKey::Worktree {
+                        repo: repo.clone(),
+                        name: entry.worktree.display_name(),
+                    };
+                    let left = if app.marked.contains(&key) {
+                        marked_left(&app.tree_glyphs, &item.left)
+                    } else {
+                        item.left.clone()
+                    };
+                    let left_style = if is_selected {
                         selected_primary
                     } else {
                         worktree_left_style
-                    },
-                    if is_selected {
+                    };
+                    let right_style = if is_selected {
                         selected_secondary
                     } else {
                         worktree_right_style
-                    },
-                    area.width,
-                ),
+                    };
+                    // Churn is extra; drop it rather than squeeze the worktree name.
+                    let content_width = area.width.saturating_sub(2) as usize;
+                    let churn = app
+                        .tree_show_churn
+                        .then(|| {
+                            churn_text(&app.tree_glyphs, &entry.changed_files, entry.changed_total)
+                        })
+                        .flatten()
+                        .filter(|churn| {
+                            left.width() + 1 + churn.width() + 1 + item.right.width()
+                                <= content_width
+                        });
+                    match churn {
+                        Some(churn) => list_item_with_right_parts(
+                            &left,
+                            left_style,
+                            vec![
+                                (churn, right_style),
+                                (" ".to_string(), right_style),
+                                (item.right.clone(), right_style),
+                            ],
+                            area.width,
+                        ),
+                        None => list_item_with_right_text(
+                            &left,
+                            &item.right,
+                            left_style,
+                            right_style,
+                            area.width,
+                        ),
+                    }
+                }
             }
         })
         .collect();
@@ -272,6 +508,34 @@ fn render_tree_list(frame: &mut Frame, area: Rect, app: &mut App) {
     );
 }
 
+/// `+added -removed` summed over a worktree's changed files, or `None` when nothing changed.
+/// When `max_changed_files` left some of the `total` files out, the sum is only a lower bound
+/// and is prefixed with the `at_least` glyph.
+fn churn_text(glyphs: &TreeGlyphs, files: &[ChangedFile], total: usize) -> Option<String> {
+    let (added, removed) = files.iter().fold((0u64, 0u64), |(added, removed), file| {
+        (added + u64::from(file.added), removed + u64::from(file.removed))
+    });
+    let partial = if total > files.len() { glyphs.at_least } else { "" };
+    (added > 0 || removed > 0).then(|| format!("{partial}+{added} -{removed}"))
+}
+
+/// Writes the mark and a space over the start of the row's indent, so marked rows keep the
+/// same alignment as the rest.
+fn marked_left(glyphs: &TreeGlyphs, left: &str) -> String {
+    let width = glyphs.mark.width() + 1;
+    let mut covered = 0;
+    let mut rest = left;
+    while covered < width {
+        let Some(ch) = rest.chars().next() else {
+            break;
+        };
+        covered += ch.width().unwrap_or(0);
+        rest = &rest[ch.len_utf8()..];
+    }
+    let pad = " ".repeat(covered.saturating_sub(width));
+    format!("{} {pad}{rest}", glyphs.mark)
+}
+
 fn render_env_info(frame: &mut Frame, area: Rect, app: &App) {
     if area.height == 0 || area.width == 0 {
         return;
@@ -365,6 +629,9 @@ fn render_worktree_info(frame: &mut Frame, area: Rect, entry: &WorktreeEntry, ap
     }
 
     let name = entry.worktree.display_name();
+    let labels = app
+        .selected_repo()
+        .and_then(|repo| app.labels.get(&(repo.name.clone(), name.clone())));
     let repo = app
         .selected_repo()
         .map(|repo| app.display_repo_name(&repo.name));
@@ -399,6 +666,7 @@ fn render_worktree_info(frame: &mut Frame, area: Rect, entry: &WorktreeEntry, ap
         "Head:",
         "Sync:",
         "Changes:",
+        "Labels:",
     ]);
 
     let mut lines = Vec::new();
@@ -418,6 +686,9 @@ fn render_worktree_info(frame: &mut Frame, area: Rect, entry: &WorktreeEntry, ap
         label_width,
         inner.width,
     ));
+    if let Some(labels) = labels {
+        lines.push(label_chips_line(labels, color, dim, label_width));
+    }
     lines.push(aligned_info_line(
         "Dir: ",
         &dir_value,
@@ -477,6 +748,16 @@ fn render_worktree_info(frame: &mut Frame, area: Rect, entry: &WorktreeEntry, ap
         label_width,
         inner.width,
     ));
+    if let Some(state) = entry.conflict_state {
+        lines.push(aligned_info_line(
+            "",
+            &format!("⚠ {}", state.label()),
+            dim,
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            label_width,
+            inner.width,
+        ));
+    }
     let remaining = (inner.height as usize).saturating_sub(lines.len());
     if remaining > 0 {
         let dash_count = inner.width as usize;
@@ -505,9 +786,10 @@ fn render_worktree_info(frame: &mut Frame, area: Rect, entry: &WorktreeEntry, ap
                 .collect()
         };
 
-        if !entry.changed_files.is_empty() && items.len() > remaining {
-            let visible = remaining.saturating_sub(1);
-            let more_count = items.len().saturating_sub(visible);
+        let total = entry.changed_total.max(entry.changed_files.len());
+        if !entry.changed_files.is_empty() && (items.len() > remaining || total > items.len()) {
+            let visible = items.len().min(remaining.saturating_sub(1));
+            let more_count = total.saturating_sub(visible);
             items.truncate(visible);
             items.push((format!("(+{} more)", more_count), String::new(), dim, dim));
         }
@@ -536,6 +818,24 @@ fn render_worktree_info(frame: &mut Frame, area: Rect, entry: &WorktreeEntry, ap
     frame.render_widget(paragraph, inner);
 }
 
+/// Worktree labels as small filled chips after a `Labels:` heading.
+fn label_chips_line(
+    labels: &[String],
+    color: Color,
+    label_style: Style,
+    label_width: usize,
+) -> Line<'static> {
+    let chip = Style::default().fg(SELECTED_TEXT).bg(color);
+    let mut spans = vec![Span::styled(pad_to_width("Labels: ", label_width), label_style)];
+    for (idx, label) in labels.iter().enumerate() {
+        if idx > 0 {
+            spans.push(Span::raw(" "));
+        }
+        spans.push(Span::styled(format!(" {label} "), chip));
+    }
+    Line::from(spans)
+}
+
 #[derive(Clone, Copy)]
 enum HighlightMode {
     Primary,
@@ -584,7 +884,7 @@ fn list_item_with_right_text(
         return ListItem::new(Line::from(Span::raw(String::new())));
     }
 
-    let right_len = right.chars().count();
+    let right_len = right.width();
     if right_len >= content_width {
         let right_text = truncate_to_width(right, content_width);
         return ListItem::new(Line::from(Span::styled(right_text, right_style)));
@@ -592,7 +892,7 @@ fn list_item_with_right_text(
 
     let max_left = content_width.saturating_sub(right_len + 1);
     let left_text = truncate_left_from_start(left, max_left);
-    let left_len = left_text.chars().count();
+    let left_len = left_text.width();
     let padding = content_width.saturating_sub(left_len + right_len);
     let spaces = " ".repeat(padding);
 
@@ -616,7 +916,7 @@ fn list_item_with_right_parts(
 
     let right_len: usize = right_parts
         .iter()
-        .map(|(text, _)| text.chars().count())
+        .map(|(text, _)| text.width())
         .sum();
     let right_text: String = right_parts
         .iter()
@@ -634,7 +934,7 @@ fn list_item_with_right_parts(
 
     let max_left = content_width.saturating_sub(right_len + 1);
     let left_text = truncate_to_width(left, max_left);
-    let left_len = left_text.chars().count();
+    let left_len = left_text.width();
     let padding = content_width.saturating_sub(left_len + right_len);
     let spaces = " ".repeat(padding);
     let mut spans: Vec<Span> = Vec::new();
@@ -646,27 +946,45 @@ fn list_item_with_right_parts(
     ListItem::new(Line::from(spans))
 }
 
+/// Longest prefix of `text` that fits in `max` terminal cells.
 fn truncate_to_width(text: &str, max: usize) -> String {
-    text.chars().take(max).collect()
+    let mut width = 0;
+    let mut out = String::new();
+    for ch in text.chars() {
+        width += ch.width().unwrap_or(0);
+        if width > max {
+            break;
+        }
+        out.push(ch);
+    }
+    out
+}
+
+/// Longest suffix of `text` that fits in `max` terminal cells.
+fn tail_to_width(text: &str, max: usize) -> String {
+    let mut width = 0;
+    let mut chars: Vec<char> = Vec::new();
+    for ch in text.chars().rev() {
+        width += ch.width().unwrap_or(0);
+        if width > max {
+            break;
+        }
+        chars.push(ch);
+    }
+    chars.into_iter().rev().collect()
 }
 
 fn truncate_from_start_with_ellipsis(text: &str, max: usize) -> String {
     if max == 0 {
         return String::new();
     }
-    let len = text.chars().count();
-    if len <= max {
+    if text.width() <= max {
         return text.to_string();
     }
     if max == 1 {
         return "…".to_string();
     }
-    let mut chars: Vec<char> = text.chars().rev().take(max).collect();
-    chars.reverse();
-    if !chars.is_empty() {
-        chars[0] = '…';
-    }
-    chars.into_iter().collect()
+    format!("…{}", tail_to_width(text, max - 1))
 }
 
 fn truncate_left_from_start(text: &str, max: usize) -> String {
@@ -684,9 +1002,9 @@ fn truncate_left_from_start(text: &str, max: usize) -> String {
             remainder.push(ch);
         }
     }
-    let prefix_len = prefix.chars().count();
+    let prefix_len = prefix.width();
     if prefix_len >= max {
-        return prefix.chars().take(max).collect();
+        return truncate_to_width(&prefix, max);
     }
     let available = max - prefix_len;
     let tail = truncate_from_start_with_ellipsis(&remainder, available);
@@ -697,16 +1015,14 @@ fn truncate_after_first_slash(text: &str, max: usize) -> String {
     if max == 0 {
         return String::new();
     }
-    let len = text.chars().count();
-    if len <= max {
+    if text.width() <= max {
         return text.to_string();
     }
 
-    let mut iter = text.chars();
     let mut prefix = String::new();
     let mut remainder = String::new();
     let mut found_slash = false;
-    while let Some(ch) = iter.next() {
+    for ch in text.chars() {
         if !found_slash {
             prefix.push(ch);
             if ch == '/' {
@@ -717,7 +1033,7 @@ fn truncate_after_first_slash(text: &str, max: usize) -> String {
         }
     }
 
-    let prefix_len = prefix.chars().count();
+    let prefix_len = prefix.width();
     if prefix_len >= max {
         return truncate_to_width(&prefix, max);
     }
@@ -731,15 +1047,7 @@ fn truncate_after_first_slash(text: &str, max: usize) -> String {
     if available == 1 {
         return format!("{prefix}…");
     }
-    let tail_len = available - 1;
-    let tail = remainder
-        .chars()
-        .rev()
-        .take(tail_len)
-        .collect::<Vec<_>>()
-        .into_iter()
-        .rev()
-        .collect::<String>();
+    let tail = tail_to_width(&remainder, available - 1);
     format!("{prefix}…{tail}")
 }
 
@@ -895,24 +1203,53 @@ fn render_block_title(frame: &mut Frame, area: Rect, title: &str, color: Color)
     frame.render_widget(paragraph, title_area);
 }
 
-fn build_help_text(app: &App) -> String {
-    let mut items: Vec<&str> = Vec::new();
+fn build_help_text(app: &App, layout: LayoutMode) -> String {
+    let mut items: Vec<String> = Vec::new();
     let focus = app.effective_focus();
     let has_repos = !app.repos.is_empty();
+    let key = |action: KeyAction, label: &str| format!("{} {}", app.keymap.key_for(action), label);
 
     if focus == Focus::List || !has_repos {
-        items.push("c clone");
+        items.push(key(KeyAction::Clone, "clone"));
     }
     if app.selected_repo().is_some() {
-        items.push("n new worktree");
+        items.push(key(KeyAction::New, "new worktree"));
+        items.push(key(KeyAction::FromClipboard, "from clipboard"));
     }
     let delete_available = focus == Focus::List && app.selected_tree_item().is_some();
     if delete_available {
-        items.push("d delete");
+        items.push(key(KeyAction::Delete, "delete"));
     }
     if app.selected_worktree_entry().is_some() {
-        items.push("t terminal");
-        items.push("enter editor");
+        items.push(key(KeyAction::Terminal, "terminal"));
+        items.push(key(KeyAction::Reveal, "reveal"));
+        items.push(key(KeyAction::RenameBranch, "rename branch"));
+        items.push(key(KeyAction::Mark, "mark"));
+        items.push(key(KeyAction::Favorite, "favorite"));
+        items.push(key(KeyAction::Label, "label"));
+        items.push(key(KeyAction::CopyGitCommand, "copy git cmd"));
+        items.push("enter editor".to_string());
+    }
+    if !app.marked.is_empty() {
+        items.push(format!("{} marked", app.marked.len()));
+    }
+    if focus == Focus::List && app.has_recent_worktree() {
+        items.push(key(KeyAction::OpenRecent, "open recent"));
+    }
+    if focus == Focus::List && has_repos {
+        items.push(key(KeyAction::FetchAll, "fetch all"));
+    }
+    if focus == Focus::List && app.can_undo_delete() {
+        items.push(key(KeyAction::Undo, "undo"));
+    }
+    if layout == LayoutMode::Compact {
+        let label = if app.show_detail { "tree" } else { "detail" };
+        items.push(key(KeyAction::ToggleDetail, label));
+    }
+    if focus == Focus::List {
+        let label = if app.show_env { "hide env" } else { "show env" };
+        items.push(key(KeyAction::ToggleEnv, label));
+        items.push(key(KeyAction::Settings, "settings"));
     }
 
     items.join(" | ")
@@ -930,16 +1267,18 @@ fn render_status(frame: &mut Frame, area: Rect, app: &App) {
     let error = Style::default().fg(Color::Red).add_modifier(Modifier::BOLD);
     let error_dim = Style::default().fg(Color::Red);
 
-    let help = build_help_text(app);
+    let help = build_help_text(app, layout_mode(frame.size().width));
 
     if let Some(status) = app.status.as_ref() {
         let (prefix_style, message_style) = match status.tone {
             super::types::StatusTone::Success => (dim, normal),
             super::types::StatusTone::Error => (error_dim, error),
         };
+        let remaining = status.remaining_secs(std::time::Instant::now());
         let line = Line::from(vec![
             Span::styled("→ ", prefix_style),
             Span::styled(status.text.clone(), message_style),
+            Span::styled(format!(" ({remaining}s)"), prefix_style),
         ]);
         let paragraph = Paragraph::new(line).style(message_style).wrap(Wrap { trim: true });
         frame.render_widget(paragraph, area);
@@ -1011,11 +1350,48 @@ fn render_prompt_line(frame: &mut Frame, area: Rect, input: &InputState, color:
     let paragraph = Paragraph::new(line).style(base_style);
     frame.render_widget(paragraph, area);
 
-    let cursor_x = area.x + label.len() as u16 + input.buffer.len() as u16;
+    let cursor_x = area.x.saturating_add(prompt_cursor_offset(&label, &input.buffer));
     let cursor_x = cursor_x.min(area.x + area.width.saturating_sub(1));
     frame.set_cursor(cursor_x, area.y);
 }
 
+/// Cursor column after the label and buffer, in terminal cells rather than bytes.
+fn prompt_cursor_offset(label: &str, buffer: &str) -> u16 {
+    let width = label.width() + buffer.width();
+    width.min(u16::MAX as usize) as u16
+}
+
+fn terminal_too_small(size: Rect) -> bool {
+    size.width < MIN_TERMINAL_WIDTH || size.height < MIN_TERMINAL_HEIGHT
+}
+
+fn render_too_small(frame: &mut Frame, app: &App) {
+    let size = frame.size();
+    if size.width == 0 || size.height == 0 {
+        return;
+    }
+
+    let style = Style::default().fg(app.theme_color());
+    let lines = vec![
+        Line::styled("terminal too small", style),
+        Line::styled(
+            format!("need {}x{}", MIN_TERMINAL_WIDTH, MIN_TERMINAL_HEIGHT),
+            style.add_modifier(Modifier::DIM),
+        ),
+    ];
+    let height = (lines.len() as u16).min(size.height);
+    let area = Rect {
+        x: size.x,
+        y: size.y + (size.height - height) / 2,
+        width: size.width,
+        height,
+    };
+    let paragraph = Paragraph::new(lines)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    frame.render_widget(paragraph, area);
+}
+
 fn inset_h(area: Rect, padding: u16) -> Rect {
     let total = padding.saturating_mul(2);
     if area.width <= total {
@@ -1035,6 +1411,17 @@ fn inset_h(area: Rect, padding: u16) -> Rect {
     }
 }
 
+/// Splits the right column into (worktree detail, env box) heights; a hidden env box gives
+/// its rows to the detail panel.
+fn right_column_heights(total_height: u16, show_env: bool) -> (u16, u16) {
+    let env_height = if show_env {
+        env_box_height(total_height)
+    } else {
+        0
+    };
+    (total_height - env_height, env_height)
+}
+
 fn env_box_height(total_height: u16) -> u16 {
     let min_height: u16 = 5;
     if total_height >= min_height.saturating_add(2) {
@@ -1130,7 +1517,7 @@ fn left_right_spans(
         return vec![Span::styled(left_text, left_style)];
     }
 
-    let right_len = right.chars().count();
+    let right_len = right.width();
     if right_len >= width {
         let right_text = truncate_to_width(right, width);
         return vec![Span::styled(right_text, right_style)];
@@ -1138,7 +1525,7 @@ fn left_right_spans(
 
     let max_left = width.saturating_sub(right_len + 1);
     let left_text = truncate_to_width(left, max_left);
-    let left_len = left_text.chars().count();
+    let left_len = left_text.width();
     let padding = width.saturating_sub(left_len + right_len);
     let spaces = " ".repeat(padding);
 
@@ -1158,15 +1545,18 @@ fn wrap_text(value: &str, max: usize) -> Vec<String> {
     let mut current = String::new();
 
     for word in value.split_whitespace() {
-        let word_len = word.chars().count();
+        let word_len = word.width();
 
         if current.is_empty() {
             if word_len > max {
                 let mut remainder = word.to_string();
-                while remainder.chars().count() > max {
+                while remainder.width() > max {
                     let chunk = truncate_to_width(&remainder, max);
-                    lines.push(chunk.clone());
-                    remainder = remainder.chars().skip(max).collect();
+                    if chunk.is_empty() {
+                        break;
+                    }
+                    remainder = remainder[chunk.len()..].to_string();
+                    lines.push(chunk);
                 }
                 current = remainder;
             } else {
@@ -1175,7 +1565,7 @@ fn wrap_text(value: &str, max: usize) -> Vec<String> {
             continue;
         }
 
-        let candidate_len = current.chars().count() + 1 + word_len;
+        let candidate_len = current.width() + 1 + word_len;
         if candidate_len <= max {
             current.push(' ');
             current.push_str(word);
@@ -1185,10 +1575,13 @@ fn wrap_text(value: &str, max: usize) -> Vec<String> {
         lines.push(current);
         if word_len > max {
             let mut remainder = word.to_string();
-            while remainder.chars().count() > max {
+            while remainder.width() > max {
                 let chunk = truncate_to_width(&remainder, max);
-                lines.push(chunk.clone());
-                remainder = remainder.chars().skip(max).collect();
+                if chunk.is_empty() {
+                    break;
+                }
+                remainder = remainder[chunk.len()..].to_string();
+                lines.push(chunk);
             }
             current = remainder;
         } else {
@@ -1210,14 +1603,14 @@ fn wrap_text(value: &str, max: usize) -> Vec<String> {
 fn label_width(labels: &[&str]) -> usize {
     labels
         .iter()
-        .map(|label| label.chars().count())
+        .map(|label| label.width())
         .max()
         .unwrap_or(0)
         .saturating_add(1)
 }
 
 fn pad_to_width(value: &str, width: usize) -> String {
-    let len = value.chars().count();
+    let len = value.width();
     if len >= width {
         return value.to_string();
     }
@@ -1277,7 +1670,7 @@ fn build_footer_line(theme: &str, normal: Style, dim: Style) -> (Line<'static>,
 }
 
 fn push_span_owned(spans: &mut Vec<Span<'static>>, text: String, style: Style, len: &mut usize) {
-    *len += text.chars().count();
+    *len += text.width();
     spans.push(Span::styled(text, style));
 }
 
@@ -1303,3 +1696,106 @@ fn spinner_frame(started_at: std::time::Instant) -> &'static str {
     let idx = (elapsed / SPINNER_INTERVAL_MS) as usize % SPINNER_FRAMES.len();
     SPINNER_FRAMES[idx]
 }
+
+#[cfg(test)]
+mod tests {
+    use ratatui::layout::Rect;
+
+    use unicode_width::UnicodeWidthStr;
+
+    use super::{
+        churn_text, layout_mode, marked_left, pad_to_width, prompt_cursor_offset,
+        right_column_heights, terminal_too_small, truncate_after_first_slash,
+        truncate_from_start_with_ellipsis, truncate_to_width, wrap_text, LayoutMode,
+    };
+    use bbq::ChangedFile;
+
+    use crate::tui::types::TreeGlyphs;
+
+    #[test]
+    fn churn_sums_added_and_removed_lines() {
+        let file = |path: &str, added, removed| ChangedFile {
+            path: path.to_string(),
+            added,
+            removed,
+        };
+        let glyphs = TreeGlyphs::unicode(2);
+        let churn = |files: &[ChangedFile], total| churn_text(&glyphs, files, total);
+        let files = [file("a.rs", 100, 20), file("b.rs", 20, 10), file("new.txt", 0, 0)];
+        assert_eq!(churn(&files, 3).as_deref(), Some("+120 -30"));
+        assert_eq!(churn(&[file("gone.rs", 0, 4)], 1).as_deref(), Some("+0 -4"));
+        assert_eq!(churn(&[file("bin.png", 0, 0)], 1), None);
+        assert_eq!(churn(&[], 0), None);
+        // Files past the `max_changed_files` cap weren't counted.
+        assert_eq!(churn(&files, 40).as_deref(), Some("≥+120 -30"));
+        let ascii = TreeGlyphs::ascii(2);
+        assert_eq!(churn_text(&ascii, &files, 40).as_deref(), Some(">=+120 -30"));
+    }
+
+    #[test]
+    fn too_small_below_minimum_size() {
+        assert!(terminal_too_small(Rect::new(0, 0, 0, 0)));
+        assert!(terminal_too_small(Rect::new(0, 0, 39, 40)));
+        assert!(terminal_too_small(Rect::new(0, 0, 120, 9)));
+        assert!(terminal_too_small(Rect::new(0, 0, 20, 5)));
+        assert!(!terminal_too_small(Rect::new(0, 0, 40, 10)));
+        assert!(!terminal_too_small(Rect::new(0, 0, 200, 60)));
+    }
+
+    #[test]
+    fn narrow_terminals_use_compact_layout() {
+        assert_eq!(layout_mode(40), LayoutMode::Compact);
+        assert_eq!(layout_mode(79), LayoutMode::Compact);
+        assert_eq!(layout_mode(80), LayoutMode::Split);
+        assert_eq!(layout_mode(200), LayoutMode::Split);
+    }
+
+    #[test]
+    fn tree_glyphs_match_configured_mode() {
+        let unicode = TreeGlyphs::unicode(2);
+        assert_eq!(unicode.arrow(true), "↓");
+        assert_eq!(unicode.arrow(false), "→");
+        assert_eq!(unicode.repo_marker(true, 0), "·");
+        assert_eq!(unicode.repo_marker(true, 3), "↓");
+        assert_eq!(unicode.worktree_prefix(true), "★ ");
+        assert_eq!(marked_left(&unicode, "  lima"), "✓ lima");
+
+        let ascii = TreeGlyphs::ascii(4);
+        assert_eq!(ascii.arrow(true), "v");
+        assert_eq!(ascii.arrow(false), ">");
+        assert_eq!(ascii.repo_marker(false, 0), "-");
+        assert_eq!(ascii.worktree_prefix(true), "*   ");
+        assert_eq!(ascii.worktree_prefix(false), "    ");
+        assert_eq!(marked_left(&ascii, "    lima"), "x   lima");
+        assert_eq!(marked_left(&ascii, "*   lima"), "x   lima");
+    }
+
+    #[test]
+    fn hiding_env_box_gives_detail_full_height() {
+        assert_eq!(right_column_heights(30, true), (25, 5));
+        assert_eq!(right_column_heights(30, false), (30, 0));
+        assert_eq!(right_column_heights(6, true), (6, 0));
+    }
+
+    #[test]
+    fn prompt_cursor_counts_cells_not_bytes() {
+        assert_eq!(prompt_cursor_offset(" Branch: ", "main"), 13);
+        assert_eq!(prompt_cursor_offset(" Branch: ", "café"), 13);
+        assert_eq!(prompt_cursor_offset(" Branch: ", "功能"), 13);
+    }
+
+    #[test]
+    fn truncation_fits_wide_characters_in_cell_width() {
+        let branch = "feature/日本語のブランチ";
+        for max in 1..=branch.width() {
+            assert!(truncate_to_width(branch, max).width() <= max, "max {max}");
+            assert!(truncate_from_start_with_ellipsis(branch, max).width() <= max, "max {max}");
+            assert!(truncate_after_first_slash(branch, max).width() <= max, "max {max}");
+        }
+        assert_eq!(truncate_to_width("日本語", 5), "日本");
+        assert_eq!(truncate_from_start_with_ellipsis("日本語", 5), "…本語");
+        assert_eq!(truncate_after_first_slash(branch, 14), "feature/…ンチ");
+        assert_eq!(pad_to_width("日本", 6), "日本  ");
+        assert!(wrap_text("日本語のブランチ", 5).iter().all(|line| line.width() <= 5));
+    }
+}
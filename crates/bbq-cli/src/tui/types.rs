@@ -1,7 +1,7 @@
-use std::collections::HashMap;
-use std::time::Instant;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
 
-use bbq::{Repo, Worktree};
+use bbq::{ChangedFile, ConflictState, Repo, Worktree};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum StatusTone {
@@ -16,6 +16,63 @@ pub(crate) struct StatusMessage {
     pub(crate) deadline: Instant,
 }
 
+impl StatusMessage {
+    /// Whole seconds left before the message auto-dismisses, rounded up so `1s` shows until
+    /// the very end.
+    pub(crate) fn remaining_secs(&self, now: Instant) -> u64 {
+        let remaining = self.deadline.saturating_duration_since(now);
+        remaining.as_millis().div_ceil(1000) as u64
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ActivityEntry {
+    pub(crate) text: String,
+    pub(crate) at: Instant,
+}
+
+/// Recently completed actions, oldest first, capped at `max` entries.
+#[derive(Debug, Clone)]
+pub(crate) struct ActivityLog {
+    entries: VecDeque<ActivityEntry>,
+    max: usize,
+    ttl: Duration,
+}
+
+impl ActivityLog {
+    pub(crate) fn new(max: usize, ttl: Duration) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(max),
+            max,
+            ttl,
+        }
+    }
+
+    pub(crate) fn push(&mut self, text: impl Into<String>) {
+        self.entries.push_back(ActivityEntry {
+            text: text.into(),
+            at: Instant::now(),
+        });
+        while self.entries.len() > self.max {
+            self.entries.pop_front();
+        }
+    }
+
+    pub(crate) fn prune(&mut self, now: Instant) {
+        let ttl = self.ttl;
+        self.entries
+            .retain(|entry| now.saturating_duration_since(entry.at) < ttl);
+    }
+
+    pub(crate) fn entries(&self) -> impl Iterator<Item = &ActivityEntry> {
+        self.entries.iter()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum LoadingPriority {
     Background,
@@ -36,6 +93,7 @@ pub(crate) enum LoadingGroup {
     EnvInfo,
     Repos,
     Worktrees,
+    Fetch,
     Action,
 }
 
@@ -60,6 +118,11 @@ pub(crate) enum InputKind {
     CheckoutRepo,
     CreateWorktreeName { repo: Repo },
     CreateWorktreeSource { repo: Repo, name: String },
+    CreateWorktreeTicket {
+        repo: Repo,
+        name: String,
+        source_branch: String,
+    },
     CreateWorktreeBranch {
         repo: Repo,
         name: String,
@@ -68,6 +131,8 @@ pub(crate) enum InputKind {
     DeleteRepo { name: String },
     DeleteWorktree { repo: Repo, name: String },
     DeleteWorktreeForce { repo: Repo, name: String },
+    RenameBranch { repo: Repo, worktree: Worktree },
+    EditLabels { repo: String, name: String },
 }
 
 #[derive(Debug, Clone)]
@@ -79,13 +144,19 @@ pub(crate) struct WorktreeEntry {
     pub(crate) sync_status: String,
     pub(crate) worktree_path: String,
     pub(crate) changed_files: Vec<ChangedFile>,
+    /// Number of changed files, including any past the `max_changed_files` cap.
+    pub(crate) changed_total: usize,
+    pub(crate) conflict_state: Option<ConflictState>,
 }
 
 #[derive(Debug, Clone)]
-pub(crate) struct ChangedFile {
-    pub(crate) path: String,
-    pub(crate) added: u32,
-    pub(crate) removed: u32,
+pub(crate) struct DeletedWorktree {
+    pub(crate) repo: Repo,
+    pub(crate) name: String,
+    pub(crate) branch: String,
+    /// Whether it was a favorite, and its labels, to put back if the delete is undone.
+    pub(crate) favorite: bool,
+    pub(crate) labels: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -95,7 +166,10 @@ pub(crate) enum TreeItemKind {
         expanded: bool,
         worktree_count: usize,
     },
-    Worktree { repo: String, entry: WorktreeEntry },
+    Worktree {
+        repo: String,
+        entry: Box<WorktreeEntry>,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -105,7 +179,72 @@ pub(crate) struct TreeItem {
     pub(crate) kind: TreeItemKind,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Glyphs and indentation used to draw the repo/worktree tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TreeGlyphs {
+    pub(crate) expanded: &'static str,
+    pub(crate) collapsed: &'static str,
+    /// Shown instead of an arrow for repos with no worktrees, which have nothing to expand.
+    pub(crate) empty: &'static str,
+    pub(crate) favorite: &'static str,
+    pub(crate) mark: &'static str,
+    /// Prefixes a churn total that is only a lower bound.
+    pub(crate) at_least: &'static str,
+    pub(crate) indent: usize,
+}
+
+impl TreeGlyphs {
+    pub(crate) fn unicode(indent: usize) -> Self {
+        Self {
+            expanded: "↓",
+            collapsed: "→",
+            empty: "·",
+            favorite: "★",
+            mark: "✓",
+            at_least: "≥",
+            indent,
+        }
+    }
+
+    pub(crate) fn ascii(indent: usize) -> Self {
+        Self {
+            expanded: "v",
+            collapsed: ">",
+            empty: "-",
+            favorite: "*",
+            mark: "x",
+            at_least: ">=",
+            indent,
+        }
+    }
+
+    pub(crate) fn arrow(&self, expanded: bool) -> &'static str {
+        if expanded {
+            self.expanded
+        } else {
+            self.collapsed
+        }
+    }
+
+    pub(crate) fn repo_marker(&self, expanded: bool, worktree_count: usize) -> &'static str {
+        if worktree_count == 0 {
+            self.empty
+        } else {
+            self.arrow(expanded)
+        }
+    }
+
+    /// Leading text for a worktree row; favorites keep their marker even with a small indent.
+    pub(crate) fn worktree_prefix(&self, favorite: bool) -> String {
+        if favorite {
+            format!("{:<width$}", format!("{} ", self.favorite), width = self.indent)
+        } else {
+            " ".repeat(self.indent)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) enum TreeKey {
     Repo(String),
     Worktree { repo: String, name: String },
@@ -141,6 +280,19 @@ pub(crate) enum WorkerRequest {
     },
     DeleteRepo { name: String },
     DeleteWorktree { repo: Repo, name: String, force: bool },
+    RestoreWorktree {
+        repo: Repo,
+        name: String,
+        branch: String,
+    },
+    RenameBranch {
+        repo: Repo,
+        worktree: Worktree,
+        branch: String,
+    },
+    FetchAll,
+    ListBranches { repo: Repo },
+    CopyToClipboard { text: String },
 }
 
 #[derive(Debug)]
@@ -181,6 +333,68 @@ pub(crate) enum WorkerEvent {
         worktree_name: String,
         result: Result<(), String>,
     },
+    RestoreWorktreeResult {
+        repo_name: String,
+        result: Result<Worktree, String>,
+    },
+    RenameBranchResult {
+        repo_name: String,
+        worktree_name: String,
+        branch: String,
+        result: Result<(), String>,
+    },
+    FetchAllResult {
+        fetched: usize,
+        result: Result<(), String>,
+    },
+    BranchesLoaded {
+        repo_name: String,
+        result: Result<Vec<String>, String>,
+    },
+    ClipboardWritten {
+        text: String,
+        result: Result<(), String>,
+    },
+}
+
+/// Branch suggestions shown above the source branch prompt, filtered by what's typed.
+/// `selected` stays `None` until the user moves through the list, so typed text wins.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BranchPicker {
+    pub(crate) repo_name: String,
+    pub(crate) branches: Vec<String>,
+    pub(crate) selected: Option<usize>,
+}
+
+impl BranchPicker {
+    pub(crate) fn new(repo_name: &str) -> Self {
+        Self {
+            repo_name: repo_name.to_string(),
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn matches(&self, query: &str) -> Vec<&str> {
+        let query = query.trim().to_lowercase();
+        self.branches
+            .iter()
+            .map(String::as_str)
+            .filter(|branch| branch.to_lowercase().contains(&query))
+            .collect()
+    }
+
+    pub(crate) fn move_selection(&mut self, delta: i32, count: usize) {
+        if count == 0 {
+            self.selected = None;
+            return;
+        }
+        let next = match self.selected {
+            None if delta < 0 => count - 1,
+            None => 0,
+            Some(index) => (index as i32 + delta).rem_euclid(count as i32) as usize,
+        };
+        self.selected = Some(next);
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -196,12 +410,15 @@ impl InputState {
             InputKind::CheckoutRepo => "clone from > ".to_string(),
             InputKind::CreateWorktreeSource { .. } => "source branch > ".to_string(),
             InputKind::CreateWorktreeName { .. } => "worktree name > ".to_string(),
+            InputKind::CreateWorktreeTicket { .. } => "ticket > ".to_string(),
             InputKind::CreateWorktreeBranch { .. } => "new branch > ".to_string(),
             InputKind::DeleteRepo { name } => format!("delete {} repo? > ", name),
             InputKind::DeleteWorktree { name, .. } => format!("delete {} worktree? > ", name),
             InputKind::DeleteWorktreeForce { name, .. } => {
                 format!("delete {} worktree and discard changes? > ", name)
             }
+            InputKind::RenameBranch { .. } => "rename branch > ".to_string(),
+            InputKind::EditLabels { name, .. } => format!("labels for {} > ", name),
         }
     }
 
@@ -210,11 +427,14 @@ impl InputState {
             InputKind::CheckoutRepo => "git url or github user/repo",
             InputKind::CreateWorktreeSource { .. } => "source branch",
             InputKind::CreateWorktreeName { .. } => "worktree name",
+            InputKind::CreateWorktreeTicket { .. } => "ticket key like ABC-123, empty to skip",
             InputKind::CreateWorktreeBranch { .. } => "branch name",
             InputKind::DeleteRepo { .. } | InputKind::DeleteWorktree { .. } => {
                 "type 'yes' to confirm"
             }
             InputKind::DeleteWorktreeForce { .. } => "type 'discard' to confirm",
+            InputKind::RenameBranch { .. } => "new branch name",
+            InputKind::EditLabels { .. } => "space-separated labels, empty to clear",
         }
     }
 }
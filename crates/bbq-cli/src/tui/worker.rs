@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
 use std::sync::mpsc;
 use std::thread;
 use std::time::{Duration, Instant, SystemTime};
@@ -10,15 +11,24 @@ use std::time::{Duration, Instant, SystemTime};
 use notify::{RecursiveMode, Watcher};
 
 use bbq::{
-    checkout_repo, create_worktree_from, list_repos, list_worktrees, remove_repo,
-    remove_worktree_with_force, run_post_create_script, run_pre_delete_script,
-    find_post_create_script, find_pre_delete_script, Repo, ScriptOutput,
+    branch_exists, checkout_repo, copy_worktree_templates, create_worktree_from, fetch_all,
+    find_post_create_script, find_pre_delete_script, find_repo_by_remote, find_worktree,
+    git_changed_files, head_commit_info, head_divergence, list_branches, list_repos,
+    list_worktrees, remove_repo, remove_worktree_with_force, rename_branch, run_post_create_script,
+    run_pre_delete_script, worktree_conflict_state, worktree_upstream_ref, Repo, ScriptOutput,
+    UpstreamRef, Worktree,
 };
 use bbq::paths;
 
+use crate::clipboard::write_clipboard;
+use crate::env_info::{bbq_root_display, command_version, display_path, home_dir_path};
+use crate::config::{
+    apply_repo_git_config, expand_paths_enabled, load_auto_fetch_interval, load_max_changed_files,
+};
 use crate::update;
 
-use super::types::{AllData, ChangedFile, WorktreeEntry, WorkerEvent, WorkerRequest};
+use super::constants::AUTO_FETCH_TICK_MS;
+use super::types::{AllData, WorktreeEntry, WorkerEvent, WorkerRequest};
 
 pub(crate) fn start_background_tasks(
 ) -> (mpsc::Sender<WorkerRequest>, mpsc::Receiver<WorkerEvent>) {
@@ -26,6 +36,9 @@ pub(crate) fn start_background_tasks(
     let (event_tx, event_rx) = mpsc::channel();
     spawn_worker(request_rx, event_tx.clone());
     spawn_filesystem_watcher(event_tx.clone());
+    if let Some(interval) = load_auto_fetch_interval().filter(|_| !bbq::offline_enabled()) {
+        spawn_auto_fetch(event_tx, interval);
+    }
     (request_tx, event_rx)
 }
 
@@ -34,11 +47,7 @@ fn spawn_worker(request_rx: mpsc::Receiver<WorkerRequest>, event_tx: mpsc::Sende
         for request in request_rx {
             match request {
                 WorkerRequest::LoadEnvInfo => {
-                    let home_dir = (|| {
-                        let bbq_root = paths::bbq_root().ok()?;
-                        let user_home = paths::config_root().ok()?.parent()?.to_path_buf();
-                        Some(display_path_with_tilde(&bbq_root, &user_home))
-                    })();
+                    let home_dir = bbq_root_display();
                     let git_version = command_version("git", &["--version"]);
                     let gh_version = command_version("gh", &["--version"]);
                     let _ = event_tx.send(WorkerEvent::EnvInfoLoaded {
@@ -60,7 +69,7 @@ fn spawn_worker(request_rx: mpsc::Receiver<WorkerRequest>, event_tx: mpsc::Sende
                     let _ = event_tx.send(WorkerEvent::AllDataLoaded { request_id, result });
                 }
                 WorkerRequest::CheckoutRepo { url } => {
-                    let result = checkout_repo(&url).map_err(|err| err.to_string());
+                    let result = clone_new_repo(&url);
                     let _ = event_tx.send(WorkerEvent::CheckoutRepoResult { result });
                 }
                 WorkerRequest::CreateWorktree {
@@ -70,27 +79,8 @@ fn spawn_worker(request_rx: mpsc::Receiver<WorkerRequest>, event_tx: mpsc::Sende
                     source_branch,
                 } => {
                     let repo_name = repo.name.clone();
-                    let result = match create_worktree_from(&repo, &name, &branch, &source_branch) {
-                        Ok(worktree) => {
-                            if let Some(script_path) = find_post_create_script(&worktree) {
-                                let display_path = display_script_path(&script_path);
-                                let _ = event_tx.send(WorkerEvent::WorktreeScriptStarted {
-                                    kind: "post-create".to_string(),
-                                    path: display_path,
-                                });
-                                if let Err(err) =
-                                    run_post_create_script(&worktree, ScriptOutput::Capture)
-                                {
-                                    Err(err.to_string())
-                                } else {
-                                    Ok(worktree)
-                                }
-                            } else {
-                                Ok(worktree)
-                            }
-                        }
-                        Err(err) => Err(err.to_string()),
-                    };
+                    let result =
+                        create_worktree_with_script(&event_tx, &repo, &name, &branch, &source_branch);
                     let _ = event_tx.send(WorkerEvent::CreateWorktreeResult { repo_name, result });
                 }
                 WorkerRequest::DeleteRepo { name } => {
@@ -100,7 +90,7 @@ fn spawn_worker(request_rx: mpsc::Receiver<WorkerRequest>, event_tx: mpsc::Sende
                 WorkerRequest::DeleteWorktree { repo, name, force } => {
                     let repo_name = repo.name.clone();
                     let worktree_name = name.clone();
-                    let result = match find_worktree_for_delete(&repo, &name) {
+                    let result = match find_worktree(&repo, &name) {
                         Ok(worktree) => {
                             if let Some(script_path) = find_pre_delete_script(&worktree) {
                                 let display_path = display_script_path(&script_path);
@@ -129,11 +119,102 @@ fn spawn_worker(request_rx: mpsc::Receiver<WorkerRequest>, event_tx: mpsc::Sende
                         result,
                     });
                 }
+                WorkerRequest::RestoreWorktree { repo, name, branch } => {
+                    let repo_name = repo.name.clone();
+                    let result = restore_worktree(&event_tx, &repo, &name, &branch);
+                    let _ = event_tx.send(WorkerEvent::RestoreWorktreeResult { repo_name, result });
+                }
+                WorkerRequest::RenameBranch {
+                    repo,
+                    worktree,
+                    branch,
+                } => {
+                    let result = rename_branch(&worktree, &branch).map_err(|err| err.to_string());
+                    let _ = event_tx.send(WorkerEvent::RenameBranchResult {
+                        repo_name: repo.name,
+                        worktree_name: worktree.display_name(),
+                        branch,
+                        result,
+                    });
+                }
+                WorkerRequest::FetchAll => {
+                    let event = match list_repos() {
+                        Ok(repos) => fetch_repos(&repos, fetch_all).unwrap_or_else(|| {
+                            WorkerEvent::FetchAllResult {
+                                fetched: 0,
+                                result: Err("A fetch is already running".to_string()),
+                            }
+                        }),
+                        Err(err) => WorkerEvent::FetchAllResult {
+                            fetched: 0,
+                            result: Err(err.to_string()),
+                        },
+                    };
+                    let _ = event_tx.send(event);
+                }
+                WorkerRequest::ListBranches { repo } => {
+                    let result = list_branches(&repo).map_err(|err| err.to_string());
+                    let _ = event_tx.send(WorkerEvent::BranchesLoaded {
+                        repo_name: repo.name,
+                        result,
+                    });
+                }
+                WorkerRequest::CopyToClipboard { text } => {
+                    let result = write_clipboard(&text).map_err(|err| err.to_string());
+                    let _ = event_tx.send(WorkerEvent::ClipboardWritten { text, result });
+                }
             }
         }
     });
 }
 
+/// Clones `url` unless an existing repo already points at it. Unlike `bbq clone`, the TUI has
+/// no `--force` to clone it a second time.
+fn clone_new_repo(url: &str) -> Result<Repo, String> {
+    if let Some(existing) = find_repo_by_remote(url).map_err(|err| err.to_string())? {
+        return Err(format!("{} is already cloned as {}", url.trim(), existing.name));
+    }
+    checkout_repo(url).map_err(|err| err.to_string())
+}
+
+fn create_worktree_with_script(
+    event_tx: &mpsc::Sender<WorkerEvent>,
+    repo: &Repo,
+    name: &str,
+    branch: &str,
+    source_branch: &str,
+) -> Result<Worktree, String> {
+    let worktree =
+        create_worktree_from(repo, name, branch, source_branch).map_err(|err| err.to_string())?;
+    copy_worktree_templates(repo, &worktree).map_err(|err| err.to_string())?;
+    apply_repo_git_config(&worktree).map_err(|err| err.to_string())?;
+    if let Some(script_path) = find_post_create_script(&worktree) {
+        let display_path = display_script_path(&script_path);
+        let _ = event_tx.send(WorkerEvent::WorktreeScriptStarted {
+            kind: "post-create".to_string(),
+            path: display_path,
+        });
+        run_post_create_script(&worktree, ScriptOutput::Capture).map_err(|err| err.to_string())?;
+    }
+    Ok(worktree)
+}
+
+fn restore_worktree(
+    event_tx: &mpsc::Sender<WorkerEvent>,
+    repo: &Repo,
+    name: &str,
+    branch: &str,
+) -> Result<Worktree, String> {
+    match branch_exists(repo, branch) {
+        Ok(true) => create_worktree_with_script(event_tx, repo, name, branch, branch),
+        Ok(false) => Err(format!(
+            "Cannot restore {}/{}: branch {} no longer exists",
+            repo.name, name, branch
+        )),
+        Err(err) => Err(format!("Cannot restore {}/{}: {}", repo.name, name, err)),
+    }
+}
+
 fn spawn_filesystem_watcher(event_tx: mpsc::Sender<WorkerEvent>) {
     thread::spawn(move || {
         let _ = paths::ensure_root_dirs();
@@ -161,6 +242,7 @@ fn spawn_filesystem_watcher(event_tx: mpsc::Sender<WorkerEvent>) {
             return;
         }
         let _ = watcher.watch(&worktrees_root, RecursiveMode::NonRecursive);
+        let roots = watch_roots(repos_root, worktrees_root);
 
         let mut last_event = Instant::now() - Duration::from_secs(5);
         let debounce = Duration::from_millis(250);
@@ -169,7 +251,7 @@ fn spawn_filesystem_watcher(event_tx: mpsc::Sender<WorkerEvent>) {
                 Ok(event) => event,
                 Err(_) => continue,
             };
-            if !is_relevant_fs_event(&event, &repos_root, &worktrees_root) {
+            if !is_relevant_fs_event(&event, &roots) {
                 continue;
             }
             let now = Instant::now();
@@ -182,11 +264,120 @@ fn spawn_filesystem_watcher(event_tx: mpsc::Sender<WorkerEvent>) {
     });
 }
 
-fn is_relevant_fs_event(event: &notify::Event, repos_root: &Path, worktrees_root: &Path) -> bool {
-    event
-        .paths
-        .iter()
-        .any(|path| is_relevant_path(path, repos_root, worktrees_root))
+static FETCH_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
+
+struct FetchSchedule {
+    interval: Duration,
+    last_fetch: Instant,
+}
+
+impl FetchSchedule {
+    fn new(interval: Duration, now: Instant) -> Self {
+        Self {
+            interval,
+            last_fetch: now,
+        }
+    }
+
+    fn is_due(&self, now: Instant) -> bool {
+        now.duration_since(self.last_fetch) >= self.interval
+    }
+
+    fn mark_fetched(&mut self, now: Instant) {
+        self.last_fetch = now;
+    }
+}
+
+/// Runs `operation` unless another one is already running, as tracked by `in_progress`.
+fn with_fetch_guard(in_progress: &AtomicBool, operation: impl FnOnce()) -> bool {
+    if in_progress.swap(true, AtomicOrdering::SeqCst) {
+        return false;
+    }
+    let _guard = FetchGuard(in_progress);
+    operation();
+    true
+}
+
+/// Clears the in-progress flag on drop, so a fetch that panics doesn't block all later ones.
+struct FetchGuard<'a>(&'a AtomicBool);
+
+impl Drop for FetchGuard<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, AtomicOrdering::SeqCst);
+    }
+}
+
+/// Fetches each repo with `fetch`, returning `None` if another fetch is already running.
+fn fetch_repos(
+    repos: &[Repo],
+    fetch: impl Fn(&Repo) -> bbq::Result<()>,
+) -> Option<WorkerEvent> {
+    let mut errors = Vec::new();
+    let ran = with_fetch_guard(&FETCH_IN_PROGRESS, || {
+        for repo in repos {
+            if let Err(err) = fetch(repo) {
+                errors.push(format!("{}: {}", repo.name, err));
+            }
+        }
+    });
+    if !ran {
+        return None;
+    }
+
+    let result = if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Fetch failed for {}", errors.join("; ")))
+    };
+    Some(WorkerEvent::FetchAllResult {
+        fetched: repos.len() - errors.len(),
+        result,
+    })
+}
+
+fn spawn_auto_fetch(event_tx: mpsc::Sender<WorkerEvent>, interval: Duration) {
+    thread::spawn(move || {
+        let mut schedule = FetchSchedule::new(interval, Instant::now());
+        loop {
+            thread::sleep(Duration::from_millis(AUTO_FETCH_TICK_MS));
+            if !schedule.is_due(Instant::now()) {
+                continue;
+            }
+
+            let repos = list_repos().unwrap_or_default();
+            if fetch_repos(&repos, fetch_all).is_none() {
+                continue;
+            }
+            schedule.mark_fetched(Instant::now());
+            // The UI dropping its receiver is the signal to stop.
+            if event_tx.send(WorkerEvent::FsChanged).is_err() {
+                return;
+            }
+        }
+    });
+}
+
+/// The `(repos_root, worktrees_root)` pairs events are matched against. When the roots sit
+/// behind a symlink, the canonical pair is included too, since some platforms report events
+/// using the resolved path.
+fn watch_roots(repos_root: PathBuf, worktrees_root: PathBuf) -> Vec<(PathBuf, PathBuf)> {
+    let canonical = (
+        fs::canonicalize(&repos_root).unwrap_or_else(|_| repos_root.clone()),
+        fs::canonicalize(&worktrees_root).unwrap_or_else(|_| worktrees_root.clone()),
+    );
+    let mut roots = vec![(repos_root, worktrees_root)];
+    if canonical != roots[0] {
+        roots.push(canonical);
+    }
+    roots
+}
+
+fn is_relevant_fs_event(event: &notify::Event, roots: &[(PathBuf, PathBuf)]) -> bool {
+    event.paths.iter().any(|path| {
+        roots
+            .iter()
+            .any(|(repos_root, worktrees_root)| is_relevant_path(path, repos_root, worktrees_root))
+    })
 }
 
 fn is_relevant_path(path: &Path, repos_root: &Path, worktrees_root: &Path) -> bool {
@@ -217,14 +408,13 @@ fn is_relevant_path(path: &Path, repos_root: &Path, worktrees_root: &Path) -> bo
     };
 
     let next = next.to_string_lossy();
-    match next.as_ref() {
-        "refs" | "HEAD" | "packed-refs" => true,
-        _ => false,
-    }
+    matches!(next.as_ref(), "refs" | "HEAD" | "packed-refs")
 }
 
 fn build_worktree_entries(repo: &Repo) -> bbq::Result<Vec<WorktreeEntry>> {
     let home_dir = home_dir_path();
+    let expand_paths = expand_paths_enabled();
+    let max_changed_files = load_max_changed_files();
     let worktrees = list_worktrees(repo)?;
     let mut entries: Vec<WorktreeEntry> = worktrees
         .into_iter()
@@ -241,11 +431,10 @@ fn build_worktree_entries(repo: &Repo) -> bbq::Result<Vec<WorktreeEntry>> {
                 }
                 None => "no upstream".to_string(),
             };
-            let worktree_path = match home_dir.as_ref() {
-                Some(home) => display_path_with_tilde(&worktree.path, home),
-                None => worktree.path.display().to_string(),
-            };
-            let changed_files = git_changed_files(&worktree.path);
+            let worktree_path = display_path(&worktree.path, home_dir.as_deref(), expand_paths);
+            let (changed_files, changed_total) =
+                git_changed_files(&worktree.path, max_changed_files);
+            let conflict_state = worktree_conflict_state(&worktree.path);
             WorktreeEntry {
                 worktree,
                 head_author,
@@ -254,6 +443,8 @@ fn build_worktree_entries(repo: &Repo) -> bbq::Result<Vec<WorktreeEntry>> {
                 sync_status,
                 worktree_path,
                 changed_files,
+                changed_total,
+                conflict_state,
             }
         })
         .collect();
@@ -266,26 +457,11 @@ fn build_worktree_entries(repo: &Repo) -> bbq::Result<Vec<WorktreeEntry>> {
     Ok(entries)
 }
 
-fn find_worktree_for_delete(repo: &Repo, name: &str) -> bbq::Result<bbq::Worktree> {
-    let worktrees = list_worktrees(repo)?;
-    worktrees
-        .into_iter()
-        .find(|item| {
-            item.display_name() == name
-                || item
-                    .branch
-                    .as_deref()
-                    .map(|branch| branch == name)
-                    .unwrap_or(false)
-        })
-        .ok_or_else(|| bbq::BbqError::WorktreeNotFound(name.to_string()))
-}
-
 fn load_all_data() -> bbq::Result<AllData> {
     let mut repos = list_repos()?;
     repos.sort_by(|a, b| compare_path_time(&a.path, &b.path).then_with(|| a.name.cmp(&b.name)));
     let mut repo_worktrees = HashMap::new();
-    let gh_available = command_version("gh", &["--version"]).is_some();
+    let gh_available = !bbq::offline_enabled() && command_version("gh", &["--version"]).is_some();
     let mut repo_display = HashMap::new();
     let mut error = None;
 
@@ -314,156 +490,6 @@ fn load_all_data() -> bbq::Result<AllData> {
     })
 }
 
-struct CommitInfo {
-    author: String,
-    message: String,
-}
-
-struct UpstreamRef {
-    rev: String,
-    display: String,
-}
-
-fn head_commit_info(path: &Path) -> Option<CommitInfo> {
-    let output = Command::new("git")
-        .arg("-C")
-        .arg(path)
-        .args(["log", "-1", "--format=%an%n%s"])
-        .output()
-        .ok()?;
-    if !output.status.success() {
-        return None;
-    }
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let mut lines = stdout.lines();
-    let author = lines.next().unwrap_or("").trim().to_string();
-    let message = lines.next().unwrap_or("").trim().to_string();
-    if author.is_empty() && message.is_empty() {
-        return None;
-    }
-
-    Some(CommitInfo { author, message })
-}
-
-fn worktree_upstream_ref(path: &Path) -> Option<UpstreamRef> {
-    let output = Command::new("git")
-        .arg("-C")
-        .arg(path)
-        .args(["rev-parse", "--abbrev-ref", "--symbolic-full-name", "@{u}"])
-        .output()
-        .ok()?;
-    if !output.status.success() {
-        return upstream_from_config(path);
-    }
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let value = stdout.lines().next()?.trim();
-    if value.is_empty() {
-        return None;
-    }
-
-    let display = display_ref_name(value);
-    let display = strip_origin_prefix(&display);
-    Some(UpstreamRef {
-        rev: value.to_string(),
-        display,
-    })
-}
-
-fn upstream_from_config(path: &Path) -> Option<UpstreamRef> {
-    let branch = current_branch_name(path)?;
-    let remote = git_config_value(path, &format!("branch.{branch}.remote"))?;
-    let merge = git_config_value(path, &format!("branch.{branch}.merge"))?;
-    let merge_branch = merge.strip_prefix("refs/heads/").unwrap_or(&merge);
-    if merge_branch.is_empty() {
-        return None;
-    }
-    let display = display_upstream_branch(&remote, merge_branch);
-    let rev = format!("refs/remotes/{remote}/{merge_branch}");
-    Some(UpstreamRef { rev, display })
-}
-
-fn current_branch_name(path: &Path) -> Option<String> {
-    let output = Command::new("git")
-        .arg("-C")
-        .arg(path)
-        .args(["symbolic-ref", "--short", "HEAD"])
-        .output()
-        .ok()?;
-    if !output.status.success() {
-        return None;
-    }
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let value = stdout.lines().next()?.trim();
-    if value.is_empty() {
-        None
-    } else {
-        Some(value.to_string())
-    }
-}
-
-fn git_config_value(path: &Path, key: &str) -> Option<String> {
-    let output = Command::new("git")
-        .arg("-C")
-        .arg(path)
-        .args(["config", "--get", key])
-        .output()
-        .ok()?;
-    if !output.status.success() {
-        return None;
-    }
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let value = stdout.lines().next()?.trim();
-    if value.is_empty() {
-        None
-    } else {
-        Some(value.to_string())
-    }
-}
-
-fn display_ref_name(reference: &str) -> String {
-    reference
-        .strip_prefix("refs/remotes/")
-        .or_else(|| reference.strip_prefix("refs/heads/"))
-        .unwrap_or(reference)
-        .to_string()
-}
-
-fn strip_origin_prefix(reference: &str) -> String {
-    reference
-        .strip_prefix("origin/")
-        .unwrap_or(reference)
-        .to_string()
-}
-
-fn display_upstream_branch(remote: &str, branch: &str) -> String {
-    if remote == "origin" {
-        branch.to_string()
-    } else {
-        format!("{remote}/{branch}")
-    }
-}
-
-fn head_divergence(path: &Path, upstream_ref: &str) -> Option<(u32, u32)> {
-    let range = format!("HEAD...{upstream_ref}");
-    let output = Command::new("git")
-        .arg("-C")
-        .arg(path)
-        .args(["rev-list", "--left-right", "--count", &range])
-        .output()
-        .ok()?;
-    if !output.status.success() {
-        return None;
-    }
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let mut parts = stdout.split_whitespace();
-    let ahead = parts.next()?.parse().ok()?;
-    let behind = parts.next()?.parse().ok()?;
-    Some((ahead, behind))
-}
-
 fn repo_github_name(repo: &Repo) -> Option<String> {
     let url = git_remote_url(repo, "origin")?;
     parse_github_name(&url)
@@ -505,10 +531,8 @@ fn parse_github_name(url: &str) -> Option<String> {
         rest
     } else if let Some(rest) = trimmed.strip_prefix("http://www.github.com/") {
         rest
-    } else if let Some(rest) = trimmed.strip_prefix("git://github.com/") {
-        rest
     } else {
-        return None;
+        trimmed.strip_prefix("git://github.com/")?
     };
 
     let mut parts = rest.split('/');
@@ -552,107 +576,6 @@ fn format_sync_status(upstream: &UpstreamRef, divergence: Option<(u32, u32)>) ->
     }
 }
 
-fn home_dir_path() -> Option<PathBuf> {
-    let bbq_home = paths::config_root().ok()?;
-    bbq_home.parent().map(|parent| parent.to_path_buf())
-}
-
-fn git_changed_files(path: &Path) -> Vec<ChangedFile> {
-    let mut diff_stats = git_diff_numstat(path);
-    let output = Command::new("git")
-        .arg("-C")
-        .arg(path)
-        .args(["status", "--porcelain"])
-        .output();
-    let output = match output {
-        Ok(output) if output.status.success() => output,
-        _ => return Vec::new(),
-    };
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let mut files = Vec::new();
-    for line in stdout.lines() {
-        if line.len() < 3 {
-            continue;
-        }
-        let status = &line[..2];
-        let path_part = line.get(3..).unwrap_or("").trim();
-        if path_part.is_empty() {
-            continue;
-        }
-        let file = if let Some((_, new)) = path_part.split_once("->") {
-            new.trim().to_string()
-        } else {
-            path_part.to_string()
-        };
-        if file.is_empty() {
-            continue;
-        }
-        let (added, removed) = diff_stats.remove(&file).unwrap_or_else(|| {
-            if status == "??" {
-                (count_file_lines(path, &file), 0)
-            } else {
-                (0, 0)
-            }
-        });
-        files.push(ChangedFile {
-            path: file,
-            added,
-            removed,
-        });
-    }
-    files
-}
-
-fn git_diff_numstat(path: &Path) -> HashMap<String, (u32, u32)> {
-    let output = Command::new("git")
-        .arg("-C")
-        .arg(path)
-        .args(["diff", "--numstat", "HEAD"])
-        .output();
-    let output = match output {
-        Ok(output) if output.status.success() => output,
-        _ => return HashMap::new(),
-    };
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let mut stats = HashMap::new();
-    for line in stdout.lines() {
-        let mut parts = line.split('\t');
-        let added_raw = parts.next().unwrap_or("");
-        let removed_raw = parts.next().unwrap_or("");
-        let path_raw = parts.next().unwrap_or("").trim();
-        if path_raw.is_empty() {
-            continue;
-        }
-        let added = added_raw.parse::<u32>().unwrap_or(0);
-        let removed = removed_raw.parse::<u32>().unwrap_or(0);
-        let file = if let Some((_, new)) = path_raw.split_once("->") {
-            new.trim().to_string()
-        } else {
-            path_raw.to_string()
-        };
-        if !file.is_empty() {
-            stats.insert(file, (added, removed));
-        }
-    }
-    stats
-}
-
-fn count_file_lines(repo_path: &Path, file: &str) -> u32 {
-    let path = repo_path.join(file);
-    let content = fs::read_to_string(path);
-    let content = match content {
-        Ok(content) => content,
-        Err(_) => return 0,
-    };
-    let mut lines = content.lines().count() as u32;
-    if !content.is_empty() && !content.ends_with('\n') {
-        lines += 1;
-    }
-    lines
-}
-
 fn path_timestamp(path: &Path) -> Option<SystemTime> {
     let metadata = fs::metadata(path).ok()?;
     metadata.created().or_else(|_| metadata.modified()).ok()
@@ -667,56 +590,106 @@ fn compare_path_time(a: &Path, b: &Path) -> Ordering {
     }
 }
 
-fn display_path_with_tilde(path: &Path, home: &Path) -> String {
-    if path == home {
-        return "~".to_string();
-    }
-    if let Ok(suffix) = path.strip_prefix(home) {
-        let suffix = suffix.to_string_lossy();
-        if suffix.is_empty() {
-            "~".to_string()
-        } else {
-            format!("~/{}", suffix)
+fn display_script_path(path: &Path) -> String {
+    display_path(path, home_dir_path().as_deref(), expand_paths_enabled())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::panic;
+    use std::sync::atomic::AtomicBool;
+    use std::time::{Duration, Instant};
+
+    use std::cell::RefCell;
+    use std::path::PathBuf;
+
+    use bbq::Repo;
+
+    use super::{
+        fetch_repos, is_relevant_fs_event, watch_roots, with_fetch_guard, FetchSchedule,
+    };
+    use crate::tui::types::WorkerEvent;
+
+    #[test]
+    fn fetch_repos_fetches_each_repo_and_reports_completion() {
+        let repos: Vec<Repo> = ["api", "web", "docs"]
+            .iter()
+            .map(|name| Repo {
+                name: name.to_string(),
+                path: PathBuf::from(format!("/repos/{name}.git")),
+            })
+            .collect();
+        let fetched = RefCell::new(Vec::new());
+
+        let event = fetch_repos(&repos, |repo| {
+            fetched.borrow_mut().push(repo.name.clone());
+            if repo.name == "docs" {
+                return Err(bbq::BbqError::RepoNotFound(repo.name.clone()));
+            }
+            Ok(())
+        })
+        .expect("fetch ran");
+
+        assert_eq!(fetched.into_inner(), vec!["api", "web", "docs"]);
+        match event {
+            WorkerEvent::FetchAllResult { fetched, result } => {
+                assert_eq!(fetched, 2);
+                let err = result.expect_err("docs failure reported");
+                assert!(err.contains("docs"), "unexpected error: {err}");
+            }
+            other => panic!("unexpected event: {other:?}"),
         }
-    } else {
-        path.display().to_string()
     }
-}
 
-fn display_script_path(path: &Path) -> String {
-    match home_dir_path() {
-        Some(home) => display_path_with_tilde(path, &home),
-        None => path.display().to_string(),
+    #[test]
+    fn fetch_guard_is_released_after_a_panic() {
+        let in_progress = AtomicBool::new(false);
+        let panicked = panic::catch_unwind(|| {
+            with_fetch_guard(&in_progress, || panic!("fetch blew up"));
+        });
+        assert!(panicked.is_err());
+        assert!(with_fetch_guard(&in_progress, || {}));
+
+        let ran = with_fetch_guard(&in_progress, || {
+            assert!(!with_fetch_guard(&in_progress, || {}));
+        });
+        assert!(ran);
     }
-}
 
-fn command_version(program: &str, args: &[&str]) -> Option<String> {
-    let output = Command::new(program).args(args).output().ok()?;
-    if !output.status.success() {
-        return None;
+    #[cfg(unix)]
+    #[test]
+    fn fs_events_under_symlinked_root_are_relevant() {
+        let dir = std::env::temp_dir().join(format!("bbq-symlink-root-{}", std::process::id()));
+        let real = dir.join("real");
+        fs::create_dir_all(real.join("repos/api.git/refs")).expect("create repos root");
+        fs::create_dir_all(real.join("worktrees")).expect("create worktrees root");
+        let link = dir.join("link");
+        std::os::unix::fs::symlink(&real, &link).expect("symlink root");
+
+        let roots = watch_roots(link.join("repos"), link.join("worktrees"));
+        assert_eq!(roots.len(), 2);
+        let event = |path: PathBuf| notify::Event::default().add_path(path);
+        assert!(is_relevant_fs_event(&event(real.join("repos/api.git/HEAD")), &roots));
+        assert!(is_relevant_fs_event(&event(link.join("repos/api.git/refs")), &roots));
+        assert!(is_relevant_fs_event(&event(real.join("worktrees/api")), &roots));
+        assert!(!is_relevant_fs_event(&event(real.join("repos/api.git/objects")), &roots));
+        assert!(!is_relevant_fs_event(&event(dir.join("elsewhere")), &roots));
+
+        let _ = fs::remove_dir_all(&dir);
     }
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    extract_version(&stdout)
-}
 
-fn extract_version(output: &str) -> Option<String> {
-    for raw in output.split_whitespace() {
-        let trimmed = raw.trim_matches(|ch: char| ch == ',' || ch == ';');
-        let trimmed = trimmed.trim_start_matches('v');
-        if !trimmed.chars().any(|ch| ch.is_ascii_digit()) {
-            continue;
-        }
-        let mut cleaned = String::new();
-        for ch in trimmed.chars() {
-            if ch.is_ascii_digit() || ch == '.' || ch == '-' {
-                cleaned.push(ch);
-            } else {
-                break;
-            }
-        }
-        if cleaned.chars().any(|ch| ch.is_ascii_digit()) {
-            return Some(cleaned);
-        }
+    #[test]
+    fn fetch_schedule_waits_for_interval() {
+        let start = Instant::now();
+        let interval = Duration::from_secs(300);
+        let mut schedule = FetchSchedule::new(interval, start);
+        assert!(!schedule.is_due(start));
+        assert!(!schedule.is_due(start + Duration::from_secs(299)));
+        assert!(schedule.is_due(start + interval));
+
+        schedule.mark_fetched(start + interval);
+        assert!(!schedule.is_due(start + Duration::from_secs(400)));
+        assert!(schedule.is_due(start + Duration::from_secs(600)));
     }
-    None
 }
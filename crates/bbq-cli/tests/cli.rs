@@ -1,7 +1,9 @@
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::process::{Command, Output};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::process::{Command, Output, Stdio};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 #[test]
 fn repo_list_empty() {
@@ -12,6 +14,56 @@ fn repo_list_empty() {
     assert_eq!(stdout.trim(), "no repos");
 }
 
+#[test]
+fn config_path_prints_resolved_roots() {
+    let ctx = TestContext::new("config_path_prints_resolved_roots");
+
+    let output = ctx.bbq(&["config", "path"]);
+    let stdout = assert_success(output);
+    let repos_line = format!("repos: {}", ctx.root.join("repos").display());
+    assert!(stdout.lines().any(|line| line == repos_line), "stdout: {stdout}");
+    let config_line = format!(
+        "config: {}",
+        ctx.home.join(".bbq").join("config.toml").display()
+    );
+    assert!(stdout.lines().any(|line| line == config_line), "stdout: {stdout}");
+}
+
+#[test]
+fn config_set_then_get_round_trips() {
+    let ctx = TestContext::new("config_set_then_get_round_trips");
+
+    let output = ctx.bbq(&["config", "get", "theme"]);
+    assert_eq!(assert_success(output).trim(), "orange");
+    let output = ctx.bbq(&["config", "set", "theme", "blue"]);
+    assert_success(output);
+    let output = ctx.bbq(&["config", "get", "theme"]);
+    assert_eq!(assert_success(output).trim(), "blue");
+
+    let output = ctx.bbq(&["config", "set", "open_order", "cursor, zed"]);
+    assert_success(output);
+    let config = fs::read_to_string(ctx.home.join(".bbq").join("config.toml")).expect("config");
+    assert!(config.contains("open_order = [\"cursor\", \"zed\"]"), "config: {config}");
+    let output = ctx.bbq(&["config", "get", "open_order"]);
+    assert_eq!(assert_success(output).trim(), "cursor,zed");
+
+    let editor = r#"code --wait "C:\My Files""#;
+    let output = ctx.bbq(&["config", "set", "editor", editor]);
+    assert_success(output);
+    let config = fs::read_to_string(ctx.home.join(".bbq").join("config.toml")).expect("config");
+    assert!(
+        config.contains(r#"editor = "code --wait \"C:\\My Files\"""#),
+        "config: {config}"
+    );
+    let output = ctx.bbq(&["config", "get", "editor"]);
+    assert_eq!(assert_success(output).trim(), editor);
+
+    let output = ctx.bbq(&["config", "set", "confirm_deletes", "maybe"]);
+    assert_failure_contains(output, "expects true or false");
+    let output = ctx.bbq(&["config", "get", "colour"]);
+    assert_failure_contains(output, "valid keys: root_dir, theme");
+}
+
 #[test]
 fn repo_clone_and_list() {
     let ctx = TestContext::new("repo_clone_and_list");
@@ -26,6 +78,62 @@ fn repo_clone_and_list() {
     assert_eq!(stdout.trim(), "source");
 }
 
+#[test]
+fn repo_clone_with_worktree_creates_a_usable_worktree() {
+    let ctx = TestContext::new("repo_clone_with_worktree_creates_a_usable_worktree");
+    let src_repo = ctx.root.join("source");
+    init_repo(&src_repo);
+    add_post_create_script(&src_repo);
+
+    let output = ctx.bbq(&[
+        "repo",
+        "clone",
+        src_repo.to_str().expect("repo path"),
+        "--with-worktree=work",
+    ]);
+    let stdout = assert_success(output);
+    assert!(stdout.contains("checked out source"), "stdout: {stdout}");
+    assert!(stdout.contains("created work"), "stdout: {stdout}");
+
+    let worktree_path = ctx.root.join("worktrees").join("source").join("work");
+    assert!(worktree_path.join("README.md").is_file());
+    assert!(worktree_path.join("post-create.log").is_file());
+    run_git(&["status", "--short"], &worktree_path);
+
+    // Without a name the worktree is named after the default branch, not `origin/<branch>`.
+    let other_repo = ctx.root.join("other");
+    init_repo(&other_repo);
+    run_git(&["branch", "-M", "trunk"], &other_repo);
+    let output = ctx.bbq(&[
+        "repo",
+        "clone",
+        other_repo.to_str().expect("repo path"),
+        "--with-worktree",
+    ]);
+    assert_success_contains(output, "created trunk");
+    let worktree_path = ctx.root.join("worktrees").join("other").join("trunk");
+    assert!(worktree_path.join("README.md").is_file());
+    assert!(!ctx.root.join("worktrees").join("other").join("origin").exists());
+}
+
+#[test]
+fn repo_list_porcelain_prints_bare_repo_path() {
+    let ctx = TestContext::new("repo_list_porcelain_prints_bare_repo_path");
+    let src_repo = ctx.root.join("source");
+    init_repo(&src_repo);
+
+    let output = ctx.bbq(&["repo", "clone", src_repo.to_str().expect("repo path")]);
+    assert_success_contains(output, "checked out source");
+
+    let output = ctx.bbq(&["repo", "list", "--porcelain"]);
+    let stdout = assert_success(output);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 1);
+    let fields: Vec<&str> = lines[0].split('\t').collect();
+    assert_eq!(fields[0], "source");
+    assert_eq!(Path::new(fields[1]), ctx.root.join("repos").join("source.git"));
+}
+
 #[test]
 fn repo_clone_custom_name() {
     let ctx = TestContext::new("repo_clone_custom_name");
@@ -45,6 +153,73 @@ fn repo_clone_custom_name() {
     assert_eq!(stdout.trim(), "custom");
 }
 
+#[test]
+fn repo_clone_print_name_does_not_clone() {
+    let ctx = TestContext::new("repo_clone_print_name_does_not_clone");
+
+    let output = ctx.bbq(&[
+        "repo",
+        "clone",
+        "git@github.com:octocat/My Project!.git",
+        "--print-name",
+    ]);
+    let stdout = assert_success(output);
+    assert_eq!(stdout.trim(), "My-Project");
+
+    let output = ctx.bbq(&[
+        "repo",
+        "clone",
+        "https://example.com/a/b.git",
+        "my repo",
+        "--print-name",
+    ]);
+    let stdout = assert_success(output);
+    assert_eq!(stdout.trim(), "my-repo");
+
+    let output = ctx.bbq(&["repo", "list"]);
+    let stdout = assert_success(output);
+    assert_eq!(stdout.trim(), "no repos");
+}
+
+#[test]
+fn repo_clone_single_branch_warns() {
+    let ctx = TestContext::new("repo_clone_single_branch_warns");
+    let src_repo = ctx.root.join("source");
+    init_repo(&src_repo);
+
+    let output = ctx.bbq(&[
+        "repo",
+        "clone",
+        src_repo.to_str().expect("repo path"),
+        "--single-branch",
+    ]);
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    assert_success_contains(output, "checked out source");
+    assert!(stderr.contains("single-branch clone"), "stderr: {stderr}");
+}
+
+#[test]
+fn repo_clone_detects_duplicate_remote() {
+    let ctx = TestContext::new("repo_clone_detects_duplicate_remote");
+    let src_repo = ctx.root.join("source");
+    init_repo(&src_repo);
+    let src = src_repo.to_str().expect("repo path");
+
+    let output = ctx.bbq(&["repo", "clone", src]);
+    assert_success_contains(output, "checked out source");
+
+    let trailing_slash = format!("{}/", src);
+    let output = ctx.bbq(&["repo", "clone", &trailing_slash, "copy"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("already cloned as source"), "stderr: {stderr}");
+
+    let output = ctx.bbq(&["repo", "clone", src, "copy", "--force"]);
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    assert_success_contains(output, "checked out copy");
+    assert!(stderr.contains("warning: "), "stderr: {stderr}");
+}
+
 #[test]
 fn repo_rm_removes_repo() {
     let ctx = TestContext::new("repo_rm_removes_repo");
@@ -92,6 +267,139 @@ fn worktree_create_list_rm() {
     assert_eq!(stdout.trim(), "no worktrees");
 }
 
+#[test]
+fn worktree_create_auto_suffix_on_collision() {
+    let ctx = TestContext::new("worktree_create_auto_suffix_on_collision");
+    let src_repo = ctx.root.join("source");
+    init_repo(&src_repo);
+
+    let output = ctx.bbq(&["repo", "clone", src_repo.to_str().expect("repo path")]);
+    assert_success_contains(output, "checked out source");
+
+    let output = ctx.bbq(&["worktree", "create", "source", "--branch", "feature"]);
+    assert_success_contains(output, "created feature");
+    // Free the branch so only the directory name collides.
+    let first = ctx.root.join("worktrees/source/feature");
+    run_git(&["checkout", "--quiet", "-b", "elsewhere"], &first);
+
+    let output = ctx.bbq(&["worktree", "create", "source", "--branch", "feature"]);
+    assert!(!output.status.success(), "expected collision to fail");
+
+    let output = ctx.bbq(&[
+        "worktree",
+        "create",
+        "source",
+        "--branch",
+        "feature",
+        "--auto-suffix",
+    ]);
+    assert_success_contains(output, "created feature-2");
+}
+
+#[test]
+fn repo_clone_interactive_prompts_for_taken_name() {
+    let ctx = TestContext::new("repo_clone_interactive_prompts_for_taken_name");
+    let first = ctx.root.join("source");
+    init_repo(&first);
+    let second = ctx.root.join("other/source");
+    init_repo(&second);
+
+    let output = ctx.bbq(&["repo", "clone", first.to_str().expect("repo path")]);
+    assert_success_contains(output, "checked out source");
+
+    let output = ctx.bbq(&["repo", "clone", second.to_str().expect("repo path")]);
+    assert!(!output.status.success(), "expected a taken name to fail");
+
+    let output = ctx.bbq_with_stdin(
+        &["repo", "clone", second.to_str().expect("repo path"), "--interactive"],
+        "source two\n",
+    );
+    assert_success_contains(output, "checked out source-two");
+    assert!(ctx.root.join("repos/source-two.git").is_dir());
+}
+
+#[test]
+fn worktree_list_filters_by_label() {
+    let ctx = TestContext::new("worktree_list_filters_by_label");
+    let src_repo = ctx.root.join("source");
+    init_repo(&src_repo);
+
+    let output = ctx.bbq(&["repo", "clone", src_repo.to_str().expect("repo path")]);
+    assert_success_contains(output, "checked out source");
+    let output = ctx.bbq_with_stdin(&["worktree", "create", "source", "--stdin"], "alpha\nbeta\n");
+    assert_success_contains(output, "created beta");
+    fs::write(
+        ctx.home.join(".bbq/restore.toml"),
+        "labels = [\"source/alpha:review\", \"source/beta:spike\"]\n",
+    )
+    .expect("write restore state");
+
+    let stdout = assert_success(ctx.bbq(&["worktree", "list", "source", "--label", "review"]));
+    assert!(stdout.contains("alpha"), "stdout: {stdout}");
+    assert!(!stdout.contains("beta"), "stdout: {stdout}");
+
+    let stdout = assert_success(ctx.bbq(&["worktree", "list", "source", "--label", "none"]));
+    assert_eq!(stdout.trim(), "no worktrees");
+}
+
+#[test]
+fn worktree_create_from_stdin_batch() {
+    let ctx = TestContext::new("worktree_create_from_stdin_batch");
+    let src_repo = ctx.root.join("source");
+    init_repo(&src_repo);
+    run_git(&["branch", "base"], &src_repo);
+
+    let output = ctx.bbq(&["repo", "clone", src_repo.to_str().expect("repo path")]);
+    assert_success_contains(output, "checked out source");
+
+    let output = ctx.bbq_with_stdin(
+        &["worktree", "create", "source", "--stdin"],
+        "alpha\nbeta:base\n",
+    );
+    let stdout = assert_success(output);
+    assert!(stdout.contains("created alpha"), "stdout: {stdout}");
+    assert!(stdout.contains("created beta"), "stdout: {stdout}");
+    assert!(ctx.root.join("worktrees/source/alpha").is_dir());
+    assert!(ctx.root.join("worktrees/source/beta").is_dir());
+
+    let output = ctx.bbq_with_stdin(
+        &["worktree", "create", "source", "--stdin"],
+        "alpha\ngamma\n",
+    );
+    assert!(!output.status.success(), "expected batch with a failure to fail");
+    assert!(ctx.root.join("worktrees/source/gamma").is_dir());
+}
+
+#[test]
+fn worktree_diff_stat_lists_changed_files() {
+    let ctx = TestContext::new("worktree_diff_stat_lists_changed_files");
+    let src_repo = ctx.root.join("source");
+    init_repo(&src_repo);
+
+    let output = ctx.bbq(&["repo", "clone", src_repo.to_str().expect("repo path")]);
+    assert_success_contains(output, "checked out source");
+    let output = ctx.bbq(&["worktree", "create", "source", "--branch", "feature"]);
+    assert_success_contains(output, "created feature");
+
+    let worktree = ctx.root.join("worktrees/source/feature");
+    fs::write(worktree.join("README.md"), "hello\nchanged\n").expect("edit README");
+    fs::write(worktree.join("notes.txt"), "staged\n").expect("write notes");
+    run_git(&["add", "notes.txt"], &worktree);
+
+    let output = ctx.bbq(&["worktree", "diff", "source", "feature", "--stat"]);
+    let stdout = assert_success(output);
+    assert!(stdout.contains("README.md"), "stdout: {stdout}");
+    assert!(stdout.contains("notes.txt"), "stdout: {stdout}");
+
+    let output = ctx.bbq(&["worktree", "diff", "source", "feature", "--stat", "--staged"]);
+    let stdout = assert_success(output);
+    assert!(stdout.contains("notes.txt"), "stdout: {stdout}");
+    assert!(!stdout.contains("README.md"), "stdout: {stdout}");
+
+    let output = ctx.bbq(&["worktree", "diff", "source", "feature"]);
+    assert_success_contains(output, "+changed");
+}
+
 #[test]
 fn worktree_create_runs_post_create_script() {
     let ctx = TestContext::new("worktree_create_runs_post_create_script");
@@ -216,6 +524,419 @@ fn worktree_list_shows_multiple_entries() {
     assert!(stdout.contains("beta\t"));
 }
 
+#[test]
+fn worktree_list_resolves_repo_aliases() {
+    let ctx = TestContext::new("worktree_list_resolves_repo_aliases");
+    let src_repo = ctx.root.join("source");
+    init_repo(&src_repo);
+    // A quoted comma must not split `"old,src"` into a second `src` alias.
+    ctx.write_config("aliases = { \"old,src\" = \"missing\", src = \"source\" }\n");
+
+    let output = ctx.bbq(&["repo", "clone", src_repo.to_str().expect("repo path")]);
+    assert_success_contains(output, "checked out source");
+
+    let output = ctx.bbq(&["worktree", "create", "src", "--branch", "alpha"]);
+    assert_success_contains(output, "created alpha");
+
+    let output = ctx.bbq(&["worktree", "list", "src"]);
+    assert_success_contains(output, "alpha\t");
+}
+
+#[test]
+fn color_flag_controls_escape_sequences() {
+    let ctx = TestContext::new("color_flag_controls_escape_sequences");
+    let src_repo = ctx.root.join("source");
+    init_repo(&src_repo);
+
+    let output = ctx.bbq(&["repo", "clone", src_repo.to_str().expect("repo path")]);
+    assert_success_contains(output, "checked out source");
+    let output = ctx.bbq(&["worktree", "create", "source", "--branch", "alpha"]);
+    assert_success_contains(output, "created alpha");
+
+    let output = ctx.bbq(&["worktree", "list", "source"]);
+    let stdout = assert_success(output);
+    assert!(!stdout.contains('\x1b'), "piped output colored: {stdout:?}");
+
+    let output = ctx.bbq(&["--color", "never", "worktree", "list", "source"]);
+    let stdout = assert_success(output);
+    assert!(!stdout.contains('\x1b'), "stdout: {stdout:?}");
+
+    let output = ctx.bbq(&["--color", "always", "worktree", "list", "source"]);
+    let stdout = assert_success(output);
+    assert!(stdout.contains("\x1b[1malpha\x1b[0m\t"), "stdout: {stdout:?}");
+}
+
+#[test]
+fn worktree_create_applies_repo_git_config() {
+    let ctx = TestContext::new("worktree_create_applies_repo_git_config");
+    let src_repo = ctx.root.join("source");
+    init_repo(&src_repo);
+    fs::create_dir_all(src_repo.join(".bbq")).expect("create .bbq");
+    fs::write(
+        src_repo.join(".bbq").join("repo.toml"),
+        "[git-config]\n\"user.email\" = \"work@example.com\"\n\"not a key\" = \"x\"\n",
+    )
+    .expect("write repo config");
+    run_git(&["add", ".bbq/repo.toml"], &src_repo);
+    run_git(&["commit", "--quiet", "-m", "add repo config"], &src_repo);
+
+    let output = ctx.bbq(&["repo", "clone", src_repo.to_str().expect("repo path")]);
+    assert_success_contains(output, "checked out source");
+    let output = ctx.bbq(&["worktree", "create", "source", "--branch", "work"]);
+    assert_success_contains(output, "created work");
+
+    let worktree_path = ctx.root.join("worktrees").join("source").join("work");
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(&worktree_path)
+        .args(["config", "user.email"])
+        .output()
+        .expect("read user.email");
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "work@example.com");
+}
+
+#[test]
+fn worktree_create_enables_signing_when_repo_asks() {
+    let ctx = TestContext::new("worktree_create_enables_signing_when_repo_asks");
+    let src_repo = ctx.root.join("source");
+    init_repo(&src_repo);
+    fs::create_dir_all(src_repo.join(".bbq")).expect("create .bbq");
+    fs::write(src_repo.join(".bbq").join("repo.toml"), "sign = true\n\n[env]\nsign = \"no\"\n")
+        .expect("write repo config");
+    run_git(&["add", ".bbq/repo.toml"], &src_repo);
+    run_git(&["commit", "--quiet", "-m", "add repo config"], &src_repo);
+
+    let output = ctx.bbq(&["repo", "clone", src_repo.to_str().expect("repo path")]);
+    assert_success_contains(output, "checked out source");
+    let output = ctx.bbq(&["worktree", "create", "source", "--branch", "signed"]);
+    assert_success_contains(output, "created signed");
+
+    let worktree_path = ctx.root.join("worktrees").join("source").join("signed");
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(&worktree_path)
+        .args(["config", "commit.gpgsign"])
+        .output()
+        .expect("read commit.gpgsign");
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "true");
+}
+
+#[test]
+fn worktree_create_sets_shared_hooks_path() {
+    let ctx = TestContext::new("worktree_create_sets_shared_hooks_path");
+    let src_repo = ctx.root.join("source");
+    init_repo(&src_repo);
+    fs::create_dir_all(src_repo.join(".bbq")).expect("create .bbq");
+    fs::write(src_repo.join(".bbq").join("repo.toml"), "hooks_path = \".githooks\"\n")
+        .expect("write repo config");
+    run_git(&["add", ".bbq/repo.toml"], &src_repo);
+    run_git(&["commit", "--quiet", "-m", "add repo config"], &src_repo);
+
+    let output = ctx.bbq(&["repo", "clone", src_repo.to_str().expect("repo path")]);
+    assert_success_contains(output, "checked out source");
+    let output = ctx.bbq(&["worktree", "create", "source", "--branch", "hooked"]);
+    assert_success_contains(output, "created hooked");
+
+    let worktree_path = ctx.root.join("worktrees").join("source").join("hooked");
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(&worktree_path)
+        .args(["config", "core.hooksPath"])
+        .output()
+        .expect("read core.hooksPath");
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), ".githooks");
+}
+
+#[test]
+fn worktree_list_json_includes_status_when_asked() {
+    let ctx = TestContext::new("worktree_list_json_includes_status_when_asked");
+    let src_repo = ctx.root.join("source");
+    init_repo(&src_repo);
+
+    let output = ctx.bbq(&["repo", "clone", src_repo.to_str().expect("repo path")]);
+    assert_success_contains(output, "checked out source");
+    let output = ctx.bbq(&["worktree", "create", "source", "--branch", "alpha"]);
+    assert_success_contains(output, "created alpha");
+
+    let output = ctx.bbq(&["worktree", "list", "source", "--format", "json"]);
+    let list: serde_json::Value =
+        serde_json::from_str(&assert_success(output)).expect("parse json");
+    assert_eq!(list[0]["name"], "alpha");
+    assert_eq!(list[0]["branch"], "alpha");
+    assert!(list[0].get("changed").is_none(), "list: {list}");
+
+    // Stand in for a pushed branch so the upstream resolves.
+    let worktree_path = ctx.root.join("worktrees").join("source").join("alpha");
+    run_git(&["update-ref", "refs/remotes/origin/alpha", "HEAD"], &worktree_path);
+    fs::write(worktree_path.join("notes.txt"), "draft\n").expect("write notes");
+
+    let output = ctx.bbq(&["worktree", "list", "source", "--format", "json", "--status"]);
+    let list: serde_json::Value =
+        serde_json::from_str(&assert_success(output)).expect("parse json");
+    assert_eq!(list[0]["upstream"], "alpha", "list: {list}");
+    assert_eq!(list[0]["ahead"], 0, "list: {list}");
+    assert_eq!(list[0]["behind"], 0, "list: {list}");
+    assert_eq!(list[0]["changed"], 1, "list: {list}");
+
+    let output = ctx.bbq(&["worktree", "list", "source", "--status"]);
+    assert_failure_contains(output, "--status requires --format json");
+}
+
+#[test]
+fn worktree_list_since_skips_stale_worktrees() {
+    let ctx = TestContext::new("worktree_list_since_skips_stale_worktrees");
+    let src_repo = ctx.root.join("source");
+    init_repo(&src_repo);
+
+    let output = ctx.bbq(&["repo", "clone", src_repo.to_str().expect("repo path")]);
+    assert_success_contains(output, "checked out source");
+
+    for branch in ["alpha", "beta"] {
+        let output = ctx.bbq(&["worktree", "create", "source", "--branch", branch]);
+        assert_success_contains(output, &format!("created {}", branch));
+    }
+
+    let stale = ctx.root.join("worktrees").join("source").join("alpha");
+    let old_date = "2020-01-01T00:00:00Z";
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(&stale)
+        .args(["-c", "user.name=bbq-test", "-c", "user.email=bbq-test@example.com"])
+        .args(["commit", "--quiet", "--allow-empty", "-m", "old work"])
+        .env("GIT_AUTHOR_DATE", old_date)
+        .env("GIT_COMMITTER_DATE", old_date)
+        .output()
+        .expect("backdated commit");
+    assert!(output.status.success());
+    let old_mtime = UNIX_EPOCH + Duration::from_secs(1_577_836_800);
+    fs::File::open(&stale)
+        .and_then(|dir| dir.set_modified(old_mtime))
+        .expect("backdate worktree dir");
+
+    let output = ctx.bbq(&["worktree", "list", "source", "--since", "1d"]);
+    let stdout = assert_success(output);
+    assert!(stdout.contains("beta\t"));
+    assert!(!stdout.contains("alpha\t"));
+
+    let output = ctx.bbq(&["worktree", "list", "source", "--since", "10y"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn worktree_list_porcelain_emits_stable_fields() {
+    let ctx = TestContext::new("worktree_list_porcelain_emits_stable_fields");
+    let src_repo = ctx.root.join("source");
+    init_repo(&src_repo);
+
+    let output = ctx.bbq(&["repo", "clone", src_repo.to_str().expect("repo path")]);
+    assert_success_contains(output, "checked out source");
+
+    let output = ctx.bbq(&["worktree", "create", "source", "--branch", "alpha"]);
+    assert_success_contains(output, "created alpha");
+
+    let worktree_path = ctx.root.join("worktrees").join("source").join("alpha");
+    let head = String::from_utf8_lossy(
+        &Command::new("git")
+            .arg("-C")
+            .arg(&worktree_path)
+            .args(["rev-parse", "HEAD"])
+            .output()
+            .expect("rev-parse")
+            .stdout,
+    )
+    .trim()
+    .to_string();
+
+    let output = ctx.bbq(&["worktree", "list", "source", "--porcelain"]);
+    let stdout = assert_success(output);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 1, "unexpected output: {stdout:?}");
+    let fields: Vec<&str> = lines[0].split('\t').collect();
+    assert_eq!(fields.len(), 4);
+    assert_eq!(fields[0], "alpha");
+    assert_eq!(fields[1], "alpha");
+    assert_eq!(fields[2], head);
+    assert_eq!(
+        fs::canonicalize(fields[3]).expect("canonical path"),
+        fs::canonicalize(&worktree_path).expect("canonical worktree")
+    );
+
+    let output = ctx.bbq(&["worktree", "list", "source", "--porcelain", "-z"]);
+    let stdout = assert_success(output);
+    let fields_z: Vec<&str> = stdout.split_terminator('\0').collect();
+    assert_eq!(fields_z, fields);
+}
+
+#[test]
+fn worktree_list_message_shows_commit_subject() {
+    let ctx = TestContext::new("worktree_list_message_shows_commit_subject");
+    let src_repo = ctx.root.join("source");
+    init_repo(&src_repo);
+
+    let output = ctx.bbq(&["repo", "clone", src_repo.to_str().expect("repo path")]);
+    assert_success_contains(output, "checked out source");
+
+    let output = ctx.bbq(&["worktree", "create", "source", "--branch", "alpha"]);
+    assert_success_contains(output, "created alpha");
+
+    let output = ctx.bbq(&["worktree", "list", "source"]);
+    let stdout = assert_success(output);
+    assert_eq!(stdout.trim_end().split('\t').count(), 2, "unexpected output: {stdout:?}");
+
+    let output = ctx.bbq(&["worktree", "list", "source", "--message"]);
+    let stdout = assert_success(output);
+    let fields: Vec<&str> = stdout.trim_end().split('\t').collect();
+    assert_eq!(fields.len(), 4, "unexpected output: {stdout:?}");
+    assert_eq!(fields[0], "alpha");
+    assert_eq!(fields[2], "alpha");
+    assert_eq!(fields[3], "init");
+}
+
+#[test]
+fn env_json_reports_git_version() {
+    let ctx = TestContext::new("env_json_reports_git_version");
+    let src_repo = ctx.root.join("source");
+    init_repo(&src_repo);
+
+    let output = ctx.bbq(&["repo", "clone", src_repo.to_str().expect("repo path")]);
+    assert_success_contains(output, "checked out source");
+
+    let output = ctx.bbq(&["env", "--format", "json"]);
+    let stdout = assert_success(output);
+    let report: serde_json::Value = serde_json::from_str(&stdout).expect("parse env json");
+    let git_version = report["git_version"].as_str().expect("git_version string");
+    assert!(git_version.chars().any(|ch| ch.is_ascii_digit()));
+    assert_eq!(report["repos_count"], 1);
+    assert_eq!(
+        report["root"].as_str().map(PathBuf::from),
+        Some(ctx.root.clone())
+    );
+    assert!(report["gh_authenticated"].is_boolean());
+}
+
+#[test]
+fn offline_worktree_create_uses_local_refs() {
+    let ctx = TestContext::new("offline_worktree_create_uses_local_refs");
+    let src_repo = ctx.root.join("source");
+    init_repo(&src_repo);
+    let output = ctx.bbq(&["repo", "clone", src_repo.to_str().expect("repo path")]);
+    assert_success_contains(output, "checked out source");
+
+    let output = ctx.bbq_with_stdin(
+        &["--offline", "worktree", "create", "source", "--stdin"],
+        "alpha\n",
+    );
+    assert_success_contains(output, "created alpha");
+
+    let output = ctx.bbq(&[
+        "--offline", "worktree", "create", "source", "--branch", "pinned", "--at", "HEAD",
+    ]);
+    assert_success_contains(output, "created pinned");
+}
+
+#[test]
+fn offline_flag_blocks_clone() {
+    let ctx = TestContext::new("offline_flag_blocks_clone");
+    let src_repo = ctx.root.join("source");
+    init_repo(&src_repo);
+
+    let output = ctx.bbq(&["--offline", "repo", "clone", src_repo.to_str().expect("repo path")]);
+    assert!(!output.status.success(), "expected offline clone to fail");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Offline"), "stderr: {stderr}");
+    assert!(!ctx.root.join("repos/source.git").exists());
+}
+
+#[test]
+fn version_long_reports_version_and_root() {
+    let ctx = TestContext::new("version_long_reports_version_and_root");
+
+    let stdout = assert_success(ctx.bbq(&["version"]));
+    assert_eq!(stdout.trim(), format!("bbq {}", env!("CARGO_PKG_VERSION")));
+
+    let stdout = assert_success(ctx.bbq(&["version", "--long"]));
+    assert!(
+        stdout.contains(&format!("bbq {}", env!("CARGO_PKG_VERSION"))),
+        "stdout: {stdout}"
+    );
+    assert!(stdout.contains(&format!("root: {}", ctx.root.display())), "stdout: {stdout}");
+    assert!(stdout.contains("git: "), "stdout: {stdout}");
+
+    // `--verbose` traces git commands; it doesn't ask for the long report.
+    let output = ctx.bbq(&["version", "--verbose"]);
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    let stdout = assert_success(output);
+    assert_eq!(stdout.trim(), format!("bbq {}", env!("CARGO_PKG_VERSION")));
+    assert!(stderr.is_empty(), "stderr: {stderr}");
+}
+
+#[test]
+fn verbose_flag_echoes_git_commands() {
+    let ctx = TestContext::new("verbose_flag_echoes_git_commands");
+    let src_repo = ctx.root.join("source");
+    init_repo(&src_repo);
+
+    let output = ctx.bbq(&["repo", "clone", src_repo.to_str().expect("repo path")]);
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    assert_success_contains(output, "checked out source");
+    assert!(!stderr.contains("+ git"), "expected quiet stderr, got {stderr:?}");
+
+    let output = ctx.bbq(&["--verbose", "worktree", "list", "source"]);
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    assert_success_contains(output, "no worktrees");
+    assert!(
+        stderr.contains("+ git --git-dir"),
+        "expected git trace on stderr, got {stderr:?}"
+    );
+}
+
+#[test]
+fn repo_clone_appends_configured_clone_args() {
+    let ctx = TestContext::new("repo_clone_appends_configured_clone_args");
+    let src_repo = ctx.root.join("source");
+    init_repo(&src_repo);
+    ctx.write_config("clone_args = [\"--filter=blob:none\", \"--no-tags\"]\n");
+
+    let output = ctx.bbq(&[
+        "--verbose",
+        "repo",
+        "clone",
+        src_repo.to_str().expect("repo path"),
+    ]);
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    assert_success_contains(output, "checked out source");
+    assert!(
+        stderr.contains("+ git clone --bare --filter=blob:none --no-tags "),
+        "expected clone args in order, got {stderr:?}"
+    );
+}
+
+#[test]
+fn json_error_format_reports_error_kind() {
+    let ctx = TestContext::new("json_error_format_reports_error_kind");
+    let src_repo = ctx.root.join("source");
+    init_repo(&src_repo);
+
+    let output = ctx.bbq(&["repo", "clone", src_repo.to_str().expect("repo path")]);
+    assert_success_contains(output, "checked out source");
+
+    let output = ctx.bbq(&[
+        "--error-format",
+        "json",
+        "worktree",
+        "open",
+        "source",
+        "missing",
+    ]);
+    assert!(!output.status.success(), "expected failure");
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    let error: serde_json::Value = serde_json::from_str(stderr.trim()).expect("parse error json");
+    assert_eq!(error["error"], "WorktreeNotFound");
+    assert_eq!(error["message"], "worktree not found: missing");
+    assert!(error["command"].is_null());
+    assert!(error["stderr"].is_null());
+}
+
 #[test]
 fn repo_rm_fails_with_worktrees() {
     let ctx = TestContext::new("repo_rm_fails_with_worktrees");
@@ -251,6 +972,35 @@ fn worktree_rm_missing_errors() {
     assert_failure_contains(output, "WorktreeNotFound(\"missing\")");
 }
 
+#[test]
+fn worktree_rm_refuses_dirty_or_mid_merge_worktrees_without_force() {
+    let ctx = TestContext::new("worktree_rm_refuses_dirty_or_mid_merge_worktrees_without_force");
+    let src_repo = ctx.root.join("source");
+    init_repo(&src_repo);
+
+    let output = ctx.bbq(&["repo", "clone", src_repo.to_str().expect("repo path")]);
+    assert_success_contains(output, "checked out source");
+    let output = ctx.bbq(&["worktree", "create", "source", "--branch", "feature"]);
+    assert_success_contains(output, "created feature");
+
+    let worktree_path = ctx.root.join("worktrees").join("source").join("feature");
+    fs::write(worktree_path.join("notes.txt"), "draft\n").expect("write notes");
+    let output = ctx.bbq(&["worktree", "rm", "source", "feature"]);
+    assert_failure_contains(output, "feature has 1 uncommitted change(s)");
+    fs::remove_file(worktree_path.join("notes.txt")).expect("remove notes");
+
+    let git_file = fs::read_to_string(worktree_path.join(".git")).expect("read .git file");
+    let git_dir = PathBuf::from(git_file.trim().trim_start_matches("gitdir: "));
+    fs::write(git_dir.join("MERGE_HEAD"), "0000000000000000000000000000000000000000\n")
+        .expect("write MERGE_HEAD");
+    let output = ctx.bbq(&["worktree", "rm", "source", "feature"]);
+    assert_failure_contains(output, "feature has a merge in progress");
+
+    let output = ctx.bbq(&["worktree", "rm", "source", "feature", "--force"]);
+    assert_success_contains(output, "removed feature");
+    assert!(!worktree_path.exists());
+}
+
 #[test]
 fn worktree_open_uses_editor_from_config() {
     let ctx = TestContext::new("worktree_open_uses_editor_from_config");
@@ -283,6 +1033,101 @@ fn worktree_open_uses_editor_from_config() {
     assert_success_contains(output, "opened feature-test in editor");
 }
 
+#[test]
+fn worktree_open_reports_missing_editor() {
+    let ctx = TestContext::new("worktree_open_reports_missing_editor");
+    let src_repo = ctx.root.join("source");
+    init_repo(&src_repo);
+
+    let output = ctx.bbq(&["repo", "clone", src_repo.to_str().expect("repo path")]);
+    assert_success_contains(output, "checked out source");
+    let output = ctx.bbq(&["worktree", "create", "source", "--branch", "feature"]);
+    assert_success_contains(output, "created feature");
+
+    ctx.write_config("editor = \"bbq-missing-editor\"");
+    let output = ctx.bbq(&["worktree", "open", "source", "feature"]);
+    assert_failure_contains(output, "editor `bbq-missing-editor` not found; pick another");
+
+    ctx.write_config("editor = \"bbq-missing-editor --wait\"");
+    let output = ctx.bbq(&["worktree", "open", "source", "feature"]);
+    assert_failure_contains(output, "editor `bbq-missing-editor` not found; pick another");
+
+    ctx.write_config("terminal = \"bbq-missing-terminal\"");
+    let output = ctx.bbq(&["worktree", "open", "source", "feature", "--target", "terminal"]);
+    assert_failure_contains(output, "terminal `bbq-missing-terminal` not found; pick another");
+}
+
+#[test]
+fn worktree_open_runs_terminal_editors_in_the_configured_terminal() {
+    let ctx = TestContext::new("worktree_open_runs_terminal_editors_in_the_configured_terminal");
+    let src_repo = ctx.root.join("source");
+    init_repo(&src_repo);
+
+    let output = ctx.bbq(&["repo", "clone", src_repo.to_str().expect("repo path")]);
+    assert_success_contains(output, "checked out source");
+    let output = ctx.bbq(&["worktree", "create", "source", "--branch", "feature"]);
+    assert_success_contains(output, "created feature");
+
+    let bin_dir = ctx.root.join("bin");
+    fs::create_dir_all(&bin_dir).expect("create bin dir");
+    let log = ctx.root.join("term.log");
+    let terminal = write_stub_command(
+        &bin_dir,
+        "my-term",
+        &format!("printf '%s\\n' \"$@\" > '{}'", log.display()),
+    );
+    ctx.write_config(&format!("editor = \"vim\"\nterminal = \"{}\"", terminal.display()));
+
+    let output = ctx.bbq(&["worktree", "open", "source", "feature"]);
+    assert_success_contains(output, "opened feature in editor");
+    // The terminal is spawned detached, so wait for it to write down its arguments.
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let mut args = String::new();
+    while !args.contains("exec") && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(20));
+        args = fs::read_to_string(&log).unwrap_or_default();
+    }
+    let args: Vec<&str> = args.lines().collect();
+    assert_eq!(args[..3], ["-e", "sh", "-lc"], "{args:?}");
+    assert!(args[3].ends_with("&& exec vim ."), "{args:?}");
+}
+
+#[test]
+fn open_without_args_reopens_last_worktree() {
+    let ctx = TestContext::new("open_without_args_reopens_last_worktree");
+    let src_repo = ctx.root.join("source");
+    init_repo(&src_repo);
+
+    let output = ctx.bbq(&["repo", "clone", src_repo.to_str().expect("repo path")]);
+    assert_success_contains(output, "checked out source");
+    let output = ctx.bbq(&["worktree", "create", "source", "--branch", "feature"]);
+    assert_success_contains(output, "created feature");
+
+    let output = ctx.bbq(&["open"]);
+    assert!(!output.status.success(), "expected open with no history to fail");
+
+    let bin_dir = ctx.root.join("bin");
+    fs::create_dir_all(&bin_dir).expect("create bin dir");
+    let log = ctx.root.join("editor.log");
+    write_stub_command(&bin_dir, "stub-editor", &format!("echo \"$1\" >> '{}'", log.display()));
+    ctx.write_config(&format!("editor = \"{}\"", bin_dir.join("stub-editor").display()));
+
+    let output = ctx.bbq(&["worktree", "open", "source", "feature"]);
+    assert_success_contains(output, "opened feature in editor");
+    let output = ctx.bbq(&["open"]);
+    assert_success_contains(output, "opened feature in editor");
+
+    let worktree_path = ctx.root.join("worktrees/source/feature");
+    let logged = wait_for_lines(&log, 2);
+    assert_eq!(logged.len(), 2, "editor log: {logged:?}");
+    for line in logged {
+        assert_eq!(
+            fs::canonicalize(line).expect("canonical logged path"),
+            fs::canonicalize(&worktree_path).expect("canonical worktree path")
+        );
+    }
+}
+
 #[test]
 fn worktree_create_uses_default_city_name_when_configured() {
     let ctx = TestContext::new("worktree_create_uses_default_city_name_when_configured");
@@ -308,6 +1153,34 @@ fn worktree_create_uses_default_city_name_when_configured() {
     assert_success_contains(output, &name);
 }
 
+#[test]
+fn completion_offers_branches_of_the_typed_repo() {
+    let ctx = TestContext::new("completion_offers_branches_of_the_typed_repo");
+    let src_repo = ctx.root.join("source");
+    init_repo(&src_repo);
+    run_git(&["branch", "release"], &src_repo);
+
+    let output = ctx.bbq(&["repo", "clone", src_repo.to_str().expect("repo path")]);
+    assert_success_contains(output, "checked out source");
+
+    let output = ctx.bbq(&["completions", "bash"]);
+    assert_success_contains(output, "COMPLETE=\"bash\"");
+
+    let output = Command::new(bbq_bin())
+        .args(["--", "bbq", "worktree", "create", "source", "--branch", ""])
+        .env("BBQ_ROOT_DIR", &ctx.root)
+        .env("HOME", &ctx.home)
+        .env("PATH", &ctx.path)
+        .env("COMPLETE", "bash")
+        .env("_CLAP_COMPLETE_INDEX", "5")
+        .env("_CLAP_IFS", "\n")
+        .output()
+        .expect("run bbq");
+    let stdout = assert_success(output);
+    let branches: Vec<&str> = stdout.lines().collect();
+    assert!(branches.contains(&"release"), "{stdout}");
+}
+
 struct TestContext {
     root: PathBuf,
     home: PathBuf,
@@ -333,10 +1206,34 @@ impl TestContext {
             .env("BBQ_ROOT_DIR", &self.root)
             .env("HOME", &self.home)
             .env("PATH", path)
+            .env_remove("XDG_CONFIG_HOME")
+            .env_remove("XDG_DATA_HOME")
             .output()
             .expect("run bbq")
     }
 
+    fn bbq_with_stdin(&self, args: &[&str], input: &str) -> Output {
+        let mut child = Command::new(bbq_bin())
+            .args(args)
+            .env("BBQ_ROOT_DIR", &self.root)
+            .env("HOME", &self.home)
+            .env("PATH", &self.path)
+            .env_remove("XDG_CONFIG_HOME")
+            .env_remove("XDG_DATA_HOME")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("spawn bbq");
+        child
+            .stdin
+            .take()
+            .expect("bbq stdin")
+            .write_all(input.as_bytes())
+            .expect("write stdin");
+        child.wait_with_output().expect("run bbq")
+    }
+
     fn write_config(&self, contents: &str) {
         let config_dir = self.home.join(".bbq");
         fs::create_dir_all(&config_dir).expect("create config dir");
@@ -427,6 +1324,23 @@ fn cleanup_root(root: &Path) {
     }
 }
 
+/// Lines written to `path` by a backgrounded stub, polling until `count` arrive or a timeout.
+fn wait_for_lines(path: &Path, count: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for _ in 0..50 {
+        lines = fs::read_to_string(path)
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_string)
+            .collect();
+        if lines.len() >= count {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    lines
+}
+
 fn write_stub_command(dir: &Path, name: &str, body: &str) -> PathBuf {
     let path = dir.join(name);
     let contents = format!("#!/bin/sh\n{}\n", body);
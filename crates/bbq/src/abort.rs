@@ -0,0 +1,147 @@
+//! Lets another thread stop the clone or script the current operation is waiting on, so the TUI
+//! can cancel a slow action without quitting.
+
+use std::io::{self, Read};
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::error::{BbqError, Result};
+
+const WAIT_POLL: Duration = Duration::from_millis(20);
+/// How long an aborted command's processes get to exit on SIGTERM before they are killed.
+#[cfg(unix)]
+const ABORT_GRACE: Duration = Duration::from_secs(2);
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static RUNNING: Mutex<Option<Running>> = Mutex::new(None);
+static ABORTED: AtomicBool = AtomicBool::new(false);
+
+struct Running {
+    child: Arc<Mutex<Child>>,
+    /// Set when the child leads its own process group, so an abort reaches everything it started.
+    grouped: bool,
+}
+
+/// Makes the commands run after this call abortable. On unix each one gets its own process
+/// group, which also cuts it off from the terminal, so only a caller that owns the terminal
+/// (the TUI) should turn this on.
+pub fn enable_abort() {
+    ENABLED.store(true, Ordering::SeqCst);
+}
+
+/// Stops the command registered by the operation in progress, along with anything it spawned.
+/// Returns `false` when nothing abortable is running.
+pub fn abort_running_command() -> bool {
+    let running = RUNNING.lock().unwrap_or_else(|err| err.into_inner());
+    let Some(running) = running.as_ref() else {
+        return false;
+    };
+    ABORTED.store(true, Ordering::SeqCst);
+    let mut child = running.child.lock().unwrap_or_else(|err| err.into_inner());
+    #[cfg(unix)]
+    if running.grouped {
+        // SIGTERM first so git can remove what it was writing; `abortable_output` escalates.
+        signal_group(child.id(), libc::SIGTERM);
+        return true;
+    }
+    let _ = child.kill();
+    true
+}
+
+/// Like `Command::output`, but once `enable_abort` has been called the command can be stopped
+/// by `abort_running_command`, in which case this returns `BbqError::Aborted` after every
+/// process it started has exited. Only one command is abortable at a time.
+pub(crate) fn abortable_output(command: &mut Command) -> Result<Output> {
+    if !ENABLED.load(Ordering::SeqCst) {
+        return Ok(command.stdin(Stdio::null()).output()?);
+    }
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(command, 0);
+    let grouped = cfg!(unix);
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let mut child = command.spawn()?;
+    #[cfg(unix)]
+    let pgid = child.id();
+    let stdout = read_in_background(child.stdout.take());
+    let stderr = read_in_background(child.stderr.take());
+
+    let child = Arc::new(Mutex::new(child));
+    ABORTED.store(false, Ordering::SeqCst);
+    *RUNNING.lock().unwrap_or_else(|err| err.into_inner()) = Some(Running {
+        child: Arc::clone(&child),
+        grouped,
+    });
+    let status = wait(&child);
+    *RUNNING.lock().unwrap_or_else(|err| err.into_inner()) = None;
+    let status = status?;
+
+    if ABORTED.swap(false, Ordering::SeqCst) {
+        #[cfg(unix)]
+        drain_group(pgid);
+        return Err(BbqError::Aborted);
+    }
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+/// Waits until no process is left in the aborted command's group, so nothing is still writing
+/// when the caller cleans up. Whatever ignores SIGTERM past `ABORT_GRACE` gets SIGKILL.
+#[cfg(unix)]
+fn drain_group(pgid: u32) {
+    use std::time::Instant;
+
+    let mut deadline = Instant::now() + ABORT_GRACE;
+    let mut killed = false;
+    while signal_group(pgid, 0) {
+        if Instant::now() >= deadline {
+            if killed {
+                return;
+            }
+            signal_group(pgid, libc::SIGKILL);
+            killed = true;
+            deadline = Instant::now() + ABORT_GRACE;
+        }
+        thread::sleep(WAIT_POLL);
+    }
+}
+
+#[cfg(unix)]
+fn signal_group(pgid: u32, signal: libc::c_int) -> bool {
+    let Ok(pgid) = libc::pid_t::try_from(pgid) else {
+        return false;
+    };
+    // SAFETY: kill(2) takes plain integers and has no memory-safety requirements.
+    unsafe { libc::kill(-pgid, signal) == 0 }
+}
+
+fn wait(child: &Mutex<Child>) -> io::Result<ExitStatus> {
+    loop {
+        let status = child
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .try_wait()?;
+        if let Some(status) = status {
+            return Ok(status);
+        }
+        thread::sleep(WAIT_POLL);
+    }
+}
+
+fn read_in_background(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
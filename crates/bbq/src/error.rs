@@ -12,28 +12,114 @@ pub enum BbqError {
     InvalidWorktreeName,
     #[error("repo already exists: {0}")]
     RepoAlreadyExists(String),
+    #[error("repo {0} is already being cloned")]
+    CloneInProgress(String),
     #[error("repo not found: {0}")]
     RepoNotFound(String),
+    #[error("repo name is ambiguous: {0}")]
+    AmbiguousRepoName(String),
+    #[error("branch already exists: {0}")]
+    BranchAlreadyExists(String),
+    #[error("branch {branch} is already checked out in worktree {worktree}")]
+    BranchAlreadyCheckedOut { branch: String, worktree: String },
+    #[error("commit not found: {0}")]
+    CommitNotFound(String),
+    #[error("source branch not found: {branch}{}", suggestion_hint(.suggestions))]
+    SourceBranchNotFound {
+        branch: String,
+        suggestions: Vec<String>,
+    },
     #[error("worktree already exists: {0}")]
     WorktreeAlreadyExists(String),
+    #[error("worktree path {} is inside {}", .path.display(), .parent.display())]
+    WorktreePathNested {
+        path: std::path::PathBuf,
+        parent: std::path::PathBuf,
+    },
     #[error("worktree not found: {0}")]
     WorktreeNotFound(String),
+    #[error("worktree name is ambiguous: {0}")]
+    AmbiguousWorktreeName(String),
+    #[error("worktree has a detached HEAD: {0}")]
+    DetachedHead(String),
+    #[error("repo {0} has no commits yet; push an initial commit, then create a worktree")]
+    EmptyRepository(String),
     #[error("repo has worktrees; remove them first")]
     RepoHasWorktrees,
     #[error("invalid repo name")]
     InvalidRepoName,
+    #[error("repo name {0} is reserved for global templates")]
+    ReservedRepoName(String),
+    #[error("repo {0} has no remote to clone from")]
+    RepoRemoteMissing(String),
+    #[error("invalid clone_args entry: {0}")]
+    InvalidCloneArg(String),
     #[error("github cli (gh) not found; install it or use a git url")]
     GitHubCliMissing,
     #[error("github cli command failed: {command}\n{stderr}")]
     GitHubCliCommand { command: String, stderr: String },
     #[error("git command failed: {command}\n{stderr}")]
     GitCommand { command: String, stderr: String },
+    #[error("offline mode: network access is disabled (--offline or BBQ_OFFLINE)")]
+    Offline,
+    #[error("aborted")]
+    Aborted,
     #[error("script missing shebang: {0}")]
     ScriptMissingShebang(String),
     #[error("script failed: {script}\n{message}")]
     ScriptFailed { script: String, message: String },
+    #[error("another bbq process is holding {}", .0.display())]
+    StateLocked(std::path::PathBuf),
     #[error(transparent)]
     Io(#[from] std::io::Error),
 }
 
+impl BbqError {
+    /// The variant name, for machine-readable error output.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            BbqError::HomeDirMissing => "HomeDirMissing",
+            BbqError::InvalidGitUrl => "InvalidGitUrl",
+            BbqError::InvalidBranchName => "InvalidBranchName",
+            BbqError::InvalidWorktreeName => "InvalidWorktreeName",
+            BbqError::RepoAlreadyExists(..) => "RepoAlreadyExists",
+            BbqError::CloneInProgress(..) => "CloneInProgress",
+            BbqError::RepoNotFound(..) => "RepoNotFound",
+            BbqError::AmbiguousRepoName(..) => "AmbiguousRepoName",
+            BbqError::BranchAlreadyExists(..) => "BranchAlreadyExists",
+            BbqError::BranchAlreadyCheckedOut { .. } => "BranchAlreadyCheckedOut",
+            BbqError::CommitNotFound(..) => "CommitNotFound",
+            BbqError::SourceBranchNotFound { .. } => "SourceBranchNotFound",
+            BbqError::WorktreeAlreadyExists(..) => "WorktreeAlreadyExists",
+            BbqError::WorktreePathNested { .. } => "WorktreePathNested",
+            BbqError::WorktreeNotFound(..) => "WorktreeNotFound",
+            BbqError::AmbiguousWorktreeName(..) => "AmbiguousWorktreeName",
+            BbqError::DetachedHead(..) => "DetachedHead",
+            BbqError::EmptyRepository(..) => "EmptyRepository",
+            BbqError::RepoHasWorktrees => "RepoHasWorktrees",
+            BbqError::InvalidRepoName => "InvalidRepoName",
+            BbqError::ReservedRepoName(..) => "ReservedRepoName",
+            BbqError::RepoRemoteMissing(..) => "RepoRemoteMissing",
+            BbqError::InvalidCloneArg(..) => "InvalidCloneArg",
+            BbqError::GitHubCliMissing => "GitHubCliMissing",
+            BbqError::GitHubCliCommand { .. } => "GitHubCliCommand",
+            BbqError::GitCommand { .. } => "GitCommand",
+            BbqError::Offline => "Offline",
+            BbqError::Aborted => "Aborted",
+            BbqError::ScriptMissingShebang(..) => "ScriptMissingShebang",
+            BbqError::ScriptFailed { .. } => "ScriptFailed",
+            BbqError::StateLocked(..) => "StateLocked",
+            BbqError::Io(..) => "Io",
+        }
+    }
+}
+
+fn suggestion_hint(suggestions: &[String]) -> String {
+    if suggestions.is_empty() {
+        String::new()
+    } else {
+        format!("; did you mean {}?", suggestions.join(", "))
+    }
+}
+
 pub type Result<T> = std::result::Result<T, BbqError>;
@@ -1,16 +1,49 @@
 use std::ffi::OsString;
-use std::fs;
-use std::io;
+use std::fs::{self, OpenOptions, TryLockError};
+use std::io::{self, Write};
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use crate::abort::abortable_output;
 use crate::error::{BbqError, Result};
-use crate::model::{Repo, Worktree};
-use crate::paths::{config_root, ensure_root_dirs, repos_root, worktrees_root};
+use crate::lock::with_state_lock;
+use crate::model::{CommitInfo, Repo, Worktree};
+use crate::paths::{
+    config_root, config_value, ensure_root_dirs, extra_clone_args, ignored_repo_names, repo_alias,
+    repos_root, worktrees_root,
+};
+use crate::templates::GLOBAL_TEMPLATE_DIR;
+use crate::validate::validate_branch_name;
+use crate::worktree_names::unique_worktree_name;
+
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+pub fn set_verbose(enabled: bool) {
+    VERBOSE.store(enabled, Ordering::Relaxed);
+}
+
+pub fn verbose_enabled() -> bool {
+    VERBOSE.load(Ordering::Relaxed)
+}
+
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Makes every clone and fetch fail fast with `BbqError::Offline` instead of touching the network.
+pub fn set_offline(enabled: bool) {
+    OFFLINE.store(enabled, Ordering::Relaxed);
+}
+
+pub fn offline_enabled() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
 
 pub fn list_repos() -> Result<Vec<Repo>> {
     ensure_root_dirs()?;
     let root = repos_root()?;
+    let ignored = ignored_repo_names();
     let mut repos = Vec::new();
 
     for entry in fs::read_dir(root)? {
@@ -20,16 +53,26 @@ pub fn list_repos() -> Result<Vec<Repo>> {
             continue;
         }
 
+        // Repo names are always UTF-8; anything else wasn't created by bbq and a lossy name
+        // would not map back to its worktrees directory.
+        let Ok(dir_name) = entry.file_name().into_string() else {
+            continue;
+        };
+        if dir_name.starts_with('.') {
+            continue;
+        }
+        if ignored.iter().any(|name| {
+            name == &dir_name || name.as_str() == dir_name.trim_end_matches(".git")
+        }) {
+            continue;
+        }
+
         let head = path.join("HEAD");
-        if !head.is_file() {
+        if !head.is_file() || clone_marker_path(&path).exists() {
             continue;
         }
 
-        let name = path
-            .file_name()
-            .map(|name| name.to_string_lossy().to_string())
-            .unwrap_or_else(|| "unknown".to_string());
-        let name = name.trim_end_matches(".git").to_string();
+        let name = dir_name.trim_end_matches(".git").to_string();
 
         repos.push(Repo { name, path });
     }
@@ -38,16 +81,67 @@ pub fn list_repos() -> Result<Vec<Repo>> {
     Ok(repos)
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct CloneOptions {
+    pub single_branch: bool,
+    pub branch: Option<String>,
+    /// Name for the cloned remote instead of `origin`.
+    pub origin: Option<String>,
+    /// Existing repo to borrow objects from via `--reference`.
+    pub reference: Option<PathBuf>,
+    /// Clone with `--mirror` instead of `--bare`, copying every ref from the remote.
+    pub mirror: bool,
+}
+
+impl CloneOptions {
+    fn layout_arg(&self) -> OsString {
+        OsString::from(if self.mirror { "--mirror" } else { "--bare" })
+    }
+
+    fn git_args(&self) -> Vec<OsString> {
+        let mut args = Vec::new();
+        if self.single_branch {
+            args.push(OsString::from("--single-branch"));
+        }
+        if let Some(branch) = self.branch.as_deref().map(str::trim) {
+            if !branch.is_empty() {
+                args.push(OsString::from("--branch"));
+                args.push(OsString::from(branch));
+            }
+        }
+        if let Some(origin) = self.origin.as_deref().map(str::trim) {
+            if !origin.is_empty() {
+                args.push(OsString::from("--origin"));
+                args.push(OsString::from(origin));
+            }
+        }
+        if let Some(reference) = self.reference.as_deref() {
+            args.push(OsString::from("--reference"));
+            args.push(reference.as_os_str().to_os_string());
+        }
+        args
+    }
+}
+
 pub fn checkout_repo(url: &str) -> Result<Repo> {
-    checkout_repo_internal(url, None)
+    checkout_repo_internal(url, None, &CloneOptions::default())
 }
 
 pub fn checkout_repo_with_name(url: &str, name: &str) -> Result<Repo> {
-    checkout_repo_internal(url, Some(name))
+    checkout_repo_internal(url, Some(name), &CloneOptions::default())
 }
 
-fn checkout_repo_internal(source: &str, name_override: Option<&str>) -> Result<Repo> {
-    ensure_root_dirs()?;
+pub fn checkout_repo_with_options(
+    url: &str,
+    name: Option<&str>,
+    options: &CloneOptions,
+) -> Result<Repo> {
+    checkout_repo_internal(url, name, options)
+}
+
+/// The repo name a clone of `source` would use, after sanitizing `name_override` or the name
+/// derived from the URL.
+pub fn clone_repo_name(source: &str, name_override: Option<&str>) -> Result<String> {
     let source = source.trim();
     if source.is_empty() {
         return Err(BbqError::InvalidGitUrl);
@@ -60,46 +154,160 @@ fn checkout_repo_internal(source: &str, name_override: Option<&str>) -> Result<R
     if name.is_empty() {
         return Err(BbqError::InvalidRepoName);
     }
+    // A repo named like the global templates dir would pick up its templates twice.
+    if name.eq_ignore_ascii_case(GLOBAL_TEMPLATE_DIR) {
+        return Err(BbqError::ReservedRepoName(name));
+    }
+    Ok(name)
+}
+
+fn checkout_repo_internal(
+    source: &str,
+    name_override: Option<&str>,
+    options: &CloneOptions,
+) -> Result<Repo> {
+    ensure_root_dirs()?;
+    let source = source.trim();
+    let name = clone_repo_name(source, name_override)?;
     let dest = repos_root()?.join(format!("{name}.git"));
+    let marker = clone_marker_path(&dest);
 
-    if dest.exists() {
-        return Err(BbqError::RepoAlreadyExists(name));
+    let github_slug = github_slug_from_source(source);
+    if github_slug.is_some() && !gh_available() {
+        return Err(BbqError::GitHubCliMissing);
+    }
+    // Claiming the name and publishing the finished repo happen under the state lock; the
+    // network-bound clone in between does not.
+    let _claim = with_state_lock(|| {
+        if dest.exists() && !marker.exists() {
+            return Err(BbqError::RepoAlreadyExists(name.clone()));
+        }
+        let mut claim = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&marker)?;
+        match claim.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => return Err(BbqError::CloneInProgress(name.clone())),
+            Err(TryLockError::Error(err)) => return Err(err.into()),
+        }
+        if dest.exists() {
+            if verbose_enabled() {
+                eprintln!("removing incomplete clone at {}", dest.display());
+            }
+            fs::remove_dir_all(&dest)?;
+        }
+        claim.set_len(0)?;
+        claim.write_all(source.as_bytes())?;
+        Ok(claim)
+    })?;
+    let cloned = with_network_retries(|| {
+        let result = match github_slug.as_deref() {
+            Some(slug) => run_gh_clone(slug, &dest, options),
+            None => run_git_clone(source, &dest, options),
+        };
+        if result.is_err() && dest.exists() {
+            let _ = fs::remove_dir_all(&dest);
+        }
+        result
+    });
+    if cloned.is_err() {
+        let _ = with_state_lock(|| Ok(fs::remove_file(&marker)?));
     }
+    cloned?;
 
-    if let Some(slug) = github_slug_from_source(source) {
-        if !gh_available() {
-            return Err(BbqError::GitHubCliMissing);
+    let repo = Repo { name, path: dest };
+    if options.mirror {
+        disable_push_mirror(&repo)?;
+    }
+    if commit_graph_enabled() {
+        if let Err(err) = write_commit_graph(&repo) {
+            if verbose_enabled() {
+                eprintln!("skipping commit-graph: {err}");
+            }
         }
-        run_gh_clone(&slug, &dest)?;
-    } else {
-        run_git_clone(source, &dest)?;
     }
+    with_state_lock(|| Ok(fs::remove_file(&marker)?))?;
+    Ok(repo)
+}
 
-    Ok(Repo { name, path: dest })
+/// Sits next to `<name>.git` while a clone is running (git won't clone into a non-empty
+/// directory, so it can't go inside). The cloning process holds an OS lock on it, so a second
+/// clone of the same name fails instead of deleting a clone in progress. If bbq dies mid-clone
+/// the marker survives unlocked, the partial repo is left out of listings, and the next clone of
+/// that name replaces it.
+fn clone_marker_path(dest: &Path) -> PathBuf {
+    let mut marker = dest.as_os_str().to_os_string();
+    marker.push(".bbq-cloning");
+    PathBuf::from(marker)
 }
 
-fn run_git_clone(source: &str, dest: &Path) -> Result<()> {
-    let args = vec![
-        OsString::from("clone"),
-        OsString::from("--bare"),
-        OsString::from(source.trim()),
-        dest.as_os_str().to_os_string(),
-    ];
-    run_git(args)
+/// Writes a commit-graph so the `rev-list`/`log` calls behind worktree status stay fast on
+/// large repos.
+fn write_commit_graph(repo: &Repo) -> Result<()> {
+    run_git(vec![
+        OsString::from("--git-dir"),
+        repo.path.as_os_str().to_os_string(),
+        OsString::from("commit-graph"),
+        OsString::from("write"),
+        OsString::from("--reachable"),
+    ])
 }
 
-fn run_gh_clone(slug: &str, dest: &Path) -> Result<()> {
-    let args = vec![
+/// `--mirror` also sets `remote.<name>.mirror`, which turns a plain `git push` from any worktree
+/// into a force-push of every ref. Worktrees push their own branch like in a bare clone instead.
+fn disable_push_mirror(repo: &Repo) -> Result<()> {
+    let Some(remote) = primary_remote(repo)? else {
+        return Ok(());
+    };
+    run_git(vec![
+        OsString::from("--git-dir"),
+        repo.path.as_os_str().to_os_string(),
+        OsString::from("config"),
+        OsString::from("--unset"),
+        OsString::from(format!("remote.{remote}.mirror")),
+    ])
+}
+
+fn run_git_clone(source: &str, dest: &Path, options: &CloneOptions) -> Result<()> {
+    let mut args = vec![OsString::from("clone"), options.layout_arg()];
+    args.extend(configured_clone_args(dest)?);
+    args.extend(options.git_args());
+    args.push(OsString::from(source.trim()));
+    args.push(dest.as_os_str().to_os_string());
+    run_git_abortable(args)
+}
+
+fn run_gh_clone(slug: &str, dest: &Path, options: &CloneOptions) -> Result<()> {
+    let mut args = vec![
         OsString::from("repo"),
         OsString::from("clone"),
         OsString::from(slug),
         dest.as_os_str().to_os_string(),
         OsString::from("--"),
-        OsString::from("--bare"),
+        options.layout_arg(),
     ];
+    args.extend(configured_clone_args(dest)?);
+    args.extend(options.git_args());
     run_gh(args)
 }
 
+/// `clone_args` from config. Entries that would end option parsing or name the destination
+/// themselves are rejected rather than passed through.
+fn configured_clone_args(dest: &Path) -> Result<Vec<OsString>> {
+    let dest = dest.to_string_lossy();
+    extra_clone_args()
+        .into_iter()
+        .map(|arg| {
+            if arg == "--" || arg.starts_with(dest.as_ref()) {
+                return Err(BbqError::InvalidCloneArg(arg));
+            }
+            Ok(OsString::from(arg))
+        })
+        .collect()
+}
+
 fn gh_available() -> bool {
     gh_command()
         .arg("--version")
@@ -121,13 +329,14 @@ pub fn list_worktrees(repo: &Repo) -> Result<Vec<Worktree>> {
 }
 
 pub fn default_remote_branch(repo: &Repo) -> Result<Option<String>> {
+    let remote = primary_remote(repo)?.unwrap_or_else(|| "origin".to_string());
     let args = vec![
         OsString::from("--git-dir"),
         repo.path.as_os_str().to_os_string(),
         OsString::from("symbolic-ref"),
-        OsString::from("refs/remotes/origin/HEAD"),
+        OsString::from(format!("refs/remotes/{remote}/HEAD")),
     ];
-    let output = git_command().args(&args).output()?;
+    let output = git_output(&args)?;
     if !output.status.success() {
         return Ok(None);
     }
@@ -149,25 +358,47 @@ pub fn default_remote_branch(repo: &Repo) -> Result<Option<String>> {
     Ok(Some(branch.to_string()))
 }
 
+/// Local and remote branch names of `repo`, as shells and pickers offer them for a worktree's
+/// source branch. Remote branches read like the `origin/name` sources `create_worktree_from`
+/// accepts; symbolic refs such as `origin/HEAD` are skipped.
+pub fn list_branches(repo: &Repo) -> Result<Vec<String>> {
+    let output = run_git_capture(vec![
+        OsString::from("--git-dir"),
+        repo.path.as_os_str().to_os_string(),
+        OsString::from("for-each-ref"),
+        OsString::from("--format=%(if)%(symref)%(then)%(else)%(refname:short)%(end)"),
+        OsString::from("refs/heads"),
+        OsString::from("refs/remotes"),
+    ])?;
+    Ok(output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
 pub fn default_branch(repo: &Repo) -> Result<Option<String>> {
     if let Some(branch) = default_remote_branch(repo)? {
         return Ok(Some(branch));
     }
 
+    let remote = primary_remote(repo)?;
     if let Some(branch) = symbolic_head_branch(repo)? {
-        if has_remote(repo, "origin")? {
-            return Ok(Some(format!("origin/{branch}")));
+        if let Some(remote) = remote {
+            return Ok(Some(format!("{remote}/{branch}")));
         }
         return Ok(Some(branch));
     }
 
+    let remote = remote.unwrap_or_else(|| "origin".to_string());
     let candidates = [
-        "refs/remotes/origin/main",
-        "refs/remotes/origin/master",
-        "refs/heads/main",
-        "refs/heads/master",
+        format!("refs/remotes/{remote}/main"),
+        format!("refs/remotes/{remote}/master"),
+        "refs/heads/main".to_string(),
+        "refs/heads/master".to_string(),
     ];
-    for reference in candidates {
+    for reference in &candidates {
         if git_ref_exists(&repo.path, reference)? {
             return Ok(Some(ref_to_branch_name(reference)));
         }
@@ -176,10 +407,52 @@ pub fn default_branch(repo: &Repo) -> Result<Option<String>> {
     Ok(None)
 }
 
+pub fn branch_exists(repo: &Repo, branch: &str) -> Result<bool> {
+    let branch = branch.trim();
+    if branch.is_empty() {
+        return Ok(false);
+    }
+    git_ref_exists(&repo.path, &format!("refs/heads/{branch}"))
+}
+
+/// Fails with `EmptyRepository` when the repo has no refs at all, fetching first in case commits
+/// were pushed after an empty clone.
+fn ensure_repo_has_commits(repo: &Repo) -> Result<()> {
+    if repo_has_refs(repo)? {
+        return Ok(());
+    }
+    fetch_primary_remote_if_present(repo)?;
+    if repo_has_refs(repo)? {
+        return Ok(());
+    }
+    Err(BbqError::EmptyRepository(repo.name.clone()))
+}
+
+fn repo_has_refs(repo: &Repo) -> Result<bool> {
+    let args = vec![
+        OsString::from("--git-dir"),
+        repo.path.as_os_str().to_os_string(),
+        OsString::from("for-each-ref"),
+        OsString::from("--count=1"),
+        OsString::from("--format=%(refname)"),
+    ];
+    Ok(!run_git_capture(args)?.trim().is_empty())
+}
+
 fn has_remote(repo: &Repo, name: &str) -> Result<bool> {
     Ok(list_remotes(repo)?.iter().any(|remote| remote == name))
 }
 
+/// The remote bbq fetches from and tracks: `origin` when present, otherwise the first remote
+/// (for example one named with `repo clone --origin`).
+fn primary_remote(repo: &Repo) -> Result<Option<String>> {
+    let remotes = list_remotes(repo)?;
+    if remotes.iter().any(|remote| remote == "origin") {
+        return Ok(Some("origin".to_string()));
+    }
+    Ok(remotes.into_iter().next())
+}
+
 fn symbolic_head_branch(repo: &Repo) -> Result<Option<String>> {
     let args = vec![
         OsString::from("--git-dir"),
@@ -187,7 +460,7 @@ fn symbolic_head_branch(repo: &Repo) -> Result<Option<String>> {
         OsString::from("symbolic-ref"),
         OsString::from("HEAD"),
     ];
-    let output = git_command().args(&args).output()?;
+    let output = git_output(&args)?;
     if !output.status.success() {
         return Ok(None);
     }
@@ -217,11 +490,159 @@ fn ref_to_branch_name(reference: &str) -> String {
         .to_string()
 }
 
+/// `name`, or `name-2`, `name-3`, ... if a worktree directory with that name already exists.
+pub fn available_worktree_name(repo: &Repo, name: &str) -> Result<String> {
+    let base_dir = worktrees_root()?.join(&repo.name);
+    Ok(unique_worktree_name(name.trim(), |candidate| {
+        base_dir.join(candidate).exists()
+    }))
+}
+
+/// Git refuses to check a branch out in two worktrees; this names the worktree that has it so
+/// the error says where to look.
+fn ensure_branch_not_checked_out(repo: &Repo, branch: &str) -> Result<()> {
+    let worktrees = list_worktrees(repo)?;
+    match worktrees
+        .iter()
+        .find(|worktree| worktree.branch.as_deref() == Some(branch))
+    {
+        Some(worktree) => Err(BbqError::BranchAlreadyCheckedOut {
+            branch: branch.to_string(),
+            worktree: worktree.display_name(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Removes an empty directory left behind by an interrupted create so the name can be reused.
+/// Returns false, leaving the path alone, if it has any contents or is a registered worktree.
+fn remove_leftover_worktree_dir(repo: &Repo, worktree_path: &Path) -> Result<bool> {
+    if !worktree_path.is_dir() || fs::read_dir(worktree_path)?.next().is_some() {
+        return Ok(false);
+    }
+    let target = canonicalize_lenient(worktree_path);
+    let registered = list_worktrees(repo)?
+        .iter()
+        .any(|worktree| canonicalize_lenient(&worktree.path) == target);
+    if registered {
+        return Ok(false);
+    }
+    fs::remove_dir(worktree_path)?;
+    Ok(true)
+}
+
+/// Runs `git worktree add` with `add_args` in two steps. Registering the worktree happens under
+/// the state lock, so two creates can't both claim `name`; checking out its files, the slow part
+/// on a big repo, happens after the lock is released.
+fn add_worktree(
+    repo: &Repo,
+    name: &str,
+    worktree_path: &Path,
+    add_args: Vec<OsString>,
+) -> Result<()> {
+    let mut args = vec![
+        OsString::from("--git-dir"),
+        repo.path.as_os_str().to_os_string(),
+        OsString::from("worktree"),
+        OsString::from("add"),
+        OsString::from("--no-checkout"),
+    ];
+    args.extend(add_args);
+    with_state_lock(|| {
+        if worktree_path.exists() && !remove_leftover_worktree_dir(repo, worktree_path)? {
+            return Err(BbqError::WorktreeAlreadyExists(name.to_string()));
+        }
+        run_git(args)
+    })?;
+
+    let checkout = run_git(vec![
+        OsString::from("-C"),
+        worktree_path.as_os_str().to_os_string(),
+        OsString::from("checkout"),
+        OsString::from("--force"),
+    ]);
+    if checkout.is_err() {
+        let _ = remove_worktree_path(repo, worktree_path, true);
+    }
+    checkout
+}
+
+/// Refuses a target path that would land inside an existing worktree or the bare repo itself,
+/// which git would otherwise accept and leave tangled in the parent's status.
+fn ensure_worktree_path_not_nested(repo: &Repo, worktree_path: &Path) -> Result<()> {
+    let target = canonicalize_lenient(worktree_path);
+    let mut parents = vec![repo.path.clone()];
+    parents.extend(list_worktrees(repo)?.into_iter().map(|worktree| worktree.path));
+    for parent in parents {
+        if target.starts_with(canonicalize_lenient(&parent)) {
+            return Err(BbqError::WorktreePathNested {
+                path: worktree_path.to_path_buf(),
+                parent,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Canonicalizes the longest existing prefix of `path` and re-appends the rest, so paths that
+/// do not exist yet still compare cleanly against resolved ones.
+fn canonicalize_lenient(path: &Path) -> PathBuf {
+    let mut existing = path;
+    let mut rest = Vec::new();
+    loop {
+        if let Ok(resolved) = fs::canonicalize(existing) {
+            return rest.iter().rev().fold(resolved, |acc, part| acc.join(part));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name.to_os_string());
+                existing = parent;
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+}
+
+/// How a new worktree's branch is set up.
+#[derive(Debug, Clone, Default)]
+pub struct WorktreeOptions {
+    /// Leave a new branch without an upstream. By default it tracks the remote branch it starts
+    /// from, or the same-named branch on the primary remote when one exists.
+    pub no_track: bool,
+    /// Start a new branch at this commit instead of the source branch tip. The branch must not
+    /// exist yet.
+    pub at: Option<String>,
+}
+
+impl WorktreeOptions {
+    fn add_args(&self) -> Vec<OsString> {
+        if self.no_track {
+            vec![OsString::from("--no-track")]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
 pub fn create_worktree(repo: &Repo, branch: &str) -> Result<Worktree> {
     create_worktree_with_name(repo, branch, branch)
 }
 
 pub fn create_worktree_with_name(repo: &Repo, name: &str, branch: &str) -> Result<Worktree> {
+    create_worktree_with_name_and_options(repo, name, branch, &WorktreeOptions::default())
+}
+
+/// Checks `branch` out into worktree `name`. A `remote/branch` spec gets a local branch tracking
+/// it; a branch that doesn't exist anywhere is created from `HEAD`.
+pub fn create_worktree_with_name_and_options(
+    repo: &Repo,
+    name: &str,
+    branch: &str,
+    options: &WorktreeOptions,
+) -> Result<Worktree> {
+    if options.at.is_some() {
+        return create_worktree_from_with_options(repo, name, branch, branch, options);
+    }
     ensure_root_dirs()?;
     let name = name.trim();
     if name.is_empty() {
@@ -237,26 +658,24 @@ pub fn create_worktree_with_name(repo: &Repo, name: &str, branch: &str) -> Resul
     fs::create_dir_all(&base_dir)?;
 
     let worktree_path = base_dir.join(name);
-    if worktree_path.exists() {
+    if worktree_path.exists() && !remove_leftover_worktree_dir(repo, &worktree_path)? {
         return Err(BbqError::WorktreeAlreadyExists(name.to_string()));
     }
+    ensure_worktree_path_not_nested(repo, &worktree_path)?;
+    ensure_repo_has_commits(repo)?;
 
     let (branch_name, start_point, upstream) = match parse_remote_branch(repo, &branch_spec)? {
         Some((remote, remote_branch)) => {
-            fetch_repo(repo, Some(&remote))?;
-            let remote_ref = format!("refs/remotes/{remote}/{remote_branch}");
-            if !git_ref_exists(&repo.path, &remote_ref)? {
-                fetch_remote_branch(repo, &remote, &remote_branch)?;
-            }
+            let start_point = remote_start_point(repo, &remote, &remote_branch)?;
             let branch_ref = format!("refs/heads/{remote_branch}");
             let branch_exists = git_ref_exists(&repo.path, &branch_ref)?;
             if branch_exists {
+                ensure_branch_not_checked_out(repo, &remote_branch)?;
                 (remote_branch, None, None)
             } else {
-                let start_point = format!("{remote}/{remote_branch}");
                 (
                     remote_branch.clone(),
-                    Some(start_point.clone()),
+                    Some(start_point),
                     Some(Upstream {
                         remote,
                         branch: remote_branch,
@@ -268,6 +687,7 @@ pub fn create_worktree_with_name(repo: &Repo, name: &str, branch: &str) -> Resul
             let branch_ref = format!("refs/heads/{branch_spec}");
             let branch_exists = git_ref_exists(&repo.path, &branch_ref)?;
             if branch_exists {
+                ensure_branch_not_checked_out(repo, &branch_spec)?;
                 (branch_spec, None, None)
             } else {
                 (branch_spec.clone(), Some("HEAD".to_string()), None)
@@ -275,15 +695,10 @@ pub fn create_worktree_with_name(repo: &Repo, name: &str, branch: &str) -> Resul
         }
     };
 
-    let mut args = vec![
-        OsString::from("--git-dir"),
-        repo.path.as_os_str().to_os_string(),
-        OsString::from("worktree"),
-        OsString::from("add"),
-    ];
-
+    let mut args = Vec::new();
     let created_branch = start_point.is_some();
     if let Some(start_point) = start_point {
+        args.extend(options.add_args());
         args.push(OsString::from("-b"));
         args.push(OsString::from(branch_name.clone()));
         args.push(worktree_path.as_os_str().to_os_string());
@@ -293,12 +708,14 @@ pub fn create_worktree_with_name(repo: &Repo, name: &str, branch: &str) -> Resul
         args.push(OsString::from(branch_name.clone()));
     }
 
-    run_git(args)?;
-    if let Some(upstream) = upstream {
-        set_branch_upstream(repo, &branch_name, &upstream)?;
-    } else if created_branch {
-        if let Some(upstream) = origin_upstream_if_present(repo, &branch_name)? {
+    add_worktree(repo, name, &worktree_path, args)?;
+    if !options.no_track {
+        if let Some(upstream) = upstream {
             set_branch_upstream(repo, &branch_name, &upstream)?;
+        } else if created_branch {
+            if let Some(upstream) = primary_upstream_if_present(repo, &branch_name)? {
+                set_branch_upstream(repo, &branch_name, &upstream)?;
+            }
         }
     }
 
@@ -314,6 +731,19 @@ pub fn create_worktree_from(
     name: &str,
     branch: &str,
     source_branch: &str,
+) -> Result<Worktree> {
+    let options = WorktreeOptions::default();
+    create_worktree_from_with_options(repo, name, branch, source_branch, &options)
+}
+
+/// Creates worktree `name` on `branch`, starting it from `source_branch` when it doesn't exist
+/// yet (or from `options.at`).
+pub fn create_worktree_from_with_options(
+    repo: &Repo,
+    name: &str,
+    branch: &str,
+    source_branch: &str,
+    options: &WorktreeOptions,
 ) -> Result<Worktree> {
     ensure_root_dirs()?;
     let name = name.trim();
@@ -333,16 +763,24 @@ pub fn create_worktree_from(
     fs::create_dir_all(&base_dir)?;
 
     let worktree_path = base_dir.join(name);
-    if worktree_path.exists() {
+    if worktree_path.exists() && !remove_leftover_worktree_dir(repo, &worktree_path)? {
         return Err(BbqError::WorktreeAlreadyExists(name.to_string()));
     }
+    ensure_worktree_path_not_nested(repo, &worktree_path)?;
+    ensure_repo_has_commits(repo)?;
 
-    fetch_origin_if_present(repo)?;
+    fetch_primary_remote_if_present(repo)?;
 
     let branch_ref = format!("refs/heads/{branch}");
     let branch_exists = git_ref_exists(&repo.path, &branch_ref)?;
 
-    let (start_point, upstream) = if branch_exists {
+    let (start_point, upstream) = if let Some(commit) = options.at.as_deref() {
+        if branch_exists {
+            return Err(BbqError::BranchAlreadyExists(branch.to_string()));
+        }
+        (Some(resolve_commit(repo, commit)?), None)
+    } else if branch_exists {
+        ensure_branch_not_checked_out(repo, branch)?;
         let upstream = if branch == source_branch && !branch_has_upstream(repo, branch)? {
             resolve_source_branch(repo, source_branch)?.upstream
         } else {
@@ -351,18 +789,22 @@ pub fn create_worktree_from(
         (None, upstream)
     } else {
         let resolved = resolve_source_branch(repo, source_branch)?;
+        if matches!(
+            resolve_commit(repo, &resolved.start_point),
+            Err(BbqError::CommitNotFound(_))
+        ) {
+            return Err(BbqError::SourceBranchNotFound {
+                branch: source_branch.to_string(),
+                suggestions: branch_suggestions(repo, source_branch)?,
+            });
+        }
         (Some(resolved.start_point), resolved.upstream)
     };
 
-    let mut args = vec![
-        OsString::from("--git-dir"),
-        repo.path.as_os_str().to_os_string(),
-        OsString::from("worktree"),
-        OsString::from("add"),
-    ];
-
+    let mut args = Vec::new();
     let created_branch = start_point.is_some();
     if let Some(start_point) = start_point {
+        args.extend(options.add_args());
         args.push(OsString::from("-b"));
         args.push(OsString::from(branch));
         args.push(worktree_path.as_os_str().to_os_string());
@@ -372,15 +814,17 @@ pub fn create_worktree_from(
         args.push(OsString::from(branch));
     }
 
-    run_git(args)?;
-    if created_branch {
-        if let Some(upstream) = origin_upstream_if_present(repo, branch)? {
-            set_branch_upstream(repo, branch, &upstream)?;
+    add_worktree(repo, name, &worktree_path, args)?;
+    if !options.no_track {
+        if created_branch {
+            if let Some(upstream) = primary_upstream_if_present(repo, branch)? {
+                set_branch_upstream(repo, branch, &upstream)?;
+            } else if let Some(upstream) = upstream {
+                set_branch_upstream(repo, branch, &upstream)?;
+            }
         } else if let Some(upstream) = upstream {
             set_branch_upstream(repo, branch, &upstream)?;
         }
-    } else if let Some(upstream) = upstream {
-        set_branch_upstream(repo, branch, &upstream)?;
     }
 
     Ok(Worktree {
@@ -390,6 +834,75 @@ pub fn create_worktree_from(
     })
 }
 
+const MAX_BRANCH_SUGGESTIONS: usize = 3;
+
+/// Local and remote-tracking branches that look like a typo of `name`, closest first.
+fn branch_suggestions(repo: &Repo, name: &str) -> Result<Vec<String>> {
+    let branches = list_branches(repo)?;
+    let branches: Vec<&str> = branches.iter().map(String::as_str).collect();
+    Ok(closest_names(name, &branches, MAX_BRANCH_SUGGESTIONS))
+}
+
+fn closest_names(target: &str, candidates: &[&str], limit: usize) -> Vec<String> {
+    let target = target.to_ascii_lowercase();
+    let max_distance = (target.chars().count() / 3).max(2);
+    let mut scored: Vec<(usize, &str)> = candidates
+        .iter()
+        .filter_map(|candidate| {
+            let lower = candidate.to_ascii_lowercase();
+            // Compare against the branch without its remote prefix too, so `mian` finds
+            // `origin/main`.
+            let tail = lower.split_once('/').map(|(_, tail)| tail).unwrap_or(&lower);
+            let distance = edit_distance(&target, &lower).min(edit_distance(&target, tail));
+            (distance <= max_distance).then_some((distance, *candidate))
+        })
+        .collect();
+    scored.sort();
+    scored.dedup_by(|a, b| a.1 == b.1);
+    scored
+        .into_iter()
+        .take(limit)
+        .map(|(_, name)| name.to_string())
+        .collect()
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_ch) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_ch) in b.iter().enumerate() {
+            let cost = usize::from(a_ch != *b_ch);
+            current[j + 1] = (previous[j] + cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+fn resolve_commit(repo: &Repo, commit: &str) -> Result<String> {
+    let commit = commit.trim();
+    if commit.is_empty() || commit.starts_with('-') {
+        return Err(BbqError::CommitNotFound(commit.to_string()));
+    }
+
+    let args = vec![
+        OsString::from("--git-dir"),
+        repo.path.as_os_str().to_os_string(),
+        OsString::from("rev-parse"),
+        OsString::from("--verify"),
+        OsString::from("--quiet"),
+        OsString::from(format!("{commit}^{{commit}}")),
+    ];
+    match run_git_capture(args) {
+        Ok(output) => Ok(output.trim().to_string()),
+        Err(BbqError::GitCommand { .. }) => Err(BbqError::CommitNotFound(commit.to_string())),
+        Err(err) => Err(err),
+    }
+}
+
 fn fetch_repo(repo: &Repo, remote: Option<&str>) -> Result<()> {
     let mut args = vec![
         OsString::from("--git-dir"),
@@ -398,8 +911,59 @@ fn fetch_repo(repo: &Repo, remote: Option<&str>) -> Result<()> {
     ];
     if let Some(remote) = remote {
         args.push(OsString::from(remote));
+        if is_mirror_remote(repo, remote)? {
+            // An empty `--refmap` keeps the configured refspec from updating branches as well.
+            args.push(OsString::from("--refmap="));
+            args.push(OsString::from(format!("+refs/heads/*:refs/remotes/{remote}/*")));
+        }
     }
-    run_git(args)
+    with_network_retries(|| run_git(args.clone()))
+}
+
+/// Whether `remote` was set up by `clone --mirror`. Its `+refs/*:refs/*` refspec would
+/// overwrite (or refuse to touch) branches checked out in worktrees, so bbq fetches mirrors into
+/// remote-tracking refs instead.
+fn is_mirror_remote(repo: &Repo, remote: &str) -> Result<bool> {
+    let args = vec![
+        OsString::from("--git-dir"),
+        repo.path.as_os_str().to_os_string(),
+        OsString::from("config"),
+        OsString::from("--get-all"),
+        OsString::from(format!("remote.{remote}.fetch")),
+    ];
+    let output = git_output(&args)?;
+    Ok(output.status.success()
+        && String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .any(|line| line.trim() == "+refs/*:refs/*"))
+}
+
+/// Fetches every remote of a repo, adding remote-tracking refspecs where a bare clone lacks them.
+pub fn fetch_all(repo: &Repo) -> Result<()> {
+    let remotes = list_remotes(repo)?;
+    if remotes.is_empty() {
+        return Ok(());
+    }
+    let mut mirror = false;
+    for remote in &remotes {
+        ensure_remote_fetchspec(repo, remote)?;
+        mirror |= is_mirror_remote(repo, remote)?;
+    }
+    if mirror {
+        // `--all` would use the mirror refspec; fetch each remote into tracking refs instead.
+        return remotes
+            .iter()
+            .try_for_each(|remote| fetch_repo(repo, Some(remote)));
+    }
+
+    let args = vec![
+        OsString::from("--git-dir"),
+        repo.path.as_os_str().to_os_string(),
+        OsString::from("fetch"),
+        OsString::from("--all"),
+        OsString::from("--quiet"),
+    ];
+    with_network_retries(|| run_git(args.clone()))
 }
 
 fn ensure_remote_fetchspec(repo: &Repo, remote: &str) -> Result<()> {
@@ -415,7 +979,7 @@ fn ensure_remote_fetchspec(repo: &Repo, remote: &str) -> Result<()> {
         OsString::from("--get-all"),
         OsString::from(key.clone()),
     ];
-    let output = git_command().args(&args).output()?;
+    let output = git_output(&args)?;
     let needle = format!("refs/remotes/{remote}/");
     let has_tracking = output.status.success()
         && String::from_utf8_lossy(&output.stdout)
@@ -438,14 +1002,38 @@ fn ensure_remote_fetchspec(repo: &Repo, remote: &str) -> Result<()> {
     Ok(())
 }
 
-fn fetch_origin_if_present(repo: &Repo) -> Result<()> {
-    if has_remote(repo, "origin")? {
-        ensure_remote_fetchspec(repo, "origin")?;
-        fetch_repo(repo, Some("origin"))?;
+/// Opportunistic refresh before creating a worktree. Offline, the refs already on disk are used
+/// as they are.
+fn fetch_primary_remote_if_present(repo: &Repo) -> Result<()> {
+    if offline_enabled() {
+        return Ok(());
+    }
+    if let Some(remote) = primary_remote(repo)? {
+        ensure_remote_fetchspec(repo, &remote)?;
+        fetch_repo(repo, Some(&remote))?;
     }
     Ok(())
 }
 
+/// Fetches `remote` and returns `<remote>/<branch>` as a start point. Offline nothing is fetched:
+/// an existing remote-tracking ref is used as it is, and failing that the bare clone's own copy
+/// of the branch stands in. Only a branch missing from both still needs the network.
+fn remote_start_point(repo: &Repo, remote: &str, branch: &str) -> Result<String> {
+    let offline = offline_enabled();
+    if !offline {
+        fetch_repo(repo, Some(remote))?;
+    }
+    let remote_ref = format!("refs/remotes/{remote}/{branch}");
+    if git_ref_exists(&repo.path, &remote_ref)? {
+        return Ok(format!("{remote}/{branch}"));
+    }
+    if offline && git_ref_exists(&repo.path, &format!("refs/heads/{branch}"))? {
+        return Ok(branch.to_string());
+    }
+    fetch_remote_branch(repo, remote, branch)?;
+    Ok(format!("{remote}/{branch}"))
+}
+
 fn list_remotes(repo: &Repo) -> Result<Vec<String>> {
     let args = vec![
         OsString::from("--git-dir"),
@@ -486,10 +1074,6 @@ fn parse_remote_branch(repo: &Repo, branch: &str) -> Result<Option<(String, Stri
     }
 }
 
-fn origin_branch_exists(repo: &Repo, branch: &str) -> Result<bool> {
-    let origin_ref = format!("refs/remotes/origin/{branch}");
-    git_ref_exists(&repo.path, &origin_ref)
-}
 
 struct Upstream {
     remote: String,
@@ -509,30 +1093,31 @@ fn resolve_source_branch(repo: &Repo, source_branch: &str) -> Result<ResolvedSou
         });
     }
 
-    if origin_branch_exists(repo, source_branch)? {
-        let origin_ref = format!("refs/remotes/origin/{source_branch}");
-        if !git_ref_exists(&repo.path, &origin_ref)? {
-            fetch_remote_branch(repo, "origin", source_branch)?;
-        }
-        let start_point = format!("origin/{source_branch}");
+    if let Some(start_point) = worktree_start_point(repo, source_branch)? {
         return Ok(ResolvedSourceBranch {
-            start_point: start_point.clone(),
-            upstream: Some(Upstream {
-                remote: "origin".to_string(),
-                branch: source_branch.to_string(),
-            }),
+            start_point,
+            upstream: None,
         });
     }
 
-    if let Some((remote, remote_branch)) = parse_remote_branch(repo, source_branch)? {
-        fetch_repo(repo, Some(&remote))?;
-        let remote_ref = format!("refs/remotes/{remote}/{remote_branch}");
-        if !git_ref_exists(&repo.path, &remote_ref)? {
-            fetch_remote_branch(repo, &remote, &remote_branch)?;
+    if let Some(remote) = primary_remote(repo)? {
+        let remote_ref = format!("refs/remotes/{remote}/{source_branch}");
+        if git_ref_exists(&repo.path, &remote_ref)? {
+            let start_point = format!("{remote}/{source_branch}");
+            return Ok(ResolvedSourceBranch {
+                start_point,
+                upstream: Some(Upstream {
+                    remote,
+                    branch: source_branch.to_string(),
+                }),
+            });
         }
-        let start_point = format!("{remote}/{remote_branch}");
+    }
+
+    if let Some((remote, remote_branch)) = parse_remote_branch(repo, source_branch)? {
+        let start_point = remote_start_point(repo, &remote, &remote_branch)?;
         return Ok(ResolvedSourceBranch {
-            start_point: start_point.clone(),
+            start_point,
             upstream: Some(Upstream {
                 remote,
                 branch: remote_branch,
@@ -546,15 +1131,23 @@ fn resolve_source_branch(repo: &Repo, source_branch: &str) -> Result<ResolvedSou
     })
 }
 
-fn origin_upstream_if_present(repo: &Repo, branch: &str) -> Result<Option<Upstream>> {
-    if has_remote(repo, "origin")? {
-        Ok(Some(Upstream {
-            remote: "origin".to_string(),
-            branch: branch.to_string(),
-        }))
-    } else {
-        Ok(None)
+/// Lets a source name another worktree, branching from whatever it has checked out (local
+/// commits included). A local branch with the same name takes precedence.
+fn worktree_start_point(repo: &Repo, source: &str) -> Result<Option<String>> {
+    if branch_exists(repo, source)? {
+        return Ok(None);
     }
+    let worktree = list_worktrees(repo)?
+        .into_iter()
+        .find(|worktree| worktree.display_name() == source);
+    Ok(worktree.and_then(|worktree| worktree.branch.or(worktree.head)))
+}
+
+fn primary_upstream_if_present(repo: &Repo, branch: &str) -> Result<Option<Upstream>> {
+    Ok(primary_remote(repo)?.map(|remote| Upstream {
+        remote,
+        branch: branch.to_string(),
+    }))
 }
 
 pub fn remove_worktree(repo: &Repo, name: &str) -> Result<()> {
@@ -562,12 +1155,11 @@ pub fn remove_worktree(repo: &Repo, name: &str) -> Result<()> {
 }
 
 pub fn remove_worktree_with_force(repo: &Repo, name: &str, force: bool) -> Result<()> {
-    let worktrees = list_worktrees(repo)?;
-    let worktree = worktrees
-        .into_iter()
-        .find(|item| worktree_matches_name(item, name))
-        .ok_or_else(|| BbqError::WorktreeNotFound(name.to_string()))?;
+    let worktree = find_worktree(repo, name)?;
+    remove_worktree_path(repo, &worktree.path, force)
+}
 
+fn remove_worktree_path(repo: &Repo, worktree_path: &Path, force: bool) -> Result<()> {
     let mut args = vec![
         OsString::from("--git-dir"),
         repo.path.as_os_str().to_os_string(),
@@ -578,11 +1170,308 @@ pub fn remove_worktree_with_force(repo: &Repo, name: &str, force: bool) -> Resul
     if force {
         args.push(OsString::from("--force"));
     }
-    args.push(worktree.path.as_os_str().to_os_string());
+    args.push(worktree_path.as_os_str().to_os_string());
+
+    with_state_lock(|| run_git(args))
+}
+
+/// Reconnects a repo with its worktree directories after they were moved together, for
+/// example by `bbq migrate`. Every directory under `<worktrees>/<repo>` is passed to
+/// `git worktree repair`, which rewrites the links in both directions.
+pub fn repair_worktrees(repo: &Repo) -> Result<()> {
+    let base_dir = worktrees_root()?.join(&repo.name);
+    let Ok(entries) = fs::read_dir(&base_dir) else {
+        return Ok(());
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.join(".git").is_file())
+        .collect();
+    if paths.is_empty() {
+        return Ok(());
+    }
+    paths.sort();
 
+    let mut args = vec![
+        OsString::from("--git-dir"),
+        repo.path.as_os_str().to_os_string(),
+        OsString::from("worktree"),
+        OsString::from("repair"),
+    ];
+    args.extend(paths.into_iter().map(PathBuf::into_os_string));
+    run_git(args)
+}
+
+pub fn rename_branch(worktree: &Worktree, new_branch: &str) -> Result<()> {
+    let new_branch = new_branch.trim();
+    if validate_branch_name(new_branch).is_err() {
+        return Err(BbqError::InvalidBranchName);
+    }
+    if worktree.branch.is_none() {
+        return Err(BbqError::DetachedHead(worktree.display_name()));
+    }
+
+    let args = vec![
+        OsString::from("-C"),
+        worktree.path.as_os_str().to_os_string(),
+        OsString::from("branch"),
+        OsString::from("-m"),
+        OsString::from(new_branch),
+    ];
     run_git(args)
 }
 
+/// `git diff` of a worktree against `HEAD`, or of the index with `staged`. `stat` returns the
+/// `--stat` summary instead of the full patch.
+pub fn worktree_diff(worktree: &Worktree, stat: bool, staged: bool) -> Result<String> {
+    let mut args = vec![
+        OsString::from("-C"),
+        worktree.path.as_os_str().to_os_string(),
+        OsString::from("diff"),
+    ];
+    if stat {
+        args.push(OsString::from("--stat"));
+    }
+    if staged {
+        args.push(OsString::from("--cached"));
+    } else {
+        args.push(OsString::from("HEAD"));
+    }
+    args.push(OsString::from("--"));
+    run_git_capture(args)
+}
+
+/// Build output and dependency directories `copy_untracked_files` leaves behind; they are large
+/// and each worktree rebuilds its own.
+const SKIPPED_UNTRACKED_DIRS: &[&str] = &[
+    "target",
+    "node_modules",
+    ".venv",
+    "venv",
+    "__pycache__",
+    ".gradle",
+    ".next",
+    "dist",
+    "build",
+];
+
+/// Copies untracked and ignored files (local `.env`, editor config, ...) from `source` into
+/// `dest`, skipping `SKIPPED_UNTRACKED_DIRS` and nested repositories. Anything already present
+/// in `dest`, including tracked files, is left alone. Returns the number of files copied.
+pub fn copy_untracked_files(source: &Worktree, dest: &Worktree) -> Result<usize> {
+    let args = vec![
+        OsString::from("-C"),
+        source.path.as_os_str().to_os_string(),
+        OsString::from("ls-files"),
+        OsString::from("--others"),
+        OsString::from("--directory"),
+        OsString::from("-z"),
+    ];
+    let output = run_git_capture(args)?;
+    let mut copied = 0;
+    // `--directory` lists a wholly untracked directory as `dir/` rather than every file in it,
+    // so skipped directories are never walked.
+    for entry in output.split('\0').filter(|entry| !entry.is_empty()) {
+        let relative = Path::new(entry.trim_end_matches('/'));
+        if entry.ends_with('/') {
+            copy_untracked_dir(&source.path, &dest.path, relative, &mut copied)?;
+        } else {
+            copy_untracked_file(&source.path, &dest.path, relative, &mut copied)?;
+        }
+    }
+    Ok(copied)
+}
+
+fn copy_untracked_dir(
+    source: &Path,
+    dest: &Path,
+    relative: &Path,
+    copied: &mut usize,
+) -> Result<()> {
+    let from = source.join(relative);
+    let skipped = relative
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| SKIPPED_UNTRACKED_DIRS.contains(&name));
+    if skipped
+        || from.join(".git").exists()
+        || !fs::symlink_metadata(&from).is_ok_and(|meta| meta.is_dir())
+    {
+        return Ok(());
+    }
+    for entry in fs::read_dir(&from)? {
+        let entry = entry?;
+        let relative = relative.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_untracked_dir(source, dest, &relative, copied)?;
+        } else {
+            copy_untracked_file(source, dest, &relative, copied)?;
+        }
+    }
+    Ok(())
+}
+
+fn copy_untracked_file(
+    source: &Path,
+    dest: &Path,
+    relative: &Path,
+    copied: &mut usize,
+) -> Result<()> {
+    let from = source.join(relative);
+    let to = dest.join(relative);
+    if !fs::symlink_metadata(&from).is_ok_and(|meta| meta.is_file())
+        || fs::symlink_metadata(&to).is_ok()
+    {
+        return Ok(());
+    }
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::copy(&from, &to)?;
+    *copied += 1;
+    Ok(())
+}
+
+pub fn head_commit_info(path: &Path) -> Option<CommitInfo> {
+    let args = vec![
+        OsString::from("-C"),
+        path.as_os_str().to_os_string(),
+        OsString::from("log"),
+        OsString::from("-1"),
+        OsString::from("--format=%an%n%s"),
+    ];
+    let stdout = run_git_capture(args).ok()?;
+    let mut lines = stdout.lines();
+    let author = lines.next().unwrap_or("").trim().to_string();
+    let message = lines.next().unwrap_or("").trim().to_string();
+    if author.is_empty() && message.is_empty() {
+        return None;
+    }
+
+    Some(CommitInfo { author, message })
+}
+
+/// Runs `git config --worktree <key> <value>` inside the worktree, so the value applies to that
+/// worktree only instead of landing in the config every worktree of the bare repo shares.
+pub fn set_worktree_config(worktree: &Worktree, key: &str, value: &str) -> Result<()> {
+    enable_worktree_config(&worktree.path)?;
+    run_git(vec![
+        OsString::from("-C"),
+        worktree.path.as_os_str().to_os_string(),
+        OsString::from("config"),
+        OsString::from("--worktree"),
+        OsString::from(key),
+        OsString::from(value),
+    ])
+}
+
+/// Turns on `extensions.worktreeConfig` for the repo `path` belongs to. As git's docs require,
+/// the bare repo's `core.bare = true` moves to its own `config.worktree` first, since linked
+/// worktrees would otherwise read it from the shared config.
+fn enable_worktree_config(path: &Path) -> Result<()> {
+    let common_dir = run_git_capture(vec![
+        OsString::from("-C"),
+        path.as_os_str().to_os_string(),
+        OsString::from("rev-parse"),
+        OsString::from("--git-common-dir"),
+    ])?;
+    let common_dir = path.join(common_dir.trim());
+    let shared = common_dir.join("config");
+    if config_file_value(&shared, "extensions.worktreeConfig")?.as_deref() == Some("true") {
+        return Ok(());
+    }
+    let config_file = |file: &Path, args: &[&str]| {
+        let mut command = vec![
+            OsString::from("config"),
+            OsString::from("--file"),
+            file.as_os_str().to_os_string(),
+        ];
+        command.extend(args.iter().map(OsString::from));
+        run_git(command)
+    };
+    if config_file_value(&shared, "core.bare")?.as_deref() == Some("true") {
+        config_file(&common_dir.join("config.worktree"), &["core.bare", "true"])?;
+        config_file(&shared, &["--unset", "core.bare"])?;
+    }
+    config_file(&shared, &["extensions.worktreeConfig", "true"])
+}
+
+fn config_file_value(file: &Path, key: &str) -> Result<Option<String>> {
+    let output = git_output(&[
+        OsString::from("config"),
+        OsString::from("--file"),
+        file.as_os_str().to_os_string(),
+        OsString::from("--get"),
+        OsString::from(key),
+    ])?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    Ok(Some(String::from_utf8_lossy(&output.stdout).trim().to_string()))
+}
+
+/// Committer date of the HEAD commit checked out at `path`.
+pub fn head_commit_time(path: &Path) -> Option<SystemTime> {
+    let args = vec![
+        OsString::from("-C"),
+        path.as_os_str().to_os_string(),
+        OsString::from("log"),
+        OsString::from("-1"),
+        OsString::from("--format=%ct"),
+    ];
+    let seconds: u64 = run_git_capture(args).ok()?.trim().parse().ok()?;
+    Some(UNIX_EPOCH + Duration::from_secs(seconds))
+}
+
+/// Existing repo whose primary remote points at the same place as `source`, compared after
+/// normalizing away protocol, user, `.git`, and trailing slashes.
+pub fn find_repo_by_remote(source: &str) -> Result<Option<Repo>> {
+    let wanted = normalize_remote_url(source);
+    if wanted.is_empty() {
+        return Ok(None);
+    }
+    for repo in list_repos()? {
+        if let Some((_, url)) = repo_remote_url(&repo)? {
+            if normalize_remote_url(&url) == wanted {
+                return Ok(Some(repo));
+            }
+        }
+    }
+    Ok(None)
+}
+
+fn normalize_remote_url(source: &str) -> String {
+    let trimmed = source.trim();
+    if let Some(slug) = github_slug_from_source(trimmed) {
+        return format!("github.com/{}", slug.to_ascii_lowercase());
+    }
+    if !looks_like_url_or_ssh(trimmed) {
+        let path = trimmed.strip_prefix("file://").unwrap_or(trimmed);
+        let path = fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path));
+        let path = path.to_string_lossy();
+        return path.trim_end_matches('/').trim_end_matches(".git").to_string();
+    }
+
+    let rest = match trimmed.split_once("://") {
+        Some((_, rest)) => rest.to_string(),
+        // scp-style `user@host:owner/repo`
+        None => trimmed.replacen(':', "/", 1),
+    };
+    let rest = match rest.split_once('@') {
+        Some((user, host_path)) if !user.contains('/') => host_path,
+        _ => rest.as_str(),
+    };
+    let rest = rest.trim_end_matches('/').trim_end_matches(".git");
+    match rest.split_once('/') {
+        // GitHub owner and repo names are case-insensitive, so they compare like the slug form.
+        Some((host, path)) if host.eq_ignore_ascii_case("github.com") => {
+            format!("github.com/{}", path.to_ascii_lowercase())
+        }
+        Some((host, path)) => format!("{}/{}", host.to_ascii_lowercase(), path),
+        None => rest.to_ascii_lowercase(),
+    }
+}
+
 pub fn remove_repo(name: &str) -> Result<()> {
     let repo = resolve_repo(name)?;
     let worktrees = list_worktrees(&repo)?;
@@ -594,6 +1483,79 @@ pub fn remove_repo(name: &str) -> Result<()> {
     Ok(())
 }
 
+/// Fetch URL of the repo's primary remote (`origin`, or the first remote when it was cloned
+/// with `--origin`).
+pub fn repo_remote_url(repo: &Repo) -> Result<Option<(String, String)>> {
+    let Some(remote) = primary_remote(repo)? else {
+        return Ok(None);
+    };
+    let args = vec![
+        OsString::from("--git-dir"),
+        repo.path.as_os_str().to_os_string(),
+        OsString::from("config"),
+        OsString::from("--get"),
+        OsString::from(format!("remote.{remote}.url")),
+    ];
+    let output = git_output(&args)?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    let url = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if url.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some((remote, url)))
+}
+
+/// Replaces a repo's bare clone with a fresh one from its remote, for recovering from a
+/// corrupted repo. The old clone is moved aside until the new clone succeeds and restored if
+/// it fails.
+pub fn reclone_repo(name: &str) -> Result<Repo> {
+    let repo = resolve_repo(name)?;
+    if repo_has_worktrees(&repo)? {
+        return Err(BbqError::RepoHasWorktrees);
+    }
+    let Some((remote, url)) = repo_remote_url(&repo)? else {
+        return Err(BbqError::RepoRemoteMissing(repo.name));
+    };
+
+    let backup = repos_root()?.join(format!(".{}.git.reclone", repo.name));
+    if backup.exists() {
+        fs::remove_dir_all(&backup)?;
+    }
+    fs::rename(&repo.path, &backup)?;
+
+    let options = CloneOptions {
+        origin: (remote != "origin").then_some(remote),
+        ..CloneOptions::default()
+    };
+    match checkout_repo_internal(&url, Some(&repo.name), &options) {
+        Ok(recloned) => {
+            fs::remove_dir_all(&backup)?;
+            Ok(recloned)
+        }
+        Err(err) => {
+            if !repo.path.exists() {
+                fs::rename(&backup, &repo.path)?;
+            }
+            Err(err)
+        }
+    }
+}
+
+/// A repo that needs recloning may be too broken for `git worktree list`, so fall back to
+/// git's own `worktrees/` admin dir when listing fails.
+fn repo_has_worktrees(repo: &Repo) -> Result<bool> {
+    match list_worktrees(repo) {
+        Ok(worktrees) => Ok(!worktrees.is_empty()),
+        Err(_) => match fs::read_dir(repo.path.join("worktrees")) {
+            Ok(mut entries) => Ok(entries.next().is_some()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(err.into()),
+        },
+    }
+}
+
 pub fn resolve_repo(name: &str) -> Result<Repo> {
     let mut name = sanitize_name(name);
     if name.is_empty() {
@@ -603,9 +1565,23 @@ pub fn resolve_repo(name: &str) -> Result<Repo> {
     if name.ends_with(".git") {
         name = name.trim_end_matches(".git").to_string();
     }
+    if let Some(target) = repo_alias(&name) {
+        name = sanitize_name(&target).trim_end_matches(".git").to_string();
+    }
 
     let path = repos_root()?.join(format!("{name}.git"));
     if !path.exists() {
+        if fuzzy_match_enabled() {
+            let repos = list_repos()?;
+            let names = repos.iter().map(|repo| repo.name.as_str());
+            match fuzzy_matches(&name, names).as_slice() {
+                [] => {}
+                [only] => return resolve_repo(only),
+                candidates => {
+                    return Err(BbqError::AmbiguousRepoName(ambiguous_hint(&name, candidates)))
+                }
+            }
+        }
         return Err(BbqError::RepoNotFound(name));
     }
 
@@ -622,7 +1598,7 @@ fn git_ref_exists(repo_path: &Path, reference: &str) -> Result<bool> {
         OsString::from(reference),
     ];
 
-    let output = git_command().args(&args).output()?;
+    let output = git_output(&args)?;
     Ok(output.status.success())
 }
 
@@ -659,20 +1635,23 @@ fn branch_has_upstream(repo: &Repo, branch: &str) -> Result<bool> {
         OsString::from("--symbolic-full-name"),
         OsString::from(upstream_spec),
     ];
-    let output = git_command().args(&args).output()?;
+    let output = git_output(&args)?;
     Ok(output.status.success())
 }
 
 fn fetch_remote_branch(repo: &Repo, remote: &str, branch: &str) -> Result<()> {
     let refspec = format!("refs/heads/{branch}:refs/remotes/{remote}/{branch}");
-    let args = vec![
+    let mut args = vec![
         OsString::from("--git-dir"),
         repo.path.as_os_str().to_os_string(),
         OsString::from("fetch"),
         OsString::from(remote),
-        OsString::from(refspec),
     ];
-    run_git(args)
+    if is_mirror_remote(repo, remote)? {
+        args.push(OsString::from("--refmap="));
+    }
+    args.push(OsString::from(refspec));
+    with_network_retries(|| run_git(args.clone()))
 }
 
 fn parse_worktrees(output: &str, repo_path: &Path) -> Vec<Worktree> {
@@ -716,17 +1695,83 @@ fn parse_worktrees(output: &str, repo_path: &Path) -> Vec<Worktree> {
         worktrees.push(worktree);
     }
 
-    worktrees.sort_by(|a, b| a.display_name().cmp(&b.display_name()));
+    worktrees.sort_by_key(|a| a.display_name());
     worktrees
 }
 
-fn worktree_matches_name(worktree: &Worktree, name: &str) -> bool {
-    worktree.display_name() == name
-        || worktree
-            .branch
-            .as_deref()
-            .map(|branch| branch == name)
-            .unwrap_or(false)
+/// Finds a worktree by directory name or branch. Exact matches win; otherwise a single
+/// case-insensitive match is accepted so names typed with different casing still resolve on
+/// case-insensitive filesystems.
+pub fn find_worktree(repo: &Repo, name: &str) -> Result<Worktree> {
+    let worktrees = list_worktrees(repo)?;
+    if let Some(worktree) = worktrees
+        .iter()
+        .find(|item| worktree_matches_name(item, name, false))
+    {
+        return Ok(worktree.clone());
+    }
+
+    let mut matches = worktrees
+        .iter()
+        .filter(|item| worktree_matches_name(item, name, true));
+    match (matches.next(), matches.next()) {
+        (Some(worktree), None) => return Ok(worktree.clone()),
+        (Some(_), Some(_)) => return Err(BbqError::AmbiguousWorktreeName(name.to_string())),
+        _ => {}
+    }
+
+    if fuzzy_match_enabled() {
+        let names: Vec<String> = worktrees.iter().map(Worktree::display_name).collect();
+        match fuzzy_matches(name, names.iter().map(String::as_str)).as_slice() {
+            [] => {}
+            [only] => {
+                if let Some(worktree) = worktrees.iter().find(|item| &item.display_name() == only) {
+                    return Ok(worktree.clone());
+                }
+            }
+            candidates => {
+                return Err(BbqError::AmbiguousWorktreeName(ambiguous_hint(name, candidates)))
+            }
+        }
+    }
+    Err(BbqError::WorktreeNotFound(name.to_string()))
+}
+
+/// Names that start with `query`, or failing that contain it, ignoring case.
+fn fuzzy_matches<'a>(query: &str, names: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let query = query.to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let names: Vec<(&str, String)> = names.map(|name| (name, name.to_lowercase())).collect();
+    let prefixed: Vec<String> = names
+        .iter()
+        .filter(|(_, lower)| lower.starts_with(&query))
+        .map(|(name, _)| name.to_string())
+        .collect();
+    if !prefixed.is_empty() {
+        return prefixed;
+    }
+    names
+        .iter()
+        .filter(|(_, lower)| lower.contains(&query))
+        .map(|(name, _)| name.to_string())
+        .collect()
+}
+
+fn ambiguous_hint(name: &str, candidates: &[String]) -> String {
+    format!("{name} (matches {})", candidates.join(", "))
+}
+
+fn worktree_matches_name(worktree: &Worktree, name: &str, ignore_case: bool) -> bool {
+    let matches = |value: &str| {
+        if ignore_case {
+            value.eq_ignore_ascii_case(name)
+        } else {
+            value == name
+        }
+    };
+    matches(&worktree.display_name()) || worktree.branch.as_deref().map(matches).unwrap_or(false)
 }
 
 #[derive(Default)]
@@ -847,7 +1892,21 @@ fn sanitize_name(raw: &str) -> String {
 }
 
 fn run_git(args: Vec<OsString>) -> Result<()> {
-    let output = git_command().args(&args).output()?;
+    let output = git_output(&args)?;
+    if output.status.success() {
+        return Ok(());
+    }
+
+    Err(BbqError::GitCommand {
+        command: format!("git {}", args_to_string(&args)),
+        stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+    })
+}
+
+/// For clones: `abort_running_command` can kill it from another thread.
+fn run_git_abortable(args: Vec<OsString>) -> Result<()> {
+    trace_command("git", &args);
+    let output = abortable_output(git_command().args(&args))?;
     if output.status.success() {
         return Ok(());
     }
@@ -858,13 +1917,12 @@ fn run_git(args: Vec<OsString>) -> Result<()> {
     })
 }
 
+/// Only used for clones, so it is abortable like `run_git_abortable`.
 fn run_gh(args: Vec<OsString>) -> Result<()> {
-    let output = gh_command().args(&args).output().map_err(|err| {
-        if err.kind() == io::ErrorKind::NotFound {
-            BbqError::GitHubCliMissing
-        } else {
-            BbqError::Io(err)
-        }
+    trace_command("gh", &args);
+    let output = abortable_output(gh_command().args(&args)).map_err(|err| match err {
+        BbqError::Io(err) if err.kind() == io::ErrorKind::NotFound => BbqError::GitHubCliMissing,
+        err => err,
     })?;
     if output.status.success() {
         return Ok(());
@@ -877,7 +1935,7 @@ fn run_gh(args: Vec<OsString>) -> Result<()> {
 }
 
 fn run_git_capture(args: Vec<OsString>) -> Result<String> {
-    let output = git_command().args(&args).output()?;
+    let output = git_output(&args)?;
     if output.status.success() {
         return Ok(String::from_utf8_lossy(&output.stdout).to_string());
     }
@@ -888,6 +1946,69 @@ fn run_git_capture(args: Vec<OsString>) -> Result<String> {
     })
 }
 
+const DEFAULT_NETWORK_RETRIES: u32 = 2;
+const NETWORK_RETRY_BASE_DELAY_MS: u64 = 250;
+const TRANSIENT_NETWORK_ERRORS: [&str; 6] = [
+    "could not resolve host",
+    "connection reset",
+    "connection refused",
+    "timed out",
+    "the remote end hung up unexpectedly",
+    "early eof",
+];
+
+fn commit_graph_enabled() -> bool {
+    config_value("commit_graph")
+        .map(|value| !matches!(value.trim().to_ascii_lowercase().as_str(), "false" | "0" | "no"))
+        .unwrap_or(true)
+}
+
+fn fuzzy_match_enabled() -> bool {
+    config_value("fuzzy_match")
+        .map(|value| matches!(value.trim().to_ascii_lowercase().as_str(), "true" | "1" | "yes"))
+        .unwrap_or(false)
+}
+
+fn network_retries() -> u32 {
+    config_value("network_retries")
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(DEFAULT_NETWORK_RETRIES)
+}
+
+/// Runs a network git/gh operation, retrying with exponential backoff when it fails with a
+/// transient network error. Other failures are returned immediately.
+fn with_network_retries(mut operation: impl FnMut() -> Result<()>) -> Result<()> {
+    if offline_enabled() {
+        return Err(BbqError::Offline);
+    }
+    let retries = network_retries();
+    let mut attempt = 0;
+    loop {
+        match operation() {
+            Err(err) if attempt < retries && is_transient_network_error(&err) => {
+                let delay = NETWORK_RETRY_BASE_DELAY_MS << attempt;
+                if verbose_enabled() {
+                    eprintln!("transient network error; retrying in {delay}ms");
+                }
+                thread::sleep(Duration::from_millis(delay));
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+fn is_transient_network_error(err: &BbqError) -> bool {
+    let stderr = match err {
+        BbqError::GitCommand { stderr, .. } | BbqError::GitHubCliCommand { stderr, .. } => stderr,
+        _ => return false,
+    };
+    let stderr = stderr.to_ascii_lowercase();
+    TRANSIENT_NETWORK_ERRORS
+        .iter()
+        .any(|pattern| stderr.contains(pattern))
+}
+
 fn args_to_string(args: &[OsString]) -> String {
     args.iter()
         .map(|arg| arg.to_string_lossy())
@@ -895,7 +2016,20 @@ fn args_to_string(args: &[OsString]) -> String {
         .join(" ")
 }
 
-fn git_command() -> Command {
+fn git_output(args: &[OsString]) -> io::Result<Output> {
+    trace_command("git", args);
+    git_command().args(args).output()
+}
+
+fn trace_command(program: &str, args: &[OsString]) {
+    if verbose_enabled() {
+        eprintln!("+ {program} {}", args_to_string(args));
+    }
+}
+
+/// Spawns git with the caller's environment untouched, so `GIT_SSH_COMMAND`, `SSH_AUTH_SOCK`
+/// and credential helper settings reach private remotes exactly as they would from a shell.
+pub(crate) fn git_command() -> Command {
     let mut command = Command::new("git");
     apply_safe_cwd(&mut command);
     command
@@ -907,6 +2041,8 @@ fn gh_command() -> Command {
     command
 }
 
+/// Only moves the child out of a deleted cwd; a valid cwd is kept so relative paths in the
+/// environment or SSH config still resolve.
 fn apply_safe_cwd(command: &mut Command) {
     if std::env::current_dir().is_ok() {
         return;
@@ -1,22 +1,43 @@
+pub mod abort;
 pub mod error;
 pub mod git;
+pub mod lock;
 pub mod model;
 pub mod paths;
 pub mod scripts;
+pub mod status;
+pub mod templates;
 pub mod validate;
 pub mod worktree_names;
 
+pub use abort::{abort_running_command, enable_abort};
 pub use error::{BbqError, Result};
 pub use git::{
-    checkout_repo, checkout_repo_with_name, create_worktree, create_worktree_from,
-    create_worktree_with_name, default_branch, default_remote_branch, list_repos, list_worktrees,
-    remove_repo, remove_worktree, remove_worktree_with_force, resolve_repo,
+    available_worktree_name, branch_exists, checkout_repo, checkout_repo_with_name,
+    checkout_repo_with_options, clone_repo_name, copy_untracked_files, create_worktree,
+    create_worktree_from, create_worktree_from_with_options, create_worktree_with_name,
+    create_worktree_with_name_and_options, default_branch, default_remote_branch, fetch_all,
+    find_repo_by_remote, find_worktree, head_commit_info, head_commit_time, list_branches,
+    list_repos, list_worktrees, offline_enabled, reclone_repo, remove_repo, remove_worktree,
+    remove_worktree_with_force, rename_branch, repair_worktrees, repo_remote_url, resolve_repo,
+    set_offline, set_verbose, set_worktree_config, verbose_enabled, worktree_diff, CloneOptions,
+    WorktreeOptions,
 };
-pub use model::{Repo, Worktree};
+pub use lock::{acquire_state_lock, with_state_lock, StateLock};
+pub use model::{CommitInfo, Repo, Worktree};
 pub use scripts::{
     find_post_create_script, find_pre_delete_script, post_create_script_path,
     pre_delete_script_path, run_post_create_script, run_pre_delete_script, ScriptOutput,
     POST_CREATE_SCRIPT_RELATIVE, PRE_DELETE_SCRIPT_RELATIVE,
 };
+pub use status::{
+    git_changed_files, head_divergence, worktree_conflict_state, worktree_upstream_ref,
+    ChangedFile, ConflictState, UpstreamRef,
+};
+pub use templates::{copy_worktree_templates, GLOBAL_TEMPLATE_DIR};
 pub use validate::{validate_branch_name, validate_worktree_name};
-pub use worktree_names::{city_worktree_name, suggest_worktree_name, DefaultWorktreeNameMode};
+pub use worktree_names::{
+    city_worktree_name, suggest_worktree_name, suggest_worktree_name_with_template,
+    unique_worktree_name, worktree_name_from_branch, DefaultWorktreeNameMode,
+    DEFAULT_WORKTREE_NAME_TEMPLATE,
+};
@@ -0,0 +1,67 @@
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::error::{BbqError, Result};
+use crate::paths::config_root;
+
+pub const LOCK_FILE_NAME: &str = ".lock";
+
+/// How long to wait for another process before giving up.
+const LOCK_WAIT: Duration = Duration::from_secs(10);
+const LOCK_POLL: Duration = Duration::from_millis(25);
+
+// The TUI writes state from several threads of one process; the file lock alone can't tell
+// those apart, so they queue on this first.
+static PROCESS_LOCK: Mutex<()> = Mutex::new(());
+
+/// Advisory lock on `~/.bbq/.lock`, released on drop. It is an OS file lock, so the lock goes
+/// away with a crashed holder and a slow holder can't have it taken over; the file itself stays.
+pub struct StateLock {
+    _file: File,
+    _guard: MutexGuard<'static, ()>,
+}
+
+/// Takes the state lock, waiting for other bbq processes.
+pub fn acquire_state_lock() -> Result<StateLock> {
+    let guard = PROCESS_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let dir = config_root()?;
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(LOCK_FILE_NAME);
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&path)?;
+    let deadline = SystemTime::now() + LOCK_WAIT;
+
+    loop {
+        match file.try_lock() {
+            Ok(()) => break,
+            Err(TryLockError::WouldBlock) => {
+                if SystemTime::now() >= deadline {
+                    return Err(BbqError::StateLocked(path));
+                }
+                thread::sleep(LOCK_POLL);
+            }
+            Err(TryLockError::Error(err)) => return Err(err.into()),
+        }
+    }
+    // Only informational: shows who holds the lock when someone goes looking.
+    let _ = file.set_len(0);
+    let _ = writeln!(file, "{}", std::process::id());
+    Ok(StateLock {
+        _file: file,
+        _guard: guard,
+    })
+}
+
+/// Runs `f` while holding the state lock.
+pub fn with_state_lock<T>(f: impl FnOnce() -> Result<T>) -> Result<T> {
+    let _lock = acquire_state_lock()?;
+    f()
+}
@@ -13,6 +13,12 @@ pub struct Worktree {
     pub head: Option<String>,
 }
 
+#[derive(Debug, Clone)]
+pub struct CommitInfo {
+    pub author: String,
+    pub message: String,
+}
+
 impl Worktree {
     pub fn display_name(&self) -> String {
         self.path
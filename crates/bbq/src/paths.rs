@@ -1,17 +1,60 @@
 use std::fs;
-use std::path::PathBuf;
+use std::io;
+use std::path::{Path, PathBuf};
 
 use crate::error::{BbqError, Result};
 
+/// Config directory: `$XDG_CONFIG_HOME/bbq` once `bbq migrate` has finished, else `~/.bbq`.
 pub fn config_root() -> Result<PathBuf> {
+    let xdg = xdg_config_root()?;
+    if xdg.is_dir() && xdg_layout_active()? {
+        return Ok(xdg);
+    }
+    legacy_root()
+}
+
+/// The XDG directories take over only after a completed migration (its note in `~/.bbq` is the
+/// marker) or when there is no `~/.bbq` at all, so a stray `~/.config/bbq` can't hide repos.
+fn xdg_layout_active() -> Result<bool> {
+    let legacy = legacy_root()?;
+    Ok(!legacy.exists() || legacy.join(MIGRATION_NOTE).is_file())
+}
+
+/// The pre-XDG location holding both config and data.
+pub fn legacy_root() -> Result<PathBuf> {
     let home = dirs::home_dir().ok_or(BbqError::HomeDirMissing)?;
     Ok(home.join(".bbq"))
 }
 
+pub fn xdg_config_root() -> Result<PathBuf> {
+    xdg_root("XDG_CONFIG_HOME", ".config")
+}
+
+pub fn xdg_data_root() -> Result<PathBuf> {
+    xdg_root("XDG_DATA_HOME", ".local/share")
+}
+
+fn xdg_root(var: &str, home_fallback: &str) -> Result<PathBuf> {
+    if let Some(base) = std::env::var_os(var) {
+        let base = PathBuf::from(base);
+        // The spec says relative values are invalid and must be ignored.
+        if base.is_absolute() {
+            return Ok(base.join("bbq"));
+        }
+    }
+    let home = dirs::home_dir().ok_or(BbqError::HomeDirMissing)?;
+    Ok(home.join(home_fallback).join("bbq"))
+}
+
 pub fn config_path() -> Result<PathBuf> {
     Ok(config_root()?.join("config.toml"))
 }
 
+/// Starter files copied into new worktrees: `templates/<repo>/` and `templates/global/`.
+pub fn templates_root() -> Result<PathBuf> {
+    Ok(config_root()?.join("templates"))
+}
+
 pub fn bbq_root() -> Result<PathBuf> {
     if let Some(root) = std::env::var_os("BBQ_ROOT_DIR") {
         if !root.is_empty() {
@@ -23,7 +66,12 @@ pub fn bbq_root() -> Result<PathBuf> {
         return Ok(root);
     }
 
-    Ok(config_root()?)
+    let xdg = xdg_data_root()?;
+    if xdg.is_dir() && xdg_layout_active()? {
+        return Ok(xdg);
+    }
+
+    legacy_root()
 }
 
 pub fn repos_root() -> Result<PathBuf> {
@@ -40,6 +88,204 @@ pub fn ensure_root_dirs() -> Result<()> {
     Ok(())
 }
 
+/// One file or directory `bbq migrate` moves out of `~/.bbq`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationMove {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+pub const MIGRATION_NOTE: &str = "MOVED.txt";
+
+/// What moving a legacy `~/.bbq` layout to the XDG directories would do. Config files go to
+/// `xdg_config_root()`; `repos/` and `worktrees/` go to `xdg_data_root()` only when they
+/// currently live in `~/.bbq` (not under `BBQ_ROOT_DIR` or `root_dir`). Targets that already
+/// exist are left alone.
+pub fn xdg_migration_plan() -> Result<Vec<MigrationMove>> {
+    let legacy = legacy_root()?;
+    let mut moves = Vec::new();
+    let mut push = |name: &str, to_root: &Path| {
+        let from = legacy.join(name);
+        let to = to_root.join(name);
+        if from.exists() && !to.exists() {
+            moves.push(MigrationMove { from, to });
+        }
+    };
+
+    let config = xdg_config_root()?;
+    push("config.toml", &config);
+    push("restore.toml", &config);
+    push("templates", &config);
+    if bbq_root()? == legacy {
+        let data = xdg_data_root()?;
+        push("repos", &data);
+        push("worktrees", &data);
+    }
+    Ok(moves)
+}
+
+/// Performs the moves from `xdg_migration_plan` and leaves a note in `~/.bbq` saying where
+/// things went. Every move is checked first, and a failure part way puts back what already moved
+/// and removes the directories created for it. The note is written last; until it exists bbq
+/// keeps using `~/.bbq`.
+pub fn apply_xdg_migration(moves: &[MigrationMove]) -> Result<()> {
+    for step in moves {
+        check_migration_move(step)?;
+    }
+
+    let mut created = Vec::new();
+    let mut done = Vec::new();
+    for step in moves {
+        if let Err(err) = migrate_one(step, &mut created) {
+            roll_back_migration(&done, &created);
+            return Err(err);
+        }
+        done.push(step);
+    }
+
+    let legacy = legacy_root()?;
+    if legacy.is_dir() && !moves.is_empty() {
+        let mut note = String::from("bbq moved these paths to their XDG locations:\n");
+        for step in moves {
+            note.push_str(&format!("{} -> {}\n", step.from.display(), step.to.display()));
+        }
+        if let Err(err) = fs::write(legacy.join(MIGRATION_NOTE), note) {
+            roll_back_migration(&done, &created);
+            return Err(err.into());
+        }
+    }
+    Ok(())
+}
+
+/// Moves are plain renames, so a target on another filesystem is refused up front instead of
+/// failing half way.
+fn check_migration_move(step: &MigrationMove) -> Result<()> {
+    let refuse = |reason: &str| {
+        Err(BbqError::Io(io::Error::other(format!(
+            "cannot move {} to {}: {reason}",
+            step.from.display(),
+            step.to.display()
+        ))))
+    };
+    let Ok(from) = step.from.symlink_metadata() else {
+        return refuse("source is missing");
+    };
+    if step.to.symlink_metadata().is_ok() {
+        return refuse("target already exists");
+    }
+    let Some(existing) = step.to.ancestors().find(|dir| dir.exists()) else {
+        return refuse("no existing parent directory");
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        if from.dev() != existing.metadata()?.dev() {
+            return refuse("target is on another filesystem; move it by hand");
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = (from, existing);
+    Ok(())
+}
+
+fn migrate_one(step: &MigrationMove, created: &mut Vec<PathBuf>) -> Result<()> {
+    if let Some(parent) = step.to.parent() {
+        let mut missing: Vec<&Path> = parent.ancestors().take_while(|dir| !dir.exists()).collect();
+        missing.reverse();
+        for dir in missing {
+            fs::create_dir(dir)?;
+            created.push(dir.to_path_buf());
+        }
+    }
+    fs::rename(&step.from, &step.to)?;
+    Ok(())
+}
+
+fn roll_back_migration(done: &[&MigrationMove], created: &[PathBuf]) {
+    for step in done.iter().rev() {
+        let _ = fs::rename(&step.to, &step.from);
+    }
+    for dir in created.iter().rev() {
+        let _ = fs::remove_dir(dir);
+    }
+}
+
+/// Raw value of a top-level key in the config file, with surrounding quotes removed.
+pub fn config_value(key: &str) -> Option<String> {
+    let path = config_path().ok()?;
+    let contents = fs::read_to_string(path).ok()?;
+    parse_config_value(&contents, key)
+}
+
+/// Directory names under the repos root that should not be treated as repos.
+pub(crate) fn ignored_repo_names() -> Vec<String> {
+    config_list("ignore_repos")
+}
+
+/// Extra arguments appended to `git clone` after `--bare`.
+pub(crate) fn extra_clone_args() -> Vec<String> {
+    config_list("clone_args")
+}
+
+/// The repo name an `aliases = { short = "repo" }` config entry maps `name` to, if any.
+pub(crate) fn repo_alias(name: &str) -> Option<String> {
+    let value = config_value("aliases")?;
+    let trimmed = value.trim();
+    let inner = trimmed.strip_prefix('{')?.strip_suffix('}')?;
+    split_unquoted(inner, ',')
+        .into_iter()
+        .find_map(|entry| match split_unquoted(entry, '=').as_slice() {
+            [alias, target] => {
+                let target = parse_config_string(target);
+                (parse_config_string(alias) == name && !target.is_empty()).then_some(target)
+            }
+            _ => None,
+        })
+}
+
+fn config_list(key: &str) -> Vec<String> {
+    config_value(key)
+        .map(|value| parse_config_list(&value))
+        .unwrap_or_default()
+}
+
+/// Items of a one-line array value like `["a", 'b']`; anything else is an empty list.
+pub fn parse_config_list(value: &str) -> Vec<String> {
+    let trimmed = value.trim();
+    if !(trimmed.starts_with('[') && trimmed.ends_with(']')) {
+        return Vec::new();
+    }
+    split_unquoted(&trimmed[1..trimmed.len() - 1], ',')
+        .into_iter()
+        .map(parse_config_string)
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// Splits `value` at each `separator` outside a quoted string, so `["a,b", "c"]` has two items.
+fn split_unquoted(value: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut quote = None;
+    let mut escaped = false;
+    for (idx, ch) in value.char_indices() {
+        match quote {
+            Some('"') if escaped => escaped = false,
+            Some('"') if ch == '\\' => escaped = true,
+            Some(open) if ch == open => quote = None,
+            Some(_) => {}
+            None if ch == '"' || ch == '\'' => quote = Some(ch),
+            None if ch == separator => {
+                parts.push(&value[start..idx]);
+                start = idx + ch.len_utf8();
+            }
+            None => {}
+        }
+    }
+    parts.push(&value[start..]);
+    parts
+}
+
 fn root_dir_from_config() -> Result<Option<PathBuf>> {
     let path = config_path()?;
     let Ok(contents) = fs::read_to_string(path) else {
@@ -56,13 +302,19 @@ fn root_dir_from_config() -> Result<Option<PathBuf>> {
         return Ok(None);
     }
 
+    expand_root_dir(value).map(Some)
+}
+
+/// Expands a `root_dir` config value, resolving a leading `~` to the home directory.
+pub fn expand_root_dir(value: &str) -> Result<PathBuf> {
+    let value = value.trim();
     if value == "~" || value.starts_with("~/") {
         let home = dirs::home_dir().ok_or(BbqError::HomeDirMissing)?;
         let suffix = value.strip_prefix("~/").unwrap_or("");
-        return Ok(Some(home.join(suffix)));
+        return Ok(home.join(suffix));
     }
 
-    Ok(Some(PathBuf::from(value)))
+    Ok(PathBuf::from(value))
 }
 
 fn parse_config_value(contents: &str, key: &str) -> Option<String> {
@@ -79,14 +331,22 @@ fn parse_config_value(contents: &str, key: &str) -> Option<String> {
         };
 
         if name == key {
-            return Some(trim_quotes(value));
+            return Some(parse_config_string(value));
         }
     }
     None
 }
 
-fn trim_quotes(value: &str) -> String {
+/// A config value as written after `=`: surrounding quotes are dropped and double-quoted
+/// values are unescaped. Shared with the CLI's config reader so both read values the same way.
+pub fn parse_config_string(value: &str) -> String {
     let trimmed = value.trim();
+    if let Some(inner) = trimmed
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+    {
+        return unescape_toml_string(inner);
+    }
     let without = trimmed
         .trim_start_matches('"')
         .trim_end_matches('"')
@@ -94,3 +354,20 @@ fn trim_quotes(value: &str) -> String {
         .trim_end_matches('\'');
     without.to_string()
 }
+
+/// Reverses the `\\` and `\"` escapes bbq writes into double-quoted values. Other backslashes
+/// are kept as written.
+fn unescape_toml_string(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch == '\\' {
+            if let Some(next) = chars.next_if(|next| matches!(next, '\\' | '"')) {
+                unescaped.push(next);
+                continue;
+            }
+        }
+        unescaped.push(ch);
+    }
+    unescaped
+}
@@ -1,8 +1,9 @@
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
-use std::process::{Command, ExitStatus, Stdio};
+use std::process::{Command, ExitStatus};
 
+use crate::abort::abortable_output;
 use crate::error::{BbqError, Result};
 use crate::model::Worktree;
 
@@ -96,10 +97,12 @@ fn run_script(worktree: &Worktree, script: &Path, output: ScriptOutput) -> Resul
             }
         }
         ScriptOutput::Capture => {
-            cmd.stdin(Stdio::null());
-            let output = cmd.output().map_err(|err| BbqError::ScriptFailed {
-                script: script_display.clone(),
-                message: err.to_string(),
+            let output = abortable_output(&mut cmd).map_err(|err| match err {
+                BbqError::Aborted => err,
+                err => BbqError::ScriptFailed {
+                    script: script_display.clone(),
+                    message: err.to_string(),
+                },
             })?;
             if output.status.success() {
                 Ok(())
@@ -127,7 +130,7 @@ fn read_shebang(script: &Path) -> Result<Vec<String>> {
             script: script.display().to_string(),
             message: err.to_string(),
         })?;
-    let line = line.trim_end_matches(|ch| ch == '\n' || ch == '\r');
+    let line = line.trim_end_matches(['\n', '\r']);
     if !line.starts_with("#!") {
         return Err(BbqError::ScriptMissingShebang(
             script.display().to_string(),
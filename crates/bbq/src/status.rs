@@ -0,0 +1,379 @@
+//! Upstream, ahead/behind, and changed-file status of a worktree, shared by the TUI detail panel
+//! and `bbq worktree list --format json`.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::git::git_command;
+
+/// Untracked files larger than this are not line-counted.
+const LINE_COUNT_MAX_BYTES: u64 = 1024 * 1024;
+/// How much of a file is checked for a NUL byte before treating it as binary.
+const BINARY_SNIFF_BYTES: usize = 8000;
+
+#[derive(Debug, Clone)]
+pub struct ChangedFile {
+    pub path: String,
+    pub added: u32,
+    pub removed: u32,
+}
+
+/// A worktree's upstream: `rev` to compare against, and `display`, its short name (`main` for
+/// `origin/main`).
+#[derive(Debug, Clone)]
+pub struct UpstreamRef {
+    pub rev: String,
+    pub display: String,
+}
+
+/// The branch's `@{u}`, or the `branch.<name>.remote`/`merge` config when the remote-tracking
+/// ref hasn't been fetched yet.
+pub fn worktree_upstream_ref(path: &Path) -> Option<UpstreamRef> {
+    let output = git_command()
+        .arg("-C")
+        .arg(path)
+        .args(["rev-parse", "--abbrev-ref", "--symbolic-full-name", "@{u}"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return upstream_from_config(path);
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let value = stdout.lines().next()?.trim();
+    if value.is_empty() {
+        return None;
+    }
+
+    let display = display_ref_name(value);
+    let display = strip_origin_prefix(&display);
+    Some(UpstreamRef {
+        rev: value.to_string(),
+        display,
+    })
+}
+
+fn upstream_from_config(path: &Path) -> Option<UpstreamRef> {
+    let branch = current_branch_name(path)?;
+    let remote = git_config_value(path, &format!("branch.{branch}.remote"))?;
+    let merge = git_config_value(path, &format!("branch.{branch}.merge"))?;
+    let merge_branch = merge.strip_prefix("refs/heads/").unwrap_or(&merge);
+    if merge_branch.is_empty() {
+        return None;
+    }
+    let display = display_upstream_branch(&remote, merge_branch);
+    let rev = format!("refs/remotes/{remote}/{merge_branch}");
+    Some(UpstreamRef { rev, display })
+}
+
+fn current_branch_name(path: &Path) -> Option<String> {
+    let output = git_command()
+        .arg("-C")
+        .arg(path)
+        .args(["symbolic-ref", "--short", "HEAD"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let value = stdout.lines().next()?.trim();
+    if value.is_empty() {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
+
+fn git_config_value(path: &Path, key: &str) -> Option<String> {
+    let output = git_command()
+        .arg("-C")
+        .arg(path)
+        .args(["config", "--get", key])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let value = stdout.lines().next()?.trim();
+    if value.is_empty() {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
+
+fn display_ref_name(reference: &str) -> String {
+    reference
+        .strip_prefix("refs/remotes/")
+        .or_else(|| reference.strip_prefix("refs/heads/"))
+        .unwrap_or(reference)
+        .to_string()
+}
+
+fn strip_origin_prefix(reference: &str) -> String {
+    reference
+        .strip_prefix("origin/")
+        .unwrap_or(reference)
+        .to_string()
+}
+
+fn display_upstream_branch(remote: &str, branch: &str) -> String {
+    if remote == "origin" {
+        branch.to_string()
+    } else {
+        format!("{remote}/{branch}")
+    }
+}
+
+/// Commits `(ahead, behind)` of `upstream_ref`.
+pub fn head_divergence(path: &Path, upstream_ref: &str) -> Option<(u32, u32)> {
+    let range = format!("HEAD...{upstream_ref}");
+    let output = git_command()
+        .arg("-C")
+        .arg(path)
+        .args(["rev-list", "--left-right", "--count", &range])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut parts = stdout.split_whitespace();
+    let ahead = parts.next()?.parse().ok()?;
+    let behind = parts.next()?.parse().ok()?;
+    Some((ahead, behind))
+}
+
+/// A merge or rebase stopped partway, usually on conflicts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictState {
+    Merge,
+    Rebase,
+}
+
+impl ConflictState {
+    pub fn label(self) -> &'static str {
+        match self {
+            ConflictState::Merge => "merge in progress",
+            ConflictState::Rebase => "rebase in progress",
+        }
+    }
+}
+
+/// Whether the worktree is partway through a merge or rebase, from the state files git leaves
+/// in its git dir.
+pub fn worktree_conflict_state(path: &Path) -> Option<ConflictState> {
+    let output = git_command()
+        .arg("-C")
+        .arg(path)
+        .args(["rev-parse", "--absolute-git-dir"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let git_dir = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    if ["rebase-merge", "rebase-apply", "REBASE_HEAD"]
+        .iter()
+        .any(|name| git_dir.join(name).exists())
+    {
+        return Some(ConflictState::Rebase);
+    }
+    git_dir
+        .join("MERGE_HEAD")
+        .exists()
+        .then_some(ConflictState::Merge)
+}
+
+/// Changed files in the worktree, stopping after `limit` (line counts are the slow part), and
+/// the total number of changes.
+pub fn git_changed_files(path: &Path, limit: Option<usize>) -> (Vec<ChangedFile>, usize) {
+    let mut diff_stats = git_diff_numstat(path);
+    let output = git_command()
+        .arg("-C")
+        .arg(path)
+        .args(["status", "--porcelain"])
+        .output();
+    let output = match output {
+        Ok(output) if output.status.success() => output,
+        _ => return (Vec::new(), 0),
+    };
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut files = Vec::new();
+    let mut total = 0;
+    for line in stdout.lines() {
+        if line.len() < 3 {
+            continue;
+        }
+        let status = &line[..2];
+        let path_part = line.get(3..).unwrap_or("").trim();
+        if path_part.is_empty() {
+            continue;
+        }
+        let file = if let Some((_, new)) = path_part.split_once("->") {
+            new.trim().to_string()
+        } else {
+            path_part.to_string()
+        };
+        if file.is_empty() {
+            continue;
+        }
+        total += 1;
+        if limit.is_some_and(|limit| files.len() >= limit) {
+            continue;
+        }
+        let (added, removed) = diff_stats.remove(&file).unwrap_or_else(|| {
+            if status == "??" {
+                (count_file_lines(path, &file), 0)
+            } else {
+                (0, 0)
+            }
+        });
+        files.push(ChangedFile {
+            path: file,
+            added,
+            removed,
+        });
+    }
+    (files, total)
+}
+
+fn git_diff_numstat(path: &Path) -> HashMap<String, (u32, u32)> {
+    let output = git_command()
+        .arg("-C")
+        .arg(path)
+        .args(["diff", "--numstat", "HEAD"])
+        .output();
+    let output = match output {
+        Ok(output) if output.status.success() => output,
+        _ => return HashMap::new(),
+    };
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut stats = HashMap::new();
+    for line in stdout.lines() {
+        let mut parts = line.split('\t');
+        let added_raw = parts.next().unwrap_or("");
+        let removed_raw = parts.next().unwrap_or("");
+        let path_raw = parts.next().unwrap_or("").trim();
+        if path_raw.is_empty() {
+            continue;
+        }
+        let added = added_raw.parse::<u32>().unwrap_or(0);
+        let removed = removed_raw.parse::<u32>().unwrap_or(0);
+        let file = if let Some((_, new)) = path_raw.split_once("->") {
+            new.trim().to_string()
+        } else {
+            path_raw.to_string()
+        };
+        if !file.is_empty() {
+            stats.insert(file, (added, removed));
+        }
+    }
+    stats
+}
+
+/// Line count for an untracked file. Files over `LINE_COUNT_MAX_BYTES` and binary files (a NUL
+/// byte near the start, as git checks) count as 0 so large artifacts don't stall the detail panel.
+fn count_file_lines(repo_path: &Path, file: &str) -> u32 {
+    let path = repo_path.join(file);
+    let Ok(metadata) = fs::metadata(&path) else {
+        return 0;
+    };
+    if !metadata.is_file() || metadata.len() > LINE_COUNT_MAX_BYTES {
+        return 0;
+    }
+    let Ok(content) = fs::read(&path) else {
+        return 0;
+    };
+    let sniff_len = content.len().min(BINARY_SNIFF_BYTES);
+    if content[..sniff_len].contains(&0) {
+        return 0;
+    }
+
+    let mut lines = content.iter().filter(|byte| **byte == b'\n').count() as u32;
+    if content.last().is_some_and(|byte| *byte != b'\n') {
+        lines += 1;
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{
+        count_file_lines, git_changed_files, worktree_conflict_state, ConflictState,
+        LINE_COUNT_MAX_BYTES,
+    };
+
+    #[test]
+    fn untracked_line_count_skips_binary_and_huge_files() {
+        let dir = std::env::temp_dir().join(format!("bbq-line-count-{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("create temp dir");
+        fs::write(dir.join("notes.txt"), "one\ntwo\nthree").expect("write text file");
+        fs::write(dir.join("image.bin"), b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\n").expect("write binary");
+        let huge = "line\n".repeat(LINE_COUNT_MAX_BYTES as usize / 5 + 1);
+        fs::write(dir.join("dump.log"), huge).expect("write huge file");
+
+        assert_eq!(count_file_lines(&dir, "notes.txt"), 3);
+        assert_eq!(count_file_lines(&dir, "image.bin"), 0);
+        assert_eq!(count_file_lines(&dir, "dump.log"), 0);
+        assert_eq!(count_file_lines(&dir, "missing.txt"), 0);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn changed_files_stop_at_the_cap_but_count_everything() {
+        let dir = std::env::temp_dir().join(format!("bbq-changed-cap-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("create temp dir");
+        let status = std::process::Command::new("git")
+            .args(["init", "--quiet"])
+            .current_dir(&dir)
+            .status()
+            .expect("git init");
+        assert!(status.success());
+        for name in ["a.txt", "b.txt", "c.txt", "d.txt", "e.txt"] {
+            fs::write(dir.join(name), "line\n").expect("write file");
+        }
+
+        let (files, total) = git_changed_files(&dir, Some(2));
+        assert_eq!(files.len(), 2);
+        assert_eq!(total, 5);
+        let (files, total) = git_changed_files(&dir, None);
+        assert_eq!(files.len(), 5);
+        assert_eq!(total, 5);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn merge_head_marks_worktree_as_conflicted() {
+        let dir = std::env::temp_dir().join(format!("bbq-conflict-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("create temp dir");
+        let status = std::process::Command::new("git")
+            .args(["init", "--quiet"])
+            .current_dir(&dir)
+            .status()
+            .expect("git init");
+        assert!(status.success());
+        assert_eq!(worktree_conflict_state(&dir), None);
+
+        fs::write(dir.join(".git/MERGE_HEAD"), "0000000000000000000000000000000000000000\n")
+            .expect("write MERGE_HEAD");
+        assert_eq!(worktree_conflict_state(&dir), Some(ConflictState::Merge));
+
+        fs::create_dir_all(dir.join(".git/rebase-merge")).expect("create rebase-merge");
+        assert_eq!(worktree_conflict_state(&dir), Some(ConflictState::Rebase));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
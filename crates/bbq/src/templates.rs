@@ -0,0 +1,40 @@
+use std::fs;
+use std::path::Path;
+
+use crate::error::Result;
+use crate::model::{Repo, Worktree};
+use crate::paths::templates_root;
+
+pub const GLOBAL_TEMPLATE_DIR: &str = "global";
+
+/// Copies the repo's template files, then the global ones, into a new worktree. Files that
+/// already exist (checked in, or from the repo templates) are left alone. Returns the number
+/// of files copied.
+pub fn copy_worktree_templates(repo: &Repo, worktree: &Worktree) -> Result<usize> {
+    let root = templates_root()?;
+    let mut copied = 0;
+    for dir in [repo.name.as_str(), GLOBAL_TEMPLATE_DIR] {
+        let source = root.join(dir);
+        if source.is_dir() {
+            copied += copy_missing(&source, &worktree.path)?;
+        }
+    }
+    Ok(copied)
+}
+
+fn copy_missing(from: &Path, to: &Path) -> Result<usize> {
+    let mut copied = 0;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            fs::create_dir_all(&target)?;
+            copied += copy_missing(&entry.path(), &target)?;
+        } else if file_type.is_file() && !target.exists() {
+            fs::copy(entry.path(), &target)?;
+            copied += 1;
+        }
+    }
+    Ok(copied)
+}
@@ -1,3 +1,6 @@
+/// Names of git internals that a worktree directory shouldn't be mistaken for.
+const RESERVED_WORKTREE_NAMES: [&str; 6] = ["HEAD", "refs", "objects", "hooks", "info", "logs"];
+
 pub fn validate_worktree_name(name: &str) -> Result<(), String> {
     if name.is_empty() {
         return Err("Worktree name required".to_string());
@@ -8,6 +11,15 @@ pub fn validate_worktree_name(name: &str) -> Result<(), String> {
     if name.chars().any(|ch| !is_worktree_char(ch)) {
         return Err("Worktree name can only use letters, numbers, '-', '_', or '.'".to_string());
     }
+    if name.starts_with('.') {
+        return Err("Worktree name cannot start with '.'".to_string());
+    }
+    if RESERVED_WORKTREE_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(name))
+    {
+        return Err(format!("Worktree name '{name}' is reserved by git"));
+    }
     Ok(())
 }
 
@@ -55,6 +67,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn validate_worktree_name_rejects_git_names() {
+        assert_eq!(
+            validate_worktree_name(".git"),
+            Err("Worktree name cannot start with '.'".to_string())
+        );
+        assert_eq!(
+            validate_worktree_name(".hidden"),
+            Err("Worktree name cannot start with '.'".to_string())
+        );
+        assert_eq!(
+            validate_worktree_name("HEAD"),
+            Err("Worktree name 'HEAD' is reserved by git".to_string())
+        );
+        assert!(validate_worktree_name("Objects").is_err());
+        assert_eq!(validate_worktree_name("head-start"), Ok(()));
+        assert_eq!(validate_worktree_name("v1.2"), Ok(()));
+    }
+
     #[test]
     fn validate_worktree_name_accepts_valid() {
         assert_eq!(validate_worktree_name("feature-1.2_ok"), Ok(()));
@@ -1,6 +1,10 @@
 use std::collections::HashSet;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use crate::validate::validate_worktree_name;
+
+pub const DEFAULT_WORKTREE_NAME_TEMPLATE: &str = "{branch_tail}";
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DefaultWorktreeNameMode {
     Cities,
@@ -20,10 +24,50 @@ pub fn suggest_worktree_name(
     default_source: &str,
     mode: Option<DefaultWorktreeNameMode>,
     existing_names: &HashSet<String>,
+) -> String {
+    suggest_worktree_name_with_template(
+        "",
+        source_branch,
+        default_source,
+        mode,
+        None,
+        existing_names,
+    )
+}
+
+/// Like `suggest_worktree_name`, but branch-mode names are formatted with `template` (see
+/// `worktree_name_from_branch`) for repo `repo_name`.
+pub fn suggest_worktree_name_with_template(
+    repo_name: &str,
+    source_branch: &str,
+    default_source: &str,
+    mode: Option<DefaultWorktreeNameMode>,
+    template: Option<&str>,
+    existing_names: &HashSet<String>,
 ) -> String {
     match mode {
         Some(DefaultWorktreeNameMode::Cities) => city_worktree_name(existing_names),
-        None => branch_worktree_name(source_branch, default_source),
+        None => branch_worktree_name(repo_name, source_branch, default_source, template),
+    }
+}
+
+/// Formats a worktree directory name for `branch` from a `worktree_name_template`. Supports
+/// `{branch_tail}` (last path segment), `{branch}` (with `/` replaced by `-`), and `{repo}`.
+/// Falls back to the branch tail when the template yields an invalid name.
+pub fn worktree_name_from_branch(repo_name: &str, branch: &str, template: Option<&str>) -> String {
+    let tail = branch.rsplit('/').next().unwrap_or(branch);
+    let template = template
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .unwrap_or(DEFAULT_WORKTREE_NAME_TEMPLATE);
+    let name = template
+        .replace("{branch_tail}", tail)
+        .replace("{branch}", &branch.replace('/', "-"))
+        .replace("{repo}", repo_name);
+    if validate_worktree_name(&name).is_ok() {
+        name
+    } else {
+        tail.to_string()
     }
 }
 
@@ -32,16 +76,17 @@ pub fn city_worktree_name(existing_names: &HashSet<String>) -> String {
     pick_city_name_with_seed(existing_names, seed)
 }
 
-fn branch_worktree_name(source_branch: &str, default_source: &str) -> String {
+fn branch_worktree_name(
+    repo_name: &str,
+    source_branch: &str,
+    default_source: &str,
+    template: Option<&str>,
+) -> String {
     if source_branch == default_source {
         return String::new();
     }
 
-    source_branch
-        .rsplit('/')
-        .next()
-        .unwrap_or(source_branch)
-        .to_string()
+    worktree_name_from_branch(repo_name, source_branch, template)
 }
 
 fn pick_city_name_with_seed(existing_names: &HashSet<String>, seed: u64) -> String {
@@ -63,10 +108,18 @@ fn pick_city_name_with_seed(existing_names: &HashSet<String>, seed: u64) -> Stri
 
     let base_index = next_index(&mut state, CITY_NAMES.len());
     let base = CITY_NAMES[base_index];
+    unique_worktree_name(base, |name| existing_names.contains(name))
+}
+
+/// Returns `base`, or `base-2`, `base-3`, ... for the first name `is_taken` rejects.
+pub fn unique_worktree_name(base: &str, is_taken: impl Fn(&str) -> bool) -> String {
+    if !is_taken(base) {
+        return base.to_string();
+    }
     let mut suffix = 2;
     loop {
         let candidate = format!("{base}-{suffix}");
-        if !existing_names.contains(&candidate) {
+        if !is_taken(&candidate) {
             return candidate;
         }
         suffix += 1;
@@ -358,7 +411,10 @@ const CITY_NAMES: &[&str] = &[
 
 #[cfg(test)]
 mod tests {
-    use super::{pick_city_name_with_seed, suggest_worktree_name, CITY_NAMES};
+    use super::{
+        pick_city_name_with_seed, suggest_worktree_name, suggest_worktree_name_with_template,
+        unique_worktree_name, worktree_name_from_branch, CITY_NAMES,
+    };
     use std::collections::HashSet;
 
     #[test]
@@ -375,6 +431,34 @@ mod tests {
         assert!(name.is_empty());
     }
 
+    #[test]
+    fn worktree_name_template_formats_branch() {
+        let branch = "origin/feature/foo";
+        let name = |template| worktree_name_from_branch("app", branch, template);
+        assert_eq!(name(None), "foo");
+        assert_eq!(name(Some("feat-{branch_tail}")), "feat-foo");
+        assert_eq!(name(Some("{repo}-{branch_tail}")), "app-foo");
+        assert_eq!(name(Some("{branch}")), "origin-feature-foo");
+
+        let existing = HashSet::new();
+        let name = suggest_worktree_name_with_template(
+            "app",
+            "feature/thing",
+            "origin/main",
+            None,
+            Some("{repo}-{branch_tail}"),
+            &existing,
+        );
+        assert_eq!(name, "app-thing");
+    }
+
+    #[test]
+    fn invalid_worktree_name_template_falls_back_to_branch_tail() {
+        let name = |template| worktree_name_from_branch("app", "feature/foo", Some(template));
+        assert_eq!(name("{branch_tail} x"), "foo");
+        assert_eq!(name("wt/{branch_tail}"), "foo");
+    }
+
     #[test]
     fn pick_city_name_returns_available_city() {
         let mut existing: HashSet<String> = CITY_NAMES.iter().map(|name| name.to_string()).collect();
@@ -392,6 +476,13 @@ mod tests {
         assert!(CITY_NAMES.contains(&base));
     }
 
+    #[test]
+    fn unique_worktree_name_appends_first_free_suffix() {
+        let taken = ["api", "api-2", "api-3"];
+        assert_eq!(unique_worktree_name("web", |name| taken.contains(&name)), "web");
+        assert_eq!(unique_worktree_name("api", |name| taken.contains(&name)), "api-4");
+    }
+
     #[test]
     fn city_list_size_within_bounds() {
         assert_eq!(CITY_NAMES.len(), 250);
@@ -3,14 +3,23 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::Mutex;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use bbq::{
-    checkout_repo, checkout_repo_with_name, create_worktree, create_worktree_from,
-    create_worktree_with_name, default_branch, default_remote_branch, list_repos, list_worktrees,
-    remove_repo, remove_worktree, remove_worktree_with_force, resolve_repo, BbqError,
+    abort_running_command, branch_exists, checkout_repo, checkout_repo_with_name,
+    checkout_repo_with_options, clone_repo_name, copy_untracked_files, copy_worktree_templates,
+    create_worktree, create_worktree_from, create_worktree_from_with_options,
+    create_worktree_with_name, create_worktree_with_name_and_options, default_branch,
+    default_remote_branch, enable_abort, fetch_all, find_repo_by_remote, find_worktree,
+    list_branches, list_repos, list_worktrees, reclone_repo, remove_repo, remove_worktree,
+    remove_worktree_with_force, rename_branch, repair_worktrees, repo_remote_url, resolve_repo,
+    run_post_create_script, set_worktree_config, BbqError, CloneOptions, ScriptOutput, Worktree,
+    WorktreeOptions, GLOBAL_TEMPLATE_DIR, POST_CREATE_SCRIPT_RELATIVE,
+};
+use bbq::paths::{
+    apply_xdg_migration, bbq_root, config_path, config_root, ensure_root_dirs, repos_root,
+    worktrees_root, xdg_migration_plan, MIGRATION_NOTE,
 };
-use bbq::paths::{bbq_root, config_root, ensure_root_dirs, repos_root, worktrees_root};
 
 static TEST_MUTEX: Mutex<()> = Mutex::new(());
 
@@ -34,6 +43,77 @@ fn checkout_repo_and_list() {
     cleanup_root(&root);
 }
 
+#[test]
+fn reclone_repo_rebuilds_bare_repo_from_remote() {
+    let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+    let root = unique_root("reclone_repo_rebuilds_bare_repo_from_remote");
+    let _env = EnvGuard::set("BBQ_ROOT_DIR", &root);
+
+    let src_repo = root.join("source");
+    init_repo(&src_repo);
+    let repo = checkout_repo(src_repo.to_str().expect("repo path")).expect("checkout repo");
+    let (remote, url) = repo_remote_url(&repo)
+        .expect("remote url")
+        .expect("origin configured");
+    assert_eq!(remote, "origin");
+    assert_eq!(url, src_repo.to_str().expect("repo path"));
+
+    let worktree = create_worktree(&repo, "feature").expect("create worktree");
+    let err = reclone_repo("source").expect_err("worktrees block reclone");
+    assert!(matches!(err, BbqError::RepoHasWorktrees));
+    remove_worktree(&repo, &worktree.display_name()).expect("remove worktree");
+
+    // Simulate a corrupted bare repo.
+    fs::remove_dir_all(repo.path.join("objects")).expect("remove objects");
+    fs::create_dir_all(repo.path.join("objects")).expect("recreate objects");
+
+    let recloned = reclone_repo("source").expect("reclone repo");
+    assert_eq!(recloned.path, repo.path);
+    create_worktree(&recloned, "after").expect("create worktree after reclone");
+    let repos = list_repos().expect("list repos");
+    assert_eq!(repos.len(), 1);
+
+    cleanup_root(&root);
+}
+
+#[test]
+fn fuzzy_match_resolves_unique_names_when_enabled() {
+    let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+    let root = unique_root("fuzzy_match_resolves_unique_names_when_enabled");
+    let _env = EnvGuard::set("BBQ_ROOT_DIR", &root);
+    let home = root.join("home");
+    fs::create_dir_all(&home).expect("create home");
+    let _home_env = EnvGuard::set("HOME", &home);
+
+    let src_repo = root.join("source");
+    init_repo(&src_repo);
+    let source = src_repo.to_str().expect("repo path");
+    for name in ["api-server", "api-client", "web"] {
+        checkout_repo_with_name(source, name).expect("checkout repo");
+    }
+    let web = resolve_repo("web").expect("resolve web");
+    create_worktree(&web, "feature-login").expect("create worktree");
+    create_worktree(&web, "feature-logout").expect("create worktree");
+
+    let err = resolve_repo("we").expect_err("fuzzy matching is off by default");
+    assert!(matches!(err, BbqError::RepoNotFound(_)));
+
+    write_config(&home, "fuzzy_match = true");
+    assert_eq!(resolve_repo("we").expect("unique prefix").name, "web");
+    assert_eq!(resolve_repo("CLIENT").expect("unique substring").name, "api-client");
+    let err = resolve_repo("api").expect_err("ambiguous prefix");
+    let message = err.to_string();
+    assert!(matches!(err, BbqError::AmbiguousRepoName(_)));
+    assert!(message.contains("api-client") && message.contains("api-server"), "{message}");
+
+    let worktree = find_worktree(&web, "logout").expect("unique substring");
+    assert_eq!(worktree.display_name(), "feature-logout");
+    let err = find_worktree(&web, "feature-log").expect_err("ambiguous prefix");
+    assert!(err.to_string().contains("feature-login, feature-logout"), "{err}");
+
+    cleanup_root(&root);
+}
+
 #[test]
 fn checkout_repo_with_custom_name() {
     let _guard = TEST_MUTEX.lock().expect("lock test mutex");
@@ -78,6 +158,13 @@ fn checkout_repo_with_invalid_name_is_rejected() {
         .expect_err("invalid name should fail");
     assert!(matches!(err, BbqError::InvalidRepoName));
 
+    let err = checkout_repo_with_name(src_repo.to_str().expect("repo path"), GLOBAL_TEMPLATE_DIR)
+        .expect_err("reserved name should fail");
+    assert!(matches!(err, BbqError::ReservedRepoName(_)));
+    let err = clone_repo_name("git@github.com:acme/Global.git", None)
+        .expect_err("reserved url name should fail");
+    assert!(matches!(err, BbqError::ReservedRepoName(_)));
+
     cleanup_root(&root);
 }
 
@@ -98,6 +185,39 @@ fn checkout_repo_duplicate_fails() {
     cleanup_root(&root);
 }
 
+#[test]
+fn checkout_repo_single_branch_fetches_other_branches_on_create() {
+    let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+    let root = unique_root("checkout_repo_single_branch_fetches_other_branches_on_create");
+    let _env = EnvGuard::set("BBQ_ROOT_DIR", &root);
+
+    let src_repo = root.join("source");
+    init_repo(&src_repo);
+    let branch = current_branch(&src_repo);
+    run_git(&["branch", "other"], &src_repo);
+
+    let options = CloneOptions {
+        single_branch: true,
+        branch: Some(branch.clone()),
+        ..CloneOptions::default()
+    };
+    let repo = checkout_repo_with_options(src_repo.to_str().expect("repo path"), None, &options)
+        .expect("checkout repo");
+    let refs = run_git_capture(&["show-ref"], &repo.path);
+    assert!(refs.contains(&format!("refs/heads/{branch}")));
+    assert!(!refs.contains("other"), "single-branch clone fetched other: {refs}");
+
+    let worktree = create_worktree_from(&repo, "other", "other", "other")
+        .expect("create worktree from unfetched branch");
+    assert_eq!(worktree.branch.as_deref(), Some("other"));
+    let refs = run_git_capture(&["show-ref"], &repo.path);
+    assert!(refs.contains("refs/remotes/origin/other"));
+
+    remove_worktree(&repo, "other").expect("remove worktree");
+    remove_repo(&repo.name).expect("remove repo");
+    cleanup_root(&root);
+}
+
 #[test]
 fn create_list_and_remove_worktree() {
     let _guard = TEST_MUTEX.lock().expect("lock test mutex");
@@ -151,6 +271,176 @@ fn remove_worktree_force_discards_changes() {
     cleanup_root(&root);
 }
 
+#[test]
+fn removed_worktree_can_be_recreated_from_its_branch() {
+    let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+    let root = unique_root("removed_worktree_can_be_recreated_from_its_branch");
+    let _env = EnvGuard::set("BBQ_ROOT_DIR", &root);
+
+    let src_repo = root.join("source");
+    init_repo(&src_repo);
+
+    let repo = checkout_repo(src_repo.to_str().expect("repo path")).expect("checkout repo");
+    let worktree = create_worktree_from(&repo, "undo-test", "user/undo-test", "HEAD")
+        .expect("create worktree");
+    assert!(!branch_exists(&repo, "user/missing").expect("check missing branch"));
+
+    remove_worktree(&repo, "undo-test").expect("remove worktree");
+    assert!(!worktree.path.exists());
+    assert!(branch_exists(&repo, "user/undo-test").expect("check branch"));
+
+    let restored = create_worktree_from(&repo, "undo-test", "user/undo-test", "user/undo-test")
+        .expect("recreate worktree");
+    assert_eq!(restored.path, worktree.path);
+    assert_eq!(restored.branch.as_deref(), Some("user/undo-test"));
+    let worktrees = list_worktrees(&repo).expect("list worktrees");
+    assert_eq!(worktrees.len(), 1);
+
+    remove_worktree(&repo, "undo-test").expect("remove worktree");
+    remove_repo(&repo.name).expect("remove repo");
+    cleanup_root(&root);
+}
+
+#[test]
+fn rename_branch_updates_worktree_branch() {
+    let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+    let root = unique_root("rename_branch_updates_worktree_branch");
+    let _env = EnvGuard::set("BBQ_ROOT_DIR", &root);
+
+    let src_repo = root.join("source");
+    init_repo(&src_repo);
+
+    let repo = checkout_repo(src_repo.to_str().expect("repo path")).expect("checkout repo");
+    let worktree = create_worktree_from(&repo, "rename-test", "user/old-name", "HEAD")
+        .expect("create worktree");
+
+    let err = rename_branch(&worktree, "bad name").expect_err("invalid branch rejected");
+    assert!(matches!(err, BbqError::InvalidBranchName));
+
+    rename_branch(&worktree, "user/new-name").expect("rename branch");
+    assert_eq!(current_branch(&worktree.path), "user/new-name");
+    assert!(!branch_exists(&repo, "user/old-name").expect("check old branch"));
+
+    let worktrees = list_worktrees(&repo).expect("list worktrees");
+    assert_eq!(worktrees.len(), 1);
+    assert_eq!(worktrees[0].display_name(), "rename-test");
+    assert_eq!(worktrees[0].branch.as_deref(), Some("user/new-name"));
+
+    let mut detached = worktrees[0].clone();
+    detached.branch = None;
+    let err = rename_branch(&detached, "user/other").expect_err("detached HEAD rejected");
+    assert!(matches!(err, BbqError::DetachedHead(_)));
+
+    remove_worktree(&repo, "rename-test").expect("remove worktree");
+    remove_repo(&repo.name).expect("remove repo");
+    cleanup_root(&root);
+}
+
+#[test]
+fn find_worktree_falls_back_to_case_insensitive_match() {
+    let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+    let root = unique_root("find_worktree_falls_back_to_case_insensitive_match");
+    let _env = EnvGuard::set("BBQ_ROOT_DIR", &root);
+
+    let src_repo = root.join("source");
+    init_repo(&src_repo);
+
+    let repo = checkout_repo(src_repo.to_str().expect("repo path")).expect("checkout repo");
+    create_worktree_from(&repo, "feature-test", "user/feature-one", "HEAD")
+        .expect("create worktree");
+
+    let found = find_worktree(&repo, "Feature-Test").expect("case-insensitive match");
+    assert_eq!(found.display_name(), "feature-test");
+    let found = find_worktree(&repo, "USER/FEATURE-ONE").expect("case-insensitive branch");
+    assert_eq!(found.display_name(), "feature-test");
+
+    create_worktree_from(&repo, "Feature-Test", "user/feature-two", "HEAD")
+        .expect("create second worktree");
+
+    let found = find_worktree(&repo, "Feature-Test").expect("exact match preferred");
+    assert_eq!(found.branch.as_deref(), Some("user/feature-two"));
+    let err = find_worktree(&repo, "FEATURE-TEST").expect_err("ambiguous name rejected");
+    assert!(matches!(err, BbqError::AmbiguousWorktreeName(_)));
+    let err = find_worktree(&repo, "missing").expect_err("missing name rejected");
+    assert!(matches!(err, BbqError::WorktreeNotFound(_)));
+
+    remove_worktree(&repo, "feature-test").expect("remove worktree");
+    remove_worktree(&repo, "Feature-Test").expect("remove second worktree");
+    remove_repo(&repo.name).expect("remove repo");
+    cleanup_root(&root);
+}
+
+#[test]
+fn create_worktree_at_earlier_commit() {
+    let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+    let root = unique_root("create_worktree_at_earlier_commit");
+    let _env = EnvGuard::set("BBQ_ROOT_DIR", &root);
+
+    let src_repo = root.join("source");
+    init_repo(&src_repo);
+    let first_commit = run_git_capture(&["rev-parse", "HEAD"], &src_repo);
+    fs::write(src_repo.join("later.txt"), "later").expect("write later file");
+    run_git(&["add", "later.txt"], &src_repo);
+    run_git(&["commit", "--quiet", "-m", "later"], &src_repo);
+
+    let repo = checkout_repo(src_repo.to_str().expect("repo path")).expect("checkout repo");
+    let at = |commit: &str| WorktreeOptions {
+        at: Some(commit.to_string()),
+        ..WorktreeOptions::default()
+    };
+    let source = current_branch(&src_repo);
+    let worktree =
+        create_worktree_from_with_options(&repo, "repro", "repro", &source, &at(&first_commit))
+            .expect("create worktree at commit");
+    let head = run_git_capture(&["rev-parse", "HEAD"], &worktree.path);
+    assert_eq!(head, first_commit);
+    assert_eq!(current_branch(&worktree.path), "repro");
+
+    let err = create_worktree_with_name_and_options(&repo, "missing", "missing", &at("deadbeef"))
+        .expect_err("unknown commit rejected");
+    assert!(matches!(err, BbqError::CommitNotFound(_)));
+    let err = create_worktree_with_name_and_options(&repo, "again", "repro", &at(&first_commit))
+        .expect_err("existing branch rejected");
+    assert!(matches!(err, BbqError::BranchAlreadyExists(_)));
+
+    remove_worktree(&repo, "repro").expect("remove worktree");
+    remove_repo(&repo.name).expect("remove repo");
+    cleanup_root(&root);
+}
+
+#[test]
+fn create_worktree_from_misspelled_source_suggests_branches() {
+    let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+    let root = unique_root("create_worktree_from_misspelled_source_suggests_branches");
+    let _env = EnvGuard::set("BBQ_ROOT_DIR", &root);
+
+    let src_repo = root.join("source");
+    init_repo(&src_repo);
+    run_git(&["branch", "feature-login"], &src_repo);
+
+    let repo = checkout_repo(src_repo.to_str().expect("repo path")).expect("checkout repo");
+    let err = create_worktree_from(&repo, "typo", "typo", "feature-logn")
+        .expect_err("misspelled source rejected");
+    match &err {
+        BbqError::SourceBranchNotFound {
+            branch,
+            suggestions,
+        } => {
+            assert_eq!(branch, "feature-logn");
+            assert!(
+                suggestions.iter().any(|name| name.ends_with("feature-login")),
+                "suggestions: {suggestions:?}"
+            );
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+    assert!(err.to_string().contains("did you mean"), "message: {err}");
+    assert!(!root.join("worktrees/source/typo").exists());
+
+    remove_repo(&repo.name).expect("remove repo");
+    cleanup_root(&root);
+}
+
 #[test]
 fn create_worktree_from_source_branch() {
     let _guard = TEST_MUTEX.lock().expect("lock test mutex");
@@ -194,6 +484,247 @@ fn create_worktree_from_source_branch() {
     cleanup_root(&root);
 }
 
+#[test]
+fn create_worktree_from_another_worktree_name() {
+    let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+    let root = unique_root("create_worktree_from_another_worktree_name");
+    let _env = EnvGuard::set("BBQ_ROOT_DIR", &root);
+
+    let src_repo = root.join("source");
+    init_repo(&src_repo);
+
+    let repo = checkout_repo(src_repo.to_str().expect("repo path")).expect("checkout repo");
+    let first = create_worktree_from(&repo, "alpha", "user/alpha", "HEAD")
+        .expect("create first worktree");
+    run_git(
+        &[
+            "-c",
+            "user.name=bbq-test",
+            "-c",
+            "user.email=bbq-test@example.com",
+            "commit",
+            "--quiet",
+            "--allow-empty",
+            "-m",
+            "alpha work",
+        ],
+        &first.path,
+    );
+    let alpha_head = run_git_capture(&["rev-parse", "HEAD"], &first.path);
+
+    let second = create_worktree_from(&repo, "beta", "user/beta", "alpha")
+        .expect("create worktree from worktree name");
+    let merge_base = run_git_capture(&["merge-base", "HEAD", &alpha_head], &second.path);
+    assert_eq!(merge_base, alpha_head);
+
+    remove_worktree(&repo, "beta").expect("remove second worktree");
+    remove_worktree(&repo, "alpha").expect("remove first worktree");
+    remove_repo(&repo.name).expect("remove repo");
+    cleanup_root(&root);
+}
+
+#[test]
+fn create_worktree_reports_branch_checked_out_elsewhere() {
+    let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+    let root = unique_root("create_worktree_reports_branch_checked_out_elsewhere");
+    let _env = EnvGuard::set("BBQ_ROOT_DIR", &root);
+
+    let src_repo = root.join("source");
+    init_repo(&src_repo);
+
+    let repo = checkout_repo(src_repo.to_str().expect("repo path")).expect("checkout repo");
+    create_worktree_from(&repo, "alpha", "feature-x", "HEAD").expect("create alpha");
+
+    let err = create_worktree_from(&repo, "beta", "feature-x", "HEAD")
+        .expect_err("branch in use should block create");
+    match err {
+        BbqError::BranchAlreadyCheckedOut { branch, worktree } => {
+            assert_eq!(branch, "feature-x");
+            assert_eq!(worktree, "alpha");
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+    let err = create_worktree_with_name(&repo, "gamma", "feature-x")
+        .expect_err("branch in use should block create");
+    assert!(matches!(err, BbqError::BranchAlreadyCheckedOut { .. }));
+
+    remove_worktree(&repo, "alpha").expect("remove worktree");
+    remove_repo(&repo.name).expect("remove repo");
+    cleanup_root(&root);
+}
+
+#[test]
+fn checkout_repo_replaces_interrupted_clone() {
+    let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+    let root = unique_root("checkout_repo_replaces_interrupted_clone");
+    let _env = EnvGuard::set("BBQ_ROOT_DIR", &root);
+
+    let src_repo = root.join("source");
+    init_repo(&src_repo);
+
+    // What an interrupted clone leaves behind: a half-written bare repo plus the marker.
+    ensure_root_dirs().expect("root dirs");
+    let partial = repos_root().expect("repos root").join("source.git");
+    fs::create_dir_all(&partial).expect("create partial clone");
+    fs::write(partial.join("HEAD"), "ref: refs/heads/main\n").expect("write HEAD");
+    let marker = repos_root().expect("repos root").join("source.git.bbq-cloning");
+    fs::write(&marker, "").expect("write marker");
+    assert!(list_repos().expect("list repos").is_empty());
+
+    // While another process holds the marker, its clone is still running and is left alone.
+    let running = fs::File::open(&marker).expect("open marker");
+    running.lock().expect("lock marker");
+    let err = checkout_repo(src_repo.to_str().expect("repo path"))
+        .expect_err("clone in progress should not be replaced");
+    assert!(matches!(err, BbqError::CloneInProgress(_)));
+    assert!(partial.join("HEAD").is_file());
+    drop(running);
+
+    let repo = checkout_repo(src_repo.to_str().expect("repo path")).expect("re-clone repo");
+    assert!(!marker.exists());
+    let names: Vec<String> = list_repos()
+        .expect("list repos")
+        .into_iter()
+        .map(|repo| repo.name)
+        .collect();
+    assert_eq!(names, vec!["source".to_string()]);
+
+    let err = checkout_repo(src_repo.to_str().expect("repo path"))
+        .expect_err("complete clone should not be replaced");
+    assert!(matches!(err, BbqError::RepoAlreadyExists(_)));
+
+    remove_repo(&repo.name).expect("remove repo");
+    cleanup_root(&root);
+}
+
+#[test]
+fn create_worktree_reuses_empty_leftover_dir() {
+    let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+    let root = unique_root("create_worktree_reuses_empty_leftover_dir");
+    let _env = EnvGuard::set("BBQ_ROOT_DIR", &root);
+
+    let src_repo = root.join("source");
+    init_repo(&src_repo);
+
+    let repo = checkout_repo(src_repo.to_str().expect("repo path")).expect("checkout repo");
+    let leftover = worktrees_root().expect("worktrees root").join(&repo.name).join("alpha");
+    fs::create_dir_all(&leftover).expect("create leftover dir");
+    let worktree = create_worktree_from(&repo, "alpha", "user/alpha", "HEAD")
+        .expect("create worktree over empty dir");
+    assert!(worktree.path.join(".git").exists());
+
+    let occupied = worktrees_root().expect("worktrees root").join(&repo.name).join("beta");
+    fs::create_dir_all(&occupied).expect("create occupied dir");
+    fs::write(occupied.join("notes.txt"), "keep me").expect("write file");
+    let err = create_worktree_from(&repo, "beta", "user/beta", "HEAD")
+        .expect_err("non-empty dir should block create");
+    assert!(matches!(err, BbqError::WorktreeAlreadyExists(_)));
+    assert!(occupied.join("notes.txt").exists());
+
+    remove_worktree(&repo, "alpha").expect("remove worktree");
+    remove_repo(&repo.name).expect("remove repo");
+    cleanup_root(&root);
+}
+
+#[test]
+fn copy_untracked_files_brings_local_config_without_overwriting() {
+    let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+    let root = unique_root("copy_untracked_files_brings_local_config_without_overwriting");
+    let _env = EnvGuard::set("BBQ_ROOT_DIR", &root);
+
+    let src_repo = root.join("source");
+    init_repo(&src_repo);
+
+    let repo = checkout_repo(src_repo.to_str().expect("repo path")).expect("checkout repo");
+    let alpha = create_worktree_from(&repo, "alpha", "user/alpha", "HEAD").expect("create alpha");
+    fs::write(alpha.path.join(".env"), "TOKEN=1").expect("write env");
+    fs::create_dir_all(alpha.path.join("config")).expect("create config dir");
+    fs::write(alpha.path.join("config/local.toml"), "debug = true").expect("write config");
+    // Build output and dependencies stay behind, even inside a copied directory.
+    fs::create_dir_all(alpha.path.join("target/debug")).expect("create target dir");
+    fs::write(alpha.path.join("target/debug/app"), "binary").expect("write build output");
+    fs::create_dir_all(alpha.path.join("config/node_modules/pkg")).expect("create modules dir");
+    fs::write(alpha.path.join("config/node_modules/pkg/index.js"), "").expect("write module");
+    // Untracked in alpha, but tracked in the new worktree.
+    run_git(&["rm", "--cached", "--quiet", "README.md"], &alpha.path);
+    fs::write(alpha.path.join("README.md"), "changed").expect("write README");
+
+    let beta = create_worktree_from(&repo, "beta", "user/beta", "HEAD").expect("create beta");
+    let copied = copy_untracked_files(&alpha, &beta).expect("copy untracked files");
+    assert_eq!(copied, 2);
+    assert_eq!(fs::read_to_string(beta.path.join(".env")).expect("read env"), "TOKEN=1");
+    assert!(beta.path.join("config/local.toml").exists());
+    assert!(!beta.path.join("target").exists());
+    assert!(!beta.path.join("config/node_modules").exists());
+    assert_eq!(fs::read_to_string(beta.path.join("README.md")).expect("read README"), "hello");
+
+    remove_worktree_with_force(&repo, "alpha", true).expect("remove alpha");
+    remove_worktree_with_force(&repo, "beta", true).expect("remove beta");
+    remove_repo(&repo.name).expect("remove repo");
+    cleanup_root(&root);
+}
+
+#[test]
+fn create_worktree_rejects_path_inside_another_worktree() {
+    let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+    let root = unique_root("create_worktree_rejects_path_inside_another_worktree");
+    let _env = EnvGuard::set("BBQ_ROOT_DIR", &root);
+
+    let src_repo = root.join("source");
+    init_repo(&src_repo);
+
+    let repo = checkout_repo(src_repo.to_str().expect("repo path")).expect("checkout repo");
+    let outer = create_worktree_from(&repo, "alpha", "user/alpha", "HEAD")
+        .expect("create outer worktree");
+
+    let err = create_worktree_from(&repo, "alpha/nested", "user/nested", "HEAD")
+        .expect_err("nested worktree should be rejected");
+    assert!(matches!(err, BbqError::WorktreePathNested { .. }));
+    assert!(!outer.path.join("nested").exists());
+
+    remove_worktree(&repo, "alpha").expect("remove outer worktree");
+    remove_repo(&repo.name).expect("remove repo");
+    cleanup_root(&root);
+}
+
+#[test]
+fn copy_worktree_templates_skips_existing_files() {
+    let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+    let root = unique_root("copy_worktree_templates_skips_existing_files");
+    let _env = EnvGuard::set("BBQ_ROOT_DIR", &root);
+    let home = root.join("home");
+    fs::create_dir_all(&home).expect("create home");
+    let _home_env = EnvGuard::set("HOME", &home);
+    let _xdg_env = EnvGuard::unset("XDG_CONFIG_HOME");
+
+    let src_repo = root.join("source");
+    init_repo(&src_repo);
+    let repo = checkout_repo(src_repo.to_str().expect("repo path")).expect("checkout repo");
+
+    let templates = home.join(".bbq").join("templates");
+    let repo_templates = templates.join("source");
+    fs::create_dir_all(repo_templates.join("config")).expect("create repo templates");
+    fs::write(repo_templates.join("config").join(".env"), "PORT=3000\n").expect("write .env");
+    fs::write(repo_templates.join("README.md"), "template readme").expect("write README");
+    let global_templates = templates.join(GLOBAL_TEMPLATE_DIR);
+    fs::create_dir_all(&global_templates).expect("create global templates");
+    fs::write(global_templates.join(".editorconfig"), "root = true\n").expect("write global");
+    fs::write(global_templates.join(".env.example"), "global").expect("write global env");
+
+    let worktree = create_worktree(&repo, "templated").expect("create worktree");
+    let copied = copy_worktree_templates(&repo, &worktree).expect("copy templates");
+    assert_eq!(copied, 3);
+    let read = |relative: &str| fs::read_to_string(worktree.path.join(relative)).expect("read");
+    assert_eq!(read("config/.env"), "PORT=3000\n");
+    assert_eq!(read(".editorconfig"), "root = true\n");
+    assert_eq!(read(".env.example"), "global");
+    assert_eq!(read("README.md"), "hello");
+
+    remove_worktree_with_force(&repo, "templated", true).expect("remove worktree");
+    remove_repo(&repo.name).expect("remove repo");
+    cleanup_root(&root);
+}
+
 #[test]
 fn create_worktree_from_origin_branch_tracks_upstream() {
     let _guard = TEST_MUTEX.lock().expect("lock test mutex");
@@ -231,6 +762,61 @@ fn create_worktree_from_origin_branch_tracks_upstream() {
     cleanup_root(&root);
 }
 
+#[test]
+fn create_worktree_from_with_no_track_leaves_upstream_unset() {
+    let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+    let root = unique_root("create_worktree_from_with_no_track_leaves_upstream_unset");
+    let _env = EnvGuard::set("BBQ_ROOT_DIR", &root);
+
+    let src_repo = root.join("source");
+    init_repo(&src_repo);
+    run_git(&["branch", "someuser/foo"], &src_repo);
+
+    let repo = checkout_repo(src_repo.to_str().expect("repo path")).expect("checkout repo");
+    let options = WorktreeOptions {
+        no_track: true,
+        ..WorktreeOptions::default()
+    };
+    let worktree = create_worktree_from_with_options(
+        &repo,
+        "untracked-test",
+        "someuser/foo",
+        "someuser/foo",
+        &options,
+    )
+    .expect("create worktree without tracking");
+
+    let output = Command::new("git")
+        .args(["rev-parse", "--abbrev-ref", "--symbolic-full-name", "@{u}"])
+        .current_dir(&worktree.path)
+        .output()
+        .expect("run git");
+    assert!(!output.status.success(), "branch should have no upstream");
+
+    // A new branch started from a remote branch would otherwise track it.
+    let worktree =
+        create_worktree_from_with_options(&repo, "fresh", "fresh", "origin/someuser/foo", &options)
+            .expect("create new branch without tracking");
+    assert_eq!(current_branch(&worktree.path), "fresh");
+    let output = Command::new("git")
+        .args(["rev-parse", "--abbrev-ref", "--symbolic-full-name", "@{u}"])
+        .current_dir(&worktree.path)
+        .output()
+        .expect("run git");
+    assert!(!output.status.success(), "new branch should have no upstream");
+    let merge = Command::new("git")
+        .args(["config", "--get", "branch.fresh.merge"])
+        .current_dir(&worktree.path)
+        .output()
+        .expect("run git");
+    assert!(!merge.status.success(), "new branch should have no merge config");
+
+    remove_worktree(&repo, "fresh").expect("remove new-branch worktree");
+    remove_worktree(&repo, "untracked-test").expect("remove worktree");
+    remove_repo(&repo.name).expect("remove repo");
+    cleanup_root(&root);
+}
+
 #[test]
 fn create_worktree_with_remote_branch() {
     let _guard = TEST_MUTEX.lock().expect("lock test mutex");
@@ -423,6 +1009,267 @@ fn default_branch_falls_back_to_head_when_origin_head_missing() {
     cleanup_root(&root);
 }
 
+#[test]
+fn checkout_repo_with_custom_origin_name() {
+    let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+    let root = unique_root("checkout_repo_with_custom_origin_name");
+    let _env = EnvGuard::set("BBQ_ROOT_DIR", &root);
+
+    let src_repo = root.join("source");
+    init_repo(&src_repo);
+    let branch = current_branch(&src_repo);
+
+    let options = CloneOptions {
+        origin: Some("upstream".to_string()),
+        ..CloneOptions::default()
+    };
+    let repo = checkout_repo_with_options(src_repo.to_str().expect("repo path"), None, &options)
+        .expect("checkout repo");
+    assert_eq!(run_git_capture(&["remote"], &repo.path), "upstream");
+    assert_eq!(
+        default_branch(&repo).expect("default branch"),
+        Some(format!("upstream/{branch}"))
+    );
+
+    let worktree = create_worktree_from(&repo, "fork", "fork", &branch).expect("create worktree");
+    let remote = run_git_capture(&["config", "branch.fork.remote"], &worktree.path);
+    assert_eq!(remote, "upstream");
+
+    remove_worktree(&repo, "fork").expect("remove worktree");
+    remove_repo(&repo.name).expect("remove repo");
+    cleanup_root(&root);
+}
+
+#[test]
+fn mirror_clone_creates_worktrees_from_the_default_branch() {
+    let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+    let root = unique_root("mirror_clone_creates_worktrees_from_the_default_branch");
+    let _env = EnvGuard::set("BBQ_ROOT_DIR", &root);
+
+    let src_repo = root.join("source");
+    init_repo(&src_repo);
+    let branch = current_branch(&src_repo);
+
+    let options = CloneOptions {
+        mirror: true,
+        ..CloneOptions::default()
+    };
+    let repo = checkout_repo_with_options(src_repo.to_str().expect("repo path"), None, &options)
+        .expect("checkout mirror");
+    assert_eq!(
+        run_git_capture(&["config", "remote.origin.fetch"], &repo.path),
+        "+refs/*:refs/*"
+    );
+    let default = default_branch(&repo)
+        .expect("default branch")
+        .expect("has default branch");
+    assert_eq!(default, format!("origin/{branch}"));
+
+    let worktree = create_worktree_from(&repo, "mirrored", "mirrored", &default)
+        .expect("create worktree from default branch");
+    assert!(worktree.path.join("README.md").is_file());
+    let main = create_worktree_with_name(&repo, "main", &branch).expect("check out default");
+
+    // Fetching must not trip over the default branch being checked out in a worktree.
+    fs::write(src_repo.join("later.txt"), "later").expect("write file");
+    run_git(&["add", "later.txt"], &src_repo);
+    run_git(&["commit", "--quiet", "-m", "later"], &src_repo);
+    fetch_all(&repo).expect("fetch mirror");
+    let remote_ref = format!("refs/remotes/origin/{branch}");
+    assert_eq!(
+        run_git_capture(&["rev-parse", &remote_ref], &repo.path),
+        run_git_capture(&["rev-parse", "HEAD"], &src_repo)
+    );
+
+    remove_worktree(&repo, "mirrored").expect("remove worktree");
+    remove_worktree(&repo, &main.display_name()).expect("remove main worktree");
+    remove_repo(&repo.name).expect("remove repo");
+    cleanup_root(&root);
+}
+
+#[test]
+fn checkout_repo_with_reference_shares_objects() {
+    let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+    let root = unique_root("checkout_repo_with_reference_shares_objects");
+    let _env = EnvGuard::set("BBQ_ROOT_DIR", &root);
+
+    let src_repo = root.join("source");
+    init_repo(&src_repo);
+    let source = src_repo.to_str().expect("repo path");
+
+    let base = checkout_repo(source).expect("checkout base repo");
+    let options = CloneOptions {
+        reference: Some(base.path.clone()),
+        ..CloneOptions::default()
+    };
+    let fork = checkout_repo_with_options(source, Some("fork"), &options).expect("checkout fork");
+
+    let alternates = fs::read_to_string(fork.path.join("objects/info/alternates"))
+        .expect("read alternates");
+    let expected = fs::canonicalize(base.path.join("objects")).expect("canonical objects dir");
+    assert_eq!(
+        fs::canonicalize(alternates.trim()).expect("canonical alternate"),
+        expected
+    );
+
+    cleanup_root(&root);
+}
+
+#[test]
+fn checkout_repo_writes_commit_graph_unless_disabled() {
+    let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+    let root = unique_root("checkout_repo_writes_commit_graph_unless_disabled");
+    let home = root.join("home");
+    fs::create_dir_all(&home).expect("create home");
+    let _home_env = EnvGuard::set("HOME", &home);
+    let _bbq_env = EnvGuard::set("BBQ_ROOT_DIR", &root);
+
+    let src_repo = root.join("source");
+    init_repo(&src_repo);
+    let source = src_repo.to_str().expect("repo path");
+
+    let repo = checkout_repo(source).expect("checkout repo");
+    assert!(repo.path.join("objects/info/commit-graph").is_file());
+
+    write_config(&home, "commit_graph = false");
+    let repo = checkout_repo_with_name(source, "no-graph").expect("checkout repo");
+    assert!(!repo.path.join("objects/info/commit-graph").exists());
+
+    cleanup_root(&root);
+}
+
+#[test]
+fn create_worktree_in_empty_repo_reports_empty_repository() {
+    let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+    let root = unique_root("create_worktree_in_empty_repo_reports_empty_repository");
+    let _env = EnvGuard::set("BBQ_ROOT_DIR", &root);
+
+    let src_repo = root.join("empty.git");
+    fs::create_dir_all(&src_repo).expect("create empty repo dir");
+    run_git(&["init", "--quiet", "--bare"], &src_repo);
+
+    let repo = checkout_repo(src_repo.to_str().expect("repo path")).expect("checkout repo");
+    let err = create_worktree(&repo, "main").expect_err("empty repo rejected");
+    assert!(matches!(err, BbqError::EmptyRepository(_)), "unexpected error: {err}");
+    let err = create_worktree_from(&repo, "feature", "feature", "main")
+        .expect_err("empty repo rejected");
+    assert!(matches!(err, BbqError::EmptyRepository(_)), "unexpected error: {err}");
+    assert!(err.to_string().contains("initial commit"));
+    assert!(list_worktrees(&repo).expect("list worktrees").is_empty());
+
+    remove_repo(&repo.name).expect("remove repo");
+    cleanup_root(&root);
+}
+
+#[test]
+fn checkout_repo_retries_transient_network_failures() {
+    let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+    let root = unique_root("checkout_repo_retries_transient_network_failures");
+    let _env = EnvGuard::set("BBQ_ROOT_DIR", &root);
+    let home = root.join("home");
+    fs::create_dir_all(&home).expect("create home");
+    let _home_env = EnvGuard::set("HOME", &home);
+
+    let src_repo = root.join("source");
+    init_repo(&src_repo);
+
+    let real_git = run_git_capture(&["--exec-path"], &root);
+    let real_git = Path::new(real_git.trim()).join("git");
+    let counter = root.join("clone-attempts");
+    let bin_dir = root.join("bin");
+    fs::create_dir_all(&bin_dir).expect("create bin dir");
+    write_stub_command(
+        &bin_dir,
+        "git",
+        &format!(
+            "for arg in \"$@\"; do\n  if [ \"$arg\" = clone ]; then\n    echo x >> '{counter}'\n    if [ \"$(wc -l < '{counter}')\" -le 2 ]; then\n      echo 'fatal: unable to access: Could not resolve host: example.com' >&2\n      exit 128\n    fi\n  fi\ndone\nexec '{git}' \"$@\"",
+            counter = counter.display(),
+            git = real_git.display()
+        ),
+    );
+    let path = format!("{}:{}", bin_dir.display(), std::env::var("PATH").unwrap_or_default());
+    let _path_env = EnvGuard::set("PATH", Path::new(&path));
+
+    let repo = checkout_repo(src_repo.to_str().expect("repo path")).expect("checkout repo");
+    assert!(repo.path.exists());
+    let attempts = fs::read_to_string(&counter).expect("read counter");
+    assert_eq!(attempts.lines().count(), 3);
+
+    remove_repo(&repo.name).expect("remove repo");
+    cleanup_root(&root);
+}
+
+#[test]
+fn spawned_git_inherits_auth_environment() {
+    let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+    let root = unique_root("spawned_git_inherits_auth_environment");
+    let _env = EnvGuard::set("BBQ_ROOT_DIR", &root);
+    let ssh_command = root.join("ssh-wrapper");
+    let _ssh_env = EnvGuard::set("GIT_SSH_COMMAND", &ssh_command);
+
+    let src_repo = root.join("source");
+    init_repo(&src_repo);
+
+    let real_git = run_git_capture(&["--exec-path"], &root);
+    let real_git = Path::new(real_git.trim()).join("git");
+    let log = root.join("ssh-env");
+    let bin_dir = root.join("bin");
+    fs::create_dir_all(&bin_dir).expect("create bin dir");
+    write_stub_command(
+        &bin_dir,
+        "git",
+        &format!(
+            "echo \"$GIT_SSH_COMMAND\" >> '{log}'\nexec '{git}' \"$@\"",
+            log = log.display(),
+            git = real_git.display()
+        ),
+    );
+    let path = format!("{}:{}", bin_dir.display(), std::env::var("PATH").unwrap_or_default());
+    let _path_env = EnvGuard::set("PATH", Path::new(&path));
+
+    let repo = checkout_repo(src_repo.to_str().expect("repo path")).expect("checkout repo");
+    let seen = fs::read_to_string(&log).expect("read ssh env log");
+    assert!(!seen.is_empty());
+    for line in seen.lines() {
+        assert_eq!(line, ssh_command.display().to_string());
+    }
+
+    remove_repo(&repo.name).expect("remove repo");
+    cleanup_root(&root);
+}
+
+#[test]
+fn list_repos_skips_ignored_and_hidden_dirs() {
+    let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+    let root = unique_root("list_repos_skips_ignored_and_hidden_dirs");
+    let home = root.join("home");
+    fs::create_dir_all(&home).expect("create home");
+    let _home_env = EnvGuard::set("HOME", &home);
+    let _bbq_env = EnvGuard::set("BBQ_ROOT_DIR", &root);
+
+    write_config(&home, "ignore_repos = [\"backups\", 'scratch', \"old, 2024\"]");
+
+    let src_repo = root.join("source");
+    init_repo(&src_repo);
+    checkout_repo(src_repo.to_str().expect("repo path")).expect("checkout repo");
+
+    let repos_dir = repos_root().expect("repos root");
+    for name in ["backups", "scratch.git", ".cache", "old, 2024.git", "notes"] {
+        let dir = repos_dir.join(name);
+        fs::create_dir_all(&dir).expect("create junk dir");
+        fs::write(dir.join("HEAD"), "ref: refs/heads/main\n").expect("write HEAD");
+    }
+
+    let names: Vec<String> = list_repos()
+        .expect("list repos")
+        .into_iter()
+        .map(|repo| repo.name)
+        .collect();
+    assert_eq!(names, vec!["notes".to_string(), "source".to_string()]);
+
+    cleanup_root(&root);
+}
+
 #[test]
 fn paths_prefers_env_var_over_config() {
     let _guard = TEST_MUTEX.lock().expect("lock test mutex");
@@ -475,6 +1322,149 @@ fn paths_empty_root_dir_falls_back_to_config_root() {
     cleanup_root(&root);
 }
 
+#[test]
+fn xdg_migration_moves_legacy_layout() {
+    let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+    let root = unique_root("xdg_migration_moves_legacy_layout");
+    let home = root.join("home");
+    fs::create_dir_all(&home).expect("create home");
+    let _home_env = EnvGuard::set("HOME", &home);
+    let _bbq_env = EnvGuard::unset("BBQ_ROOT_DIR");
+    let config_home = root.join("xdg-config");
+    let data_home = root.join("xdg-data");
+    let _config_env = EnvGuard::set("XDG_CONFIG_HOME", &config_home);
+    let _data_env = EnvGuard::set("XDG_DATA_HOME", &data_home);
+
+    write_config(&home, "theme = \"blue\"");
+    fs::write(home.join(".bbq/restore.toml"), "repo = \"source\"").expect("write restore");
+    let src_repo = root.join("source");
+    init_repo(&src_repo);
+    let repo = checkout_repo(src_repo.to_str().expect("repo path")).expect("checkout repo");
+    create_worktree(&repo, "feature").expect("create worktree");
+    assert_eq!(bbq_root().expect("bbq_root"), home.join(".bbq"));
+
+    let moves = xdg_migration_plan().expect("plan");
+    assert_eq!(moves.len(), 4);
+    apply_xdg_migration(&moves).expect("apply migration");
+
+    assert_eq!(config_root().expect("config root"), config_home.join("bbq"));
+    assert!(config_path().expect("config path").is_file());
+    assert!(config_home.join("bbq/restore.toml").is_file());
+    assert_eq!(repos_root().expect("repos root"), data_home.join("bbq/repos"));
+    assert!(home.join(".bbq").join(MIGRATION_NOTE).is_file());
+    assert!(!home.join(".bbq/repos").exists());
+
+    ensure_root_dirs().expect("ensure root dirs");
+    let repo = resolve_repo("source").expect("resolve moved repo");
+    repair_worktrees(&repo).expect("repair worktrees");
+    let worktree = find_worktree(&repo, "feature").expect("find moved worktree");
+    assert_eq!(worktree.path, data_home.join("bbq/worktrees/source/feature"));
+    run_git(&["status", "--short"], &worktree.path);
+
+    cleanup_root(&root);
+}
+
+#[test]
+fn xdg_migration_rolls_back_and_stray_xdg_dirs_are_ignored() {
+    let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+    let root = unique_root("xdg_migration_rolls_back_and_stray_xdg_dirs_are_ignored");
+    let home = root.join("home");
+    fs::create_dir_all(&home).expect("create home");
+    let _home_env = EnvGuard::set("HOME", &home);
+    let _bbq_env = EnvGuard::unset("BBQ_ROOT_DIR");
+    let config_home = root.join("xdg-config");
+    let data_home = root.join("xdg-data");
+    let _config_env = EnvGuard::set("XDG_CONFIG_HOME", &config_home);
+    let _data_env = EnvGuard::set("XDG_DATA_HOME", &data_home);
+
+    write_config(&home, "theme = \"blue\"");
+    fs::create_dir_all(home.join(".bbq/repos")).expect("create repos");
+    fs::create_dir_all(home.join(".bbq/worktrees")).expect("create worktrees");
+
+    // An XDG directory that appears on its own doesn't take over from `~/.bbq`.
+    fs::create_dir_all(config_home.join("bbq")).expect("create stray config dir");
+    fs::create_dir_all(data_home.join("bbq")).expect("create stray data dir");
+    assert_eq!(config_root().expect("config root"), home.join(".bbq"));
+    assert_eq!(bbq_root().expect("bbq_root"), home.join(".bbq"));
+    fs::remove_dir_all(&config_home).expect("remove stray config dir");
+    fs::remove_dir_all(&data_home).expect("remove stray data dir");
+
+    // A failure after config.toml has moved puts it back.
+    let moves = xdg_migration_plan().expect("plan");
+    fs::create_dir_all(&data_home).expect("create data home");
+    fs::write(data_home.join("bbq"), "not a directory").expect("block data dir");
+    assert!(apply_xdg_migration(&moves).is_err());
+    assert!(home.join(".bbq/config.toml").is_file());
+    assert!(home.join(".bbq/repos").is_dir());
+    assert!(!config_home.exists());
+    assert!(!home.join(".bbq").join(MIGRATION_NOTE).exists());
+    assert_eq!(config_root().expect("config root"), home.join(".bbq"));
+
+    cleanup_root(&root);
+}
+
+#[test]
+fn state_lock_is_an_os_lock_on_a_lasting_file() {
+    let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+    let root = unique_root("state_lock_is_an_os_lock_on_a_lasting_file");
+    let home = root.join("home");
+    let _home_env = EnvGuard::set("HOME", &home);
+    let lock_path = home.join(".bbq").join(bbq::lock::LOCK_FILE_NAME);
+    fs::create_dir_all(lock_path.parent().expect("lock dir")).expect("create lock dir");
+
+    // A file left by a crashed holder doesn't block anyone.
+    fs::write(&lock_path, "999999\n").expect("write leftover lock file");
+    let lock = bbq::acquire_state_lock().expect("take lock over leftover file");
+    let holder = fs::read_to_string(&lock_path).expect("read lock file");
+    assert_eq!(holder.trim(), std::process::id().to_string());
+
+    // Another open of the file can't lock it while it is held, and can once it's released.
+    let other = fs::File::open(&lock_path).expect("open lock file");
+    assert!(other.try_lock().is_err());
+    drop(lock);
+    assert!(lock_path.is_file());
+    other.try_lock().expect("lock released on drop");
+
+    cleanup_root(&root);
+}
+
+#[test]
+fn aborting_a_script_stops_everything_it_started() {
+    let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+    let root = unique_root("aborting_a_script_stops_everything_it_started");
+    let script = root.join(POST_CREATE_SCRIPT_RELATIVE);
+    fs::create_dir_all(script.parent().expect("script dir")).expect("create script dir");
+    fs::write(&script, "#!/bin/sh\nsleep 30 &\necho $! > sleep.pid\nwait\n").expect("write");
+    let worktree = Worktree {
+        path: root.clone(),
+        branch: None,
+        head: None,
+    };
+
+    enable_abort();
+    assert!(!abort_running_command(), "nothing is running yet");
+    let started = Instant::now();
+    let running =
+        std::thread::spawn(move || run_post_create_script(&worktree, ScriptOutput::Capture));
+    let pid_file = root.join("sleep.pid");
+    while !fs::read_to_string(&pid_file).is_ok_and(|pid| pid.ends_with('\n')) {
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    assert!(abort_running_command());
+
+    let result = running.join().expect("script thread");
+    assert!(matches!(result, Err(BbqError::Aborted)), "{result:?}");
+    assert!(started.elapsed() < Duration::from_secs(10));
+    let pid = fs::read_to_string(&pid_file).expect("read pid");
+    let alive = Command::new("kill")
+        .args(["-0", pid.trim()])
+        .status()
+        .expect("run kill");
+    assert!(!alive.success(), "the script's sleep outlived the abort");
+
+    cleanup_root(&root);
+}
+
 #[test]
 fn ensure_root_dirs_creates_structure() {
     let _guard = TEST_MUTEX.lock().expect("lock test mutex");
@@ -592,8 +1582,113 @@ impl Drop for EnvGuard {
     }
 }
 
+fn write_stub_command(dir: &Path, name: &str, body: &str) {
+    let path = dir.join(name);
+    fs::write(&path, format!("#!/bin/sh\n{}\n", body)).expect("write stub command");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = fs::metadata(&path).expect("metadata").permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(&path, perms).expect("set permissions");
+    }
+}
+
 fn write_config(home: &Path, contents: &str) {
     let config_dir = home.join(".bbq");
     fs::create_dir_all(&config_dir).expect("create config dir");
     fs::write(config_dir.join("config.toml"), contents).expect("write config");
 }
+
+#[test]
+fn list_branches_includes_local_and_remote_branches() {
+    let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+    let root = unique_root("list_branches_includes_local_and_remote_branches");
+    let _env = EnvGuard::set("BBQ_ROOT_DIR", &root);
+
+    let src_repo = root.join("source");
+    init_repo(&src_repo);
+    let branch = current_branch(&src_repo);
+    run_git(&["branch", "release"], &src_repo);
+
+    let repo = checkout_repo(src_repo.to_str().expect("repo path")).expect("checkout repo");
+    let worktree = create_worktree(&repo, "feature").expect("create worktree");
+
+    let branches = list_branches(&repo).expect("list branches");
+    assert!(branches.contains(&branch));
+    assert!(branches.contains(&"release".to_string()));
+    assert!(branches.contains(&"feature".to_string()));
+    assert!(!branches.iter().any(|name| name.contains("HEAD")));
+
+    remove_worktree(&repo, &worktree.display_name()).expect("remove worktree");
+    remove_repo(&repo.name).expect("remove repo");
+    cleanup_root(&root);
+}
+
+#[test]
+fn find_repo_by_remote_ignores_github_case_and_protocol() {
+    let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+    let root = unique_root("find_repo_by_remote_ignores_github_case_and_protocol");
+    let _env = EnvGuard::set("BBQ_ROOT_DIR", &root);
+
+    let src_repo = root.join("source");
+    init_repo(&src_repo);
+    let repo = checkout_repo(src_repo.to_str().expect("repo path")).expect("checkout repo");
+    run_git(
+        &["remote", "set-url", "origin", "https://github.com/Acme/Widgets.git"],
+        &repo.path,
+    );
+
+    for source in [
+        "acme/widgets",
+        "https://GitHub.com/acme/WIDGETS",
+        "git@github.com:ACME/widgets.git",
+    ] {
+        let found = find_repo_by_remote(source).expect("find repo by remote");
+        assert_eq!(found.map(|repo| repo.name).as_deref(), Some("source"), "{source}");
+    }
+    let other = find_repo_by_remote("https://gitlab.com/Acme/Widgets").expect("find repo");
+    assert!(other.is_none());
+
+    cleanup_root(&root);
+}
+
+#[test]
+fn worktree_config_stays_in_its_worktree() {
+    let _guard = TEST_MUTEX.lock().expect("lock test mutex");
+    let root = unique_root("worktree_config_stays_in_its_worktree");
+    let _env = EnvGuard::set("BBQ_ROOT_DIR", &root);
+
+    let src_repo = root.join("source");
+    init_repo(&src_repo);
+    let repo = checkout_repo(src_repo.to_str().expect("repo path")).expect("checkout repo");
+    let work = create_worktree(&repo, "work").expect("create work worktree");
+    let home = create_worktree(&repo, "home").expect("create home worktree");
+
+    set_worktree_config(&work, "user.email", "me@work.example").expect("set work email");
+    set_worktree_config(&home, "user.email", "me@home.example").expect("set home email");
+
+    assert_eq!(run_git_capture(&["config", "user.email"], &work.path), "me@work.example");
+    assert_eq!(run_git_capture(&["config", "user.email"], &home.path), "me@home.example");
+    let shared = repo.path.join("config");
+    let output = Command::new("git")
+        .args(["config", "--file"])
+        .arg(&shared)
+        .args(["--get", "user.email"])
+        .output()
+        .expect("read shared config");
+    assert!(!output.status.success(), "user.email leaked into the shared config");
+    assert_eq!(
+        run_git_capture(&["rev-parse", "--is-bare-repository"], &repo.path),
+        "true"
+    );
+    assert_eq!(
+        run_git_capture(&["rev-parse", "--is-bare-repository"], &work.path),
+        "false"
+    );
+
+    remove_worktree(&repo, "work").expect("remove work worktree");
+    remove_worktree(&repo, "home").expect("remove home worktree");
+    remove_repo(&repo.name).expect("remove repo");
+    cleanup_root(&root);
+}